@@ -0,0 +1,70 @@
+//! Times the hot paths of every request: serializing chat completion bodies and decoding streamed responses.
+//!
+//! Run with `cargo bench --features bench`. Every case is run for about a second after a warm-up, and the mean time
+//! per iteration is printed, so that regressions show up when comparing runs on the same machine.
+
+use groq_api_rust::__bench::{chat_completion_body, decode_transcript};
+use groq_api_rust::{messages, ChatCompletionMessage, ChatCompletionRequest};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const CHAT_STREAM_TRANSCRIPT: &str = include_str!("../fixtures/chat_stream.sse");
+const TOOL_CALL_STREAM_TRANSCRIPT: &str = include_str!("../fixtures/tool_call_stream.sse");
+
+/// Runs `f` repeatedly for about a second and prints the mean time per iteration.
+fn bench(name: &str, mut f: impl FnMut()) {
+    let warm_up = Instant::now();
+    while warm_up.elapsed() < Duration::from_millis(200) {
+        f();
+    }
+    let started = Instant::now();
+    let mut iterations = 0u32;
+    while started.elapsed() < Duration::from_secs(1) {
+        f();
+        iterations += 1;
+    }
+    println!(
+        "{:<40} {:>12?}/iter ({} iterations)",
+        name,
+        started.elapsed() / iterations,
+        iterations
+    );
+}
+
+fn main() {
+    let short = ChatCompletionRequest::new(
+        "llama-3.3-70b-versatile",
+        messages![system: "You are a helpful assistant.", user: "What is the capital of France?"],
+    );
+    let history: Vec<ChatCompletionMessage> = (0..100)
+        .flat_map(|turn| {
+            messages![
+                user: format!("Question {} about the history of Europe?", turn),
+                assistant: "An answer of a few sentences. ".repeat(10),
+            ]
+        })
+        .collect();
+    let long = ChatCompletionRequest::new("llama-3.3-70b-versatile", history);
+
+    bench("body/short", || {
+        black_box(chat_completion_body(black_box(&short)).unwrap());
+    });
+    bench("body/100 turns", || {
+        black_box(chat_completion_body(black_box(&long)).unwrap());
+    });
+    for (name, transcript) in [
+        ("chat", CHAT_STREAM_TRANSCRIPT),
+        ("tool_call", TOOL_CALL_STREAM_TRANSCRIPT),
+    ] {
+        for read_size in [16, 4096] {
+            bench(
+                &format!("decode/{} ({}-byte reads)", name, read_size),
+                || {
+                    black_box(
+                        decode_transcript(black_box(transcript.as_bytes()), read_size).unwrap(),
+                    );
+                },
+            );
+        }
+    }
+}
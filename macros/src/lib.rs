@@ -0,0 +1,186 @@
+//! Procedural macros for `groq-api-rust`, enabled with its `macros` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Error, Expr, FnArg, Ident, ItemFn, Lit, Meta, Pat,
+    ReturnType, Type,
+};
+
+/// Turns a function into a tool callable by an `Agent`.
+///
+/// The function is kept as-is, and a unit struct named after it in `PascalCase` is generated, implementing `GroqTool`:
+/// its definition uses the name of the function, its doc comment as the description, and a JSON schema of its
+/// arguments built from their types through `ToolParameter`. Calls deserialize the arguments of the model into those
+/// types, and serialize the value returned by the function. Functions returning a `Result` report their errors to the
+/// model.
+///
+/// Arguments must be owned types implementing `ToolParameter` and `Deserialize`, and the function may be async.
+///
+/// ```ignore
+/// /// Returns the current weather of a city.
+/// #[groq_tool]
+/// async fn get_weather(city: String, days: Option<u32>) -> Result<String, String> {
+///     Ok(format!("Sunny in {city}"))
+/// }
+///
+/// let agent = Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview").register(GetWeather);
+/// ```
+#[proc_macro_attribute]
+pub fn groq_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return Error::new(Span::call_site(), "`groq_tool` takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+    let function = parse_macro_input!(item as ItemFn);
+    match expand(&function) {
+        Ok(expanded) => quote!(#function #expanded).into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(function: &ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let signature = &function.sig;
+    let vis = &function.vis;
+    let function_name = &signature.ident;
+    let name = function_name.to_string();
+    let tool = Ident::new(&pascal_case(&name), function_name.span());
+    let description = doc_comment(function);
+
+    let mut idents = Vec::new();
+    let mut types = Vec::new();
+    for input in &signature.inputs {
+        let FnArg::Typed(argument) = input else {
+            return Err(Error::new(
+                input.span(),
+                "`groq_tool` does not support methods",
+            ));
+        };
+        let Pat::Ident(pattern) = argument.pat.as_ref() else {
+            return Err(Error::new(
+                argument.pat.span(),
+                "`groq_tool` arguments must be plain identifiers",
+            ));
+        };
+        if let Type::Reference(reference) = argument.ty.as_ref() {
+            return Err(Error::new(
+                reference.span(),
+                "`groq_tool` arguments must be owned types",
+            ));
+        }
+        idents.push(pattern.ident.clone());
+        types.push(argument.ty.as_ref().clone());
+    }
+    let names: Vec<String> = idents.iter().map(|ident| ident.to_string()).collect();
+
+    let call = if signature.asyncness.is_some() {
+        quote!(#function_name(#(#idents),*).await)
+    } else {
+        quote!(#function_name(#(#idents),*))
+    };
+    let to_value = quote!(::groq_api_rust::__private::serde_json::to_value);
+    let output = if returns_result(&signature.output) {
+        quote!(output
+            .map_err(|err| err.to_string())
+            .and_then(|value| #to_value(value).map_err(|err| err.to_string())))
+    } else {
+        quote!(#to_value(output).map_err(|err| err.to_string()))
+    };
+
+    Ok(quote! {
+        #[doc = concat!("The `GroqTool` generated by `#[groq_tool]` for `", #name, "`.")]
+        #[derive(Debug, Clone, Copy, Default)]
+        #vis struct #tool;
+
+        impl ::groq_api_rust::GroqTool for #tool {
+            fn definition() -> ::groq_api_rust::ToolDefinition {
+                let mut properties = ::groq_api_rust::__private::serde_json::Map::new();
+                let mut required = ::std::vec::Vec::<::groq_api_rust::__private::serde_json::Value>::new();
+                #(
+                    properties.insert(
+                        #names.to_string(),
+                        <#types as ::groq_api_rust::ToolParameter>::schema(),
+                    );
+                    if <#types as ::groq_api_rust::ToolParameter>::REQUIRED {
+                        required.push(#names.into());
+                    }
+                )*
+                ::groq_api_rust::ToolDefinition::new(
+                    #name,
+                    #description,
+                    ::groq_api_rust::__private::serde_json::json!({
+                        "type": "object",
+                        "properties": properties,
+                        "required": required,
+                    }),
+                )
+            }
+
+            fn call(
+                arguments: ::groq_api_rust::__private::serde_json::Value,
+            ) -> ::groq_api_rust::ToolFuture {
+                ::std::boxed::Box::pin(async move {
+                    #[derive(::groq_api_rust::__private::serde::Deserialize)]
+                    #[serde(crate = "::groq_api_rust::__private::serde")]
+                    struct Arguments {
+                        #(#idents: #types,)*
+                    }
+                    let Arguments { #(#idents,)* } =
+                        ::groq_api_rust::__private::serde_json::from_value(arguments)
+                            .map_err(|err| format!("invalid arguments: {err}"))?;
+                    let output = #call;
+                    #output
+                })
+            }
+        }
+    })
+}
+
+/// Returns the doc comment of the function, with the lines joined by newlines.
+fn doc_comment(function: &ItemFn) -> String {
+    let lines: Vec<String> = function
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(doc) => match &doc.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(text) => Some(text.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// Returns true if the function returns a `Result`, whose error is then reported to the model.
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,401 @@
+use crate::{
+    ChatApi, ChatCompletionMessage, ChatCompletionRequest, ChatCompletionRoles, GroqError,
+    GroqTool, ToolCall, ToolDefinition, ToolFuture,
+};
+use serde_json::Value;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+type ToolHandler = Arc<dyn Fn(Value) -> ToolFuture + Send + Sync>;
+
+/// A model answering prompts with the help of Rust functions registered as tools.
+///
+/// `run` sends the prompt with the definitions of the tools, executes the tool calls requested by the model, sends their
+/// results back and repeats until the model gives a final answer. Tool errors, unknown tools and invalid arguments are
+/// reported to the model as the result of the call, so that it can recover. The loop is bounded by `max_iterations`
+/// (10 by default) and an optional `timeout`, after which it fails with `GroqError::AgentLimitExceeded`.
+///
+/// ```no_run
+/// use groq_api_rust::{Agent, AsyncGroqClient, ToolDefinition};
+/// use serde_json::json;
+///
+/// # async fn example() -> Result<(), groq_api_rust::GroqError> {
+/// let client = AsyncGroqClient::new("my_api_key".to_string(), None).await;
+/// let weather = ToolDefinition::new(
+///     "get_weather",
+///     "Returns the current weather of a city",
+///     json!({"type": "object", "properties": {"city": {"type": "string"}}, "required": ["city"]}),
+/// );
+/// let agent = Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview")
+///     .tool(weather, |args| Ok(json!({"city": args["city"], "forecast": "sunny"})));
+/// let answer = agent.run("What's the weather like in Paris?").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Agent<'a> {
+    client: &'a dyn ChatApi,
+    request: ChatCompletionRequest,
+    system_prompt: Option<String>,
+    tools: Vec<(ToolDefinition, ToolHandler)>,
+    max_iterations: u32,
+    timeout: Option<Duration>,
+}
+
+impl<'a> Agent<'a> {
+    /// Creates a new `Agent` without tools.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+    /// * `model` - The model to use, which must support tool use.
+    pub fn new(client: &'a dyn ChatApi, model: impl Into<String>) -> Self {
+        Self::from_request(client, ChatCompletionRequest::new(model, Vec::new()))
+    }
+
+    /// Creates a new `Agent` sending every iteration with the model and parameters of the given request.
+    ///
+    /// The messages and tools of the request are ignored.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+    /// * `request` - The request holding the model, temperature, max tokens and other options.
+    pub fn from_request(client: &'a dyn ChatApi, mut request: ChatCompletionRequest) -> Self {
+        request.messages.clear();
+        request.tools.clear();
+        Self {
+            client,
+            request,
+            system_prompt: None,
+            tools: Vec::new(),
+            max_iterations: 10,
+            timeout: None,
+        }
+    }
+
+    /// Sets the system prompt sent before the prompt of every run.
+    ///
+    /// # Arguments
+    /// * `prompt` - The content of the system message.
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Registers a tool implemented by a synchronous function.
+    ///
+    /// # Arguments
+    /// * `definition` - The name, description and parameter schema of the tool.
+    /// * `handler` - The function receiving the arguments of a call and returning its result or an error message.
+    pub fn tool(
+        self,
+        definition: ToolDefinition,
+        handler: impl Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> Self {
+        self.async_tool(definition, move |args| std::future::ready(handler(args)))
+    }
+
+    /// Registers a tool implemented by an async function.
+    ///
+    /// # Arguments
+    /// * `definition` - The name, description and parameter schema of the tool.
+    /// * `handler` - The async function receiving the arguments of a call and returning its result or an error message.
+    pub fn async_tool<F>(
+        mut self,
+        definition: ToolDefinition,
+        handler: impl Fn(Value) -> F + Send + Sync + 'static,
+    ) -> Self
+    where
+        F: Future<Output = Result<Value, String>> + Send + 'static,
+    {
+        self.tools.retain(|(tool, _)| tool.name != definition.name);
+        self.tools.push((
+            definition,
+            Arc::new(move |args| Box::pin(handler(args)) as ToolFuture),
+        ));
+        self
+    }
+
+    /// Registers a `GroqTool`, such as one generated by the `#[groq_tool]` macro.
+    ///
+    /// # Arguments
+    /// * `tool` - The tool to register.
+    pub fn register<T: GroqTool + 'static>(self, tool: T) -> Self {
+        let _ = tool;
+        self.async_tool(T::definition(), T::call)
+    }
+
+    /// Sets the maximum number of model calls made by one run.
+    ///
+    /// # Arguments
+    /// * `max_iterations` - The maximum number of model calls.
+    pub fn max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Sets the maximum duration of one run, including the model calls and the tools.
+    ///
+    /// # Arguments
+    /// * `timeout` - The maximum duration.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Answers the prompt, calling tools as requested by the model, and returns the final answer.
+    ///
+    /// # Arguments
+    /// * `prompt` - The content of the user message.
+    pub async fn run(&self, prompt: &str) -> Result<String, GroqError> {
+        let started = Instant::now();
+        let mut iterations = 0;
+        let answer = match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.run_loop(prompt, &mut iterations))
+                .await
+                .unwrap_or(Ok(None))?,
+            None => self.run_loop(prompt, &mut iterations).await?,
+        };
+        answer.ok_or_else(|| GroqError::AgentLimitExceeded {
+            iterations,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    /// Runs the agent loop, returning `None` once the maximum number of iterations is reached.
+    async fn run_loop(
+        &self,
+        prompt: &str,
+        iterations: &mut u32,
+    ) -> Result<Option<String>, GroqError> {
+        let mut messages: Vec<ChatCompletionMessage> = self
+            .system_prompt
+            .iter()
+            .map(|prompt| message(ChatCompletionRoles::System, prompt.clone()))
+            .collect();
+        messages.push(message(ChatCompletionRoles::User, prompt.to_string()));
+
+        while *iterations < self.max_iterations {
+            *iterations += 1;
+            let mut request = self.request.clone();
+            request.messages = messages.clone();
+            request.tools = self.tools.iter().map(|(tool, _)| tool.clone()).collect();
+            let response = self.client.chat_completion(request).await?;
+            let Some(choice) = response.choices.into_iter().next() else {
+                return Ok(Some(String::new()));
+            };
+            let tool_calls = match choice.message.tool_calls {
+                Some(tool_calls) if !tool_calls.is_empty() => tool_calls,
+                _ => return Ok(Some(choice.message.content)),
+            };
+
+            let mut results = Vec::with_capacity(tool_calls.len());
+            for call in &tool_calls {
+                let result = self.call_tool(call).await;
+                results.push(ChatCompletionMessage {
+                    tool_call_id: Some(call.id.clone()),
+                    ..message(ChatCompletionRoles::Tool, result)
+                });
+            }
+            messages.push(ChatCompletionMessage {
+                tool_calls: Some(tool_calls),
+                ..message(ChatCompletionRoles::Assistant, choice.message.content)
+            });
+            messages.extend(results);
+        }
+        Ok(None)
+    }
+
+    /// Executes a tool call and returns the content of the message holding its result.
+    async fn call_tool(&self, call: &ToolCall) -> String {
+        let Some((_, handler)) = self
+            .tools
+            .iter()
+            .find(|(tool, _)| tool.name == call.function.name)
+        else {
+            return format!("Error: unknown tool `{}`", call.function.name);
+        };
+        let arguments = match call.function.arguments.trim() {
+            "" => Ok(Value::Object(Default::default())),
+            arguments => serde_json::from_str(arguments),
+        };
+        let result = match arguments {
+            Ok(arguments) => handler(arguments).await,
+            Err(err) => Err(format!("invalid arguments: {err}")),
+        };
+        match result {
+            Ok(Value::String(text)) => text,
+            Ok(value) => value.to_string(),
+            Err(err) => format!("Error: {err}"),
+        }
+    }
+}
+
+fn message(role: ChatCompletionRoles, content: String) -> ChatCompletionMessage {
+    ChatCompletionMessage {
+        role,
+        content: content.into(),
+        name: None,
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{completion, no_choices, server_error, Answer, ChatFn};
+    use crate::ChatCompletionResponse;
+    use serde_json::json;
+
+    /// Asks for the weather of Paris with the given arguments until a tool result is received, then answers with it.
+    fn ask_weather(
+        request: ChatCompletionRequest,
+        arguments: &str,
+    ) -> Result<ChatCompletionResponse, GroqError> {
+        assert_eq!(request.tools.len(), 1);
+        let last = request.messages.last().unwrap();
+        let message = match last.role {
+            ChatCompletionRoles::Tool => json!({"role": "assistant", "content": last.content}),
+            _ => json!({
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": arguments}
+                }]
+            }),
+        };
+        completion(
+            &request.model,
+            json!([{"index": 0, "message": message, "finish_reason": "tool_calls"}]),
+            Value::Null,
+        )
+    }
+
+    fn weather_model() -> ChatFn<impl Answer> {
+        ChatFn(|request| ask_weather(request, r#"{"city": "Paris"}"#))
+    }
+
+    fn weather() -> ToolDefinition {
+        ToolDefinition::new(
+            "get_weather",
+            "Returns the current weather of a city",
+            json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_agent_runs_tools() {
+        let client = weather_model();
+        let agent =
+            Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview").tool(weather(), |args| {
+                Ok(json!(format!(
+                    "Sunny in {}",
+                    args["city"].as_str().unwrap()
+                )))
+            });
+        assert_eq!(
+            agent.run("Weather in Paris?").await.unwrap(),
+            "Sunny in Paris"
+        );
+
+        let failing = Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview")
+            .tool(weather(), |_| Err("service unavailable".to_string()));
+        assert_eq!(
+            failing.run("Weather in Paris?").await.unwrap(),
+            "Error: service unavailable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_agent_reports_tool_errors_to_the_model() {
+        let client = weather_model();
+        let time = ToolDefinition::new("get_time", "Returns the current time", json!({}));
+        let unknown = Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview")
+            .tool(time, |_| Ok(json!("noon")));
+        assert_eq!(
+            unknown.run("Weather in Paris?").await.unwrap(),
+            "Error: unknown tool `get_weather`"
+        );
+
+        let client = ChatFn(|request| ask_weather(request, "{\"city\":"));
+        let agent = Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview")
+            .tool(weather(), |_| Ok(json!("Sunny")));
+        let answer = agent.run("Weather in Paris?").await.unwrap();
+        assert!(answer.starts_with("Error: invalid arguments: "), "{answer}");
+    }
+
+    #[tokio::test]
+    async fn test_agent_handles_empty_and_failed_responses() {
+        let client = ChatFn(|request: ChatCompletionRequest| no_choices(&request.model));
+        let agent = Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview");
+        assert_eq!(agent.run("Weather in Paris?").await.unwrap(), "");
+
+        let client = ChatFn(|_| Err(server_error()));
+        let agent = Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview");
+        assert!(matches!(
+            agent.run("Weather in Paris?").await,
+            Err(GroqError::ServerError { status: 500, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_agent_stops_after_max_iterations() {
+        // Always asks for another tool call.
+        let client = ChatFn(|mut request: ChatCompletionRequest| {
+            request.messages.truncate(1);
+            ask_weather(request, r#"{"city": "Paris"}"#)
+        });
+        let agent = Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview")
+            .tool(weather(), |_| Ok(json!("Sunny")))
+            .max_iterations(3);
+        assert!(matches!(
+            agent.run("Weather in Paris?").await,
+            Err(GroqError::AgentLimitExceeded { iterations: 3, .. })
+        ));
+    }
+
+    /// Returns the current weather of a city.
+    #[cfg(feature = "macros")]
+    #[crate::groq_tool]
+    async fn get_weather(city: String, days: Option<u32>) -> Result<String, String> {
+        match days {
+            Some(0) => Err("days must be positive".to_string()),
+            _ => Ok(format!("Sunny in {city}")),
+        }
+    }
+
+    #[cfg(feature = "macros")]
+    #[tokio::test]
+    async fn test_agent_runs_macro_tools() {
+        let definition = GetWeather::definition();
+        assert_eq!(definition.name, "get_weather");
+        assert_eq!(
+            definition.description,
+            "Returns the current weather of a city."
+        );
+        assert_eq!(
+            definition.parameters,
+            json!({
+                "type": "object",
+                "properties": {"city": {"type": "string"}, "days": {"type": "integer"}},
+                "required": ["city"]
+            })
+        );
+        assert_eq!(
+            GetWeather::call(json!({"city": "Rome", "days": 0})).await,
+            Err("days must be positive".to_string())
+        );
+        assert!(GetWeather::call(json!({"days": 2})).await.is_err());
+
+        let client = weather_model();
+        let agent =
+            Agent::new(&client, "llama3-groq-70b-8192-tool-use-preview").register(GetWeather);
+        assert_eq!(
+            agent.run("Weather in Paris?").await.unwrap(),
+            "Sunny in Paris"
+        );
+    }
+}
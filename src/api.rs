@@ -0,0 +1,173 @@
+#[cfg(feature = "stream")]
+use crate::AsyncChatCompletionStream;
+use crate::{
+    AsyncGroqClient, ChatCompletionRequest, ChatCompletionResponse, GroqError, SpeechToTextRequest,
+    SpeechToTextResponse, TextToSpeechRequest,
+};
+use async_trait::async_trait;
+
+/// The chat completion API, implemented by `AsyncGroqClient`.
+///
+/// Depend on `dyn ChatApi` instead of the concrete client to substitute a mock in tests.
+#[async_trait]
+pub trait ChatApi: Send + Sync {
+    /// Sends a chat completion request and returns the parsed response.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `ChatCompletionRequest` to send.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `ChatCompletionResponse`, or a `GroqError` if the request failed.
+    async fn chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, GroqError>;
+}
+
+#[cfg(feature = "stream")]
+/// The streaming chat completion API, implemented by `AsyncGroqClient`.
+///
+/// Providers that do not read server-sent events build their stream with `AsyncChatCompletionStream::from_chunks`.
+#[async_trait]
+pub trait StreamApi: Send + Sync {
+    /// Sends a chat completion request and returns its answer as a stream of chunks.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `ChatCompletionRequest` to send.
+    ///
+    /// # Returns
+    ///
+    /// The `AsyncChatCompletionStream` of the answer, or a `GroqError` if the request failed.
+    async fn chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<AsyncChatCompletionStream, GroqError>;
+}
+
+/// The audio API, implemented by `AsyncGroqClient` with the `audio` feature.
+///
+/// Depend on `dyn AudioApi` instead of the concrete client to substitute a mock in tests.
+#[async_trait]
+pub trait AudioApi: Send + Sync {
+    /// Sends a speech-to-text request and returns the parsed response.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `SpeechToTextRequest` to send.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `SpeechToTextResponse`, or a `GroqError` if the request failed.
+    async fn speech_to_text(
+        &self,
+        request: SpeechToTextRequest,
+    ) -> Result<SpeechToTextResponse, GroqError>;
+}
+
+/// The text-to-speech API, implemented by `AsyncGroqClient`.
+///
+/// Depend on `dyn SpeechApi` instead of the concrete client to substitute a mock in tests.
+#[async_trait]
+pub trait SpeechApi: Send + Sync {
+    /// Sends a text-to-speech request and returns the synthesized audio.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `TextToSpeechRequest` to send.
+    ///
+    /// # Returns
+    ///
+    /// The audio file, or a `GroqError` if the request failed.
+    async fn text_to_speech(&self, request: TextToSpeechRequest) -> Result<Vec<u8>, GroqError>;
+}
+
+#[cfg(feature = "stream")]
+/// The chat, streaming and speech-to-text APIs of a provider, implemented by every type implementing all three.
+///
+/// Hold providers as `Box<dyn GroqApi>` or `Arc<dyn GroqApi>` to switch between them at runtime, such as Groq, a
+/// local OpenAI-compatible server and a mock. A `&dyn GroqApi` coerces to `&dyn ChatApi`, `&dyn StreamApi` and
+/// `&dyn AudioApi` for the helpers taking only one of them.
+pub trait GroqApi: ChatApi + StreamApi + AudioApi {}
+
+#[cfg(feature = "stream")]
+impl<T: ChatApi + StreamApi + AudioApi + ?Sized> GroqApi for T {}
+
+#[async_trait]
+impl ChatApi for AsyncGroqClient {
+    async fn chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, GroqError> {
+        AsyncGroqClient::chat_completion(self, request).await
+    }
+}
+
+#[cfg(feature = "stream")]
+#[async_trait]
+impl StreamApi for AsyncGroqClient {
+    async fn chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<AsyncChatCompletionStream, GroqError> {
+        AsyncGroqClient::chat_completion_stream(self, request).await
+    }
+}
+
+#[cfg(feature = "audio")]
+#[async_trait]
+impl AudioApi for AsyncGroqClient {
+    async fn speech_to_text(
+        &self,
+        request: SpeechToTextRequest,
+    ) -> Result<SpeechToTextResponse, GroqError> {
+        AsyncGroqClient::speech_to_text(self, request).await
+    }
+}
+
+#[async_trait]
+impl SpeechApi for AsyncGroqClient {
+    async fn text_to_speech(&self, request: TextToSpeechRequest) -> Result<Vec<u8>, GroqError> {
+        AsyncGroqClient::text_to_speech(self, request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{reply, ChatFn};
+
+    async fn greet(chat: &dyn ChatApi) -> String {
+        let request = ChatCompletionRequest::new(
+            "llama3-8b-8192",
+            vec![crate::ChatCompletionMessage {
+                role: crate::ChatCompletionRoles::User,
+                content: "Hello".into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        );
+        let response = chat.chat_completion(request).await.unwrap();
+        response.choices[0].message.content.clone()
+    }
+
+    #[tokio::test]
+    async fn test_chat_api_can_be_mocked() {
+        let echo = ChatFn(|request: ChatCompletionRequest| {
+            reply(&request.model, &request.messages.last().unwrap().content)
+        });
+        assert_eq!(greet(&echo).await, "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_client_implements_traits() {
+        let client = AsyncGroqClient::new("key".to_string(), None).await;
+        let _chat: &dyn ChatApi = &client;
+        #[cfg(feature = "audio")]
+        let _audio: &dyn AudioApi = &client;
+        let _speech: &dyn SpeechApi = &client;
+    }
+}
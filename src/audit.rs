@@ -0,0 +1,196 @@
+use crate::{GroqError, ResponseMeta};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A storage backend for `AuditEntry` records.
+///
+/// `FileAuditSink` is the built-in backend. Closures taking an `AuditEntry` also implement this trait, so entries can be
+/// forwarded to a database or a compliance service without a new type.
+pub trait AuditSink: Send + Sync {
+    /// Stores one entry. Called once per call, after it succeeded or failed.
+    ///
+    /// # Arguments
+    /// * `entry` - The entry to store.
+    fn record(&self, entry: &AuditEntry);
+}
+
+impl<F> AuditSink for F
+where
+    F: Fn(&AuditEntry) + Send + Sync,
+{
+    fn record(&self, entry: &AuditEntry) {
+        self(entry)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+/// Represents how an audited call ended.
+///
+/// - `Success`: The call succeeded.
+/// - `Failure`: The call failed, with the error message.
+pub enum AuditOutcome {
+    Success,
+    Failure { error: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+/// Represents one API call, as recorded by an `AuditSink`.
+///
+/// - `timestamp_ms`: When the call ended, in milliseconds since the Unix epoch.
+/// - `context`: The context supplied by the caller with `audit_context`, such as the user on whose behalf the call was made.
+/// - `endpoint`: The path of the endpoint, such as `/chat/completions`.
+/// - `model`: The requested model.
+/// - `request_hash`: The hex-encoded SHA-256 hash of the request, which identifies it without storing its content.
+/// - `status`: The HTTP status of the response, if any.
+/// - `request_id`: The `x-request-id` of the response, if any.
+/// - `outcome`: Whether the call succeeded.
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    pub context: Option<String>,
+    pub endpoint: String,
+    pub model: String,
+    pub request_hash: String,
+    pub status: Option<u16>,
+    pub request_id: Option<String>,
+    #[serde(flatten)]
+    pub outcome: AuditOutcome,
+}
+
+impl AuditEntry {
+    /// Creates the entry of a call ending now from its result.
+    pub(crate) fn new<T>(
+        context: Option<&str>,
+        endpoint: &str,
+        model: &str,
+        request_hash: String,
+        result: &Result<(T, ResponseMeta), GroqError>,
+    ) -> Self {
+        let outcome = result.as_ref().map(|(_, meta)| meta);
+        Self::from_outcome(context, endpoint, model, request_hash, outcome)
+    }
+
+    /// Creates the entry of a call ending now from the metadata of its response or its error.
+    pub(crate) fn from_outcome(
+        context: Option<&str>,
+        endpoint: &str,
+        model: &str,
+        request_hash: String,
+        outcome: Result<&ResponseMeta, &GroqError>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let (status, request_id, outcome) = match outcome {
+            Ok(meta) => (
+                Some(meta.status),
+                meta.request_id.clone(),
+                AuditOutcome::Success,
+            ),
+            Err(err) => (
+                err.status(),
+                err.request_id().map(|id| id.to_string()),
+                AuditOutcome::Failure {
+                    error: err.to_string(),
+                },
+            ),
+        };
+        Self {
+            timestamp_ms: timestamp.as_millis() as u64,
+            context: context.map(|context| context.to_string()),
+            endpoint: endpoint.to_string(),
+            model: model.to_string(),
+            request_hash,
+            status,
+            request_id,
+            outcome,
+        }
+    }
+}
+
+/// Returns the hex-encoded SHA-256 hash of the given request parts.
+pub(crate) fn request_hash(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// An `AuditSink` appending one JSON object per line to a file.
+///
+/// The file is only ever appended to, and every entry is flushed before the call returns. Write failures are logged
+/// and otherwise ignored, so they never fail a call.
+pub struct FileAuditSink {
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    /// Creates a `FileAuditSink` appending to the file at the given path, creating it if needed.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, entry: &AuditEntry) {
+        let mut line = serde_json::to_string(entry).unwrap_or_default();
+        line.push('\n');
+        let mut file = self.file.lock().unwrap_or_else(|err| err.into_inner());
+        if let Err(err) = file
+            .write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+        {
+            log::warn!("Failed to write audit entry: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_audit_sink() {
+        let path = std::env::temp_dir().join(format!("groq-audit-{}.jsonl", std::process::id()));
+        let sink = FileAuditSink::open(&path).unwrap();
+        let hash = request_hash(&[b"{\"model\":\"llama3-8b-8192\"}"]);
+        let result: Result<((), ResponseMeta), GroqError> = Ok(((), ResponseMeta::default()));
+        sink.record(&AuditEntry::new(
+            Some("user-42"),
+            "/chat/completions",
+            "llama3-8b-8192",
+            hash.clone(),
+            &result,
+        ));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(entry["context"], "user-42");
+        assert_eq!(entry["outcome"], "success");
+        assert_eq!(entry["request_hash"], hash.as_str());
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, request_hash(&[b"{\"model\":\"llama3-70b-8192\"}"]));
+        std::fs::remove_file(path).unwrap();
+    }
+}
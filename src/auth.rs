@@ -0,0 +1,100 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Url;
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+/// Represents how the API key is sent with every request.
+///
+/// - `Bearer`: In the `Authorization` header, as `Bearer <key>`. This is what Groq and most OpenAI-compatible servers
+///   expect.
+/// - `Header`: In the header `name`, after `prefix` and a space if a prefix is given, such as `api-key: <key>`.
+/// - `Query`: In the query parameter `param` of the URL, such as `?api_key=<key>`. The key is redacted from the URLs
+///   seen by hooks, dry runs, cassettes and errors, but servers and proxies may log it.
+/// - `None`: Not sent, for servers without authentication.
+pub enum AuthScheme {
+    #[default]
+    Bearer,
+    Header {
+        name: String,
+        prefix: Option<String>,
+    },
+    Query {
+        param: String,
+    },
+    None,
+}
+
+impl AuthScheme {
+    /// Returns the name and value of the header carrying the API key, or `None` if it is not sent.
+    pub(crate) fn header(&self, api_key: &str) -> Option<(&str, String)> {
+        match self {
+            AuthScheme::Bearer => Some(("Authorization", format!("Bearer {}", api_key))),
+            AuthScheme::Header { name, prefix } => {
+                let value = match prefix {
+                    Some(prefix) => format!("{} {}", prefix, api_key),
+                    None => api_key.to_string(),
+                };
+                Some((name.as_str(), value))
+            }
+            AuthScheme::Query { .. } | AuthScheme::None => None,
+        }
+    }
+
+    /// Returns the name and value of the query parameter carrying the API key, or `None` if it is not sent in the URL.
+    pub(crate) fn query<'a>(&'a self, api_key: &'a str) -> Option<(&'a str, &'a str)> {
+        match self {
+            AuthScheme::Query { param } => Some((param.as_str(), api_key)),
+            _ => None,
+        }
+    }
+
+    /// Returns the URL with the value of the query parameter carrying the API key replaced by `[REDACTED]`.
+    pub(crate) fn redact_url<'a>(&self, url: &'a Url) -> Cow<'a, Url> {
+        let AuthScheme::Query { param } = self else {
+            return Cow::Borrowed(url);
+        };
+        if !url.query_pairs().any(|(name, _)| name == param.as_str()) {
+            return Cow::Borrowed(url);
+        }
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if name == param.as_str() {
+                    "[REDACTED]".into()
+                } else {
+                    value
+                };
+                (name.into_owned(), value.into_owned())
+            })
+            .collect();
+        let mut redacted = url.clone();
+        redacted.query_pairs_mut().clear().extend_pairs(pairs);
+        Cow::Owned(redacted)
+    }
+
+    /// Redacts the API key from the URL of an error, if the key is sent in the URL.
+    pub(crate) fn redact_error(&self, error: reqwest::Error) -> reqwest::Error {
+        match error.url().map(|url| self.redact_url(url)) {
+            Some(Cow::Owned(url)) => error.with_url(url),
+            _ => error,
+        }
+    }
+}
+
+/// Builds the header carrying the API key, marked as sensitive so that hooks and dry runs redact it.
+///
+/// The header is returned in a `HeaderMap`, as request builders only keep the flag of headers added with `headers`.
+/// Invalid values are returned as-is, for the request builder to report them when the request is built.
+pub(crate) fn sensitive(name: &str, value: String) -> Result<HeaderMap, String> {
+    let (Ok(name), Ok(mut header)) = (
+        HeaderName::from_bytes(name.as_bytes()),
+        HeaderValue::from_str(&value),
+    ) else {
+        return Err(value);
+    };
+    header.set_sensitive(true);
+    let mut headers = HeaderMap::new();
+    headers.insert(name, header);
+    Ok(headers)
+}
@@ -0,0 +1,211 @@
+use crate::{
+    ChatApi, ChatCompletionMessage, ChatCompletionRequest, ChatCompletionRoles, GroqError,
+    MessageContent,
+};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+/// A throughput benchmark sending the same chat completion several times in a row.
+///
+/// Latency is measured by the client around every call, while tokens per second come from the `Usage` timings reported
+/// by the API, so they reflect the model rather than the network.
+pub struct Benchmark {
+    request: ChatCompletionRequest,
+    requests: usize,
+}
+
+impl Benchmark {
+    /// Creates a new `Benchmark` sending 10 requests with the given prompt as a user message.
+    ///
+    /// # Arguments
+    /// * `model` - The model to benchmark.
+    /// * `prompt` - The content of the user message.
+    pub fn new(model: impl Into<String>, prompt: impl Into<MessageContent>) -> Self {
+        let message = ChatCompletionMessage {
+            role: ChatCompletionRoles::User,
+            content: prompt.into(),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        };
+        Self::from_request(ChatCompletionRequest::new(model, vec![message]))
+    }
+
+    /// Creates a new `Benchmark` sending 10 copies of the given request.
+    ///
+    /// # Arguments
+    /// * `request` - The request to send.
+    pub fn from_request(request: ChatCompletionRequest) -> Self {
+        Self {
+            request,
+            requests: 10,
+        }
+    }
+
+    /// Sets the number of requests to send.
+    ///
+    /// # Arguments
+    /// * `requests` - The number of requests.
+    pub fn requests(mut self, requests: usize) -> Self {
+        self.requests = requests;
+        self
+    }
+
+    /// Sets the maximum number of tokens generated by each request.
+    ///
+    /// # Arguments
+    /// * `max_tokens` - The maximum number of completion tokens.
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.request = self.request.max_tokens(max_tokens);
+        self
+    }
+
+    /// Sends the requests one after the other and aggregates their timings.
+    ///
+    /// # Arguments
+    /// * `client` - The client to benchmark, such as an `AsyncGroqClient`.
+    pub async fn run(&self, client: &dyn ChatApi) -> BenchReport {
+        let mut latencies = Vec::with_capacity(self.requests);
+        let mut tokens_per_second = Vec::with_capacity(self.requests);
+        let mut completion_tokens = 0;
+        let mut errors = Vec::new();
+        let started = Instant::now();
+        for _ in 0..self.requests {
+            let call_started = Instant::now();
+            match client.chat_completion(self.request.clone()).await {
+                Ok(response) => {
+                    latencies.push(call_started.elapsed());
+                    if let Some(usage) = &response.usage {
+                        completion_tokens += usage.completion_tokens;
+                        tokens_per_second.extend(usage.tokens_per_second());
+                    }
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+        latencies.sort();
+        tokens_per_second.sort_by(f64::total_cmp);
+
+        BenchReport {
+            model: self.request.model.clone(),
+            requests: self.requests,
+            total_time: started.elapsed(),
+            p50_latency: percentile(&latencies, 50).copied(),
+            p95_latency: percentile(&latencies, 95).copied(),
+            max_latency: latencies.last().copied(),
+            p50_tokens_per_second: percentile(&tokens_per_second, 50).copied(),
+            p95_tokens_per_second: percentile(&tokens_per_second, 95).copied(),
+            completion_tokens,
+            errors,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Represents the results of a `Benchmark`.
+///
+/// Latencies and tokens per second only include successful requests, and are `None` if every request failed.
+///
+/// - `model`: The benchmarked model.
+/// - `requests`: The number of requests sent.
+/// - `total_time`: The duration of the whole benchmark.
+/// - `p50_latency`: The median latency.
+/// - `p95_latency`: The 95th percentile latency.
+/// - `max_latency`: The highest latency.
+/// - `p50_tokens_per_second`: The median completion speed reported by the API.
+/// - `p95_tokens_per_second`: The 95th percentile completion speed reported by the API.
+/// - `completion_tokens`: The total number of generated tokens.
+/// - `errors`: The errors of the failed requests.
+pub struct BenchReport {
+    pub model: String,
+    pub requests: usize,
+    pub total_time: Duration,
+    pub p50_latency: Option<Duration>,
+    pub p95_latency: Option<Duration>,
+    pub max_latency: Option<Duration>,
+    pub p50_tokens_per_second: Option<f64>,
+    pub p95_tokens_per_second: Option<f64>,
+    pub completion_tokens: u64,
+    pub errors: Vec<GroqError>,
+}
+
+impl BenchReport {
+    /// Returns the number of requests that succeeded.
+    pub fn successes(&self) -> usize {
+        self.requests - self.errors.len()
+    }
+}
+
+/// Returns the nearest-rank percentile of sorted values.
+fn percentile<T>(sorted: &[T], percent: usize) -> Option<&T> {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted.get(rank - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{completion, server_error, ChatFn};
+    use crate::ChatCompletionResponse;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Answers with a completion time growing by 10ms on every call.
+    struct SlowerAndSlower(AtomicU64);
+
+    #[async_trait]
+    impl ChatApi for SlowerAndSlower {
+        async fn chat_completion(
+            &self,
+            request: ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, GroqError> {
+            let call = self.0.fetch_add(1, Ordering::Relaxed) + 1;
+            completion(
+                &request.model,
+                json!([]),
+                json!({
+                    "prompt_tokens": 5, "prompt_time": 0.0,
+                    "completion_tokens": 100, "completion_time": call as f64 / 100.0,
+                    "total_tokens": 105, "total_time": call as f64 / 100.0
+                }),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_benchmark_report() {
+        let report = Benchmark::new("llama3-8b-8192", "Hello")
+            .requests(20)
+            .run(&SlowerAndSlower(AtomicU64::new(0)))
+            .await;
+        assert_eq!(report.successes(), 20);
+        assert_eq!(report.completion_tokens, 2000);
+        // Speeds are 10000 / call, so the median of calls 1 to 20 is the speed of call 11.
+        let p50 = report.p50_tokens_per_second.unwrap();
+        assert!((p50 - 10000.0 / 11.0).abs() < 1e-9);
+        assert!(report.p95_latency.unwrap() <= report.max_latency.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_benchmark_report_counts_errors() {
+        let report = Benchmark::new("llama3-8b-8192", "Hello")
+            .requests(3)
+            .run(&ChatFn(|_| Err(server_error())))
+            .await;
+        assert_eq!(report.successes(), 0);
+        assert_eq!(report.errors.len(), 3);
+        assert_eq!(report.completion_tokens, 0);
+        assert_eq!(report.p50_latency, None);
+        assert_eq!(report.max_latency, None);
+        assert_eq!(report.p50_tokens_per_second, None);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<u32> = (1..=20).collect();
+        assert_eq!(percentile(&values, 50), Some(&10));
+        assert_eq!(percentile(&values, 95), Some(&19));
+        assert_eq!(percentile::<u32>(&[], 50), None);
+    }
+}
@@ -0,0 +1,456 @@
+//! A command-line client for the Groq API, built with the `cli` feature.
+//!
+//! The API key, endpoint and default chat model are read from a profile of the `GroqConfig` file, selected with
+//! `--profile` or `GROQ_PROFILE`. Settings missing from the profile are read from `GROQ_API_KEY` and `GROQ_ENDPOINT`.
+#![allow(clippy::result_large_err)]
+
+use groq_api_rust::models::{LLAMA_3_3_70B_VERSATILE, WHISPER_LARGE_V3_TURBO};
+use groq_api_rust::{
+    messages, ChatCompletionMessage, ChatCompletionRequest, GroqChat, GroqClient, GroqConfig,
+    GroqError, Profile, SpeechToTextRequest, TextToSpeechRequest,
+};
+use std::collections::HashMap;
+use std::io::{IsTerminal, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: groq <command> [--profile <name>] [options]
+
+Commands:
+  chat [--model <model>] [--system <prompt>] [--temperature <t>] [--max-tokens <n>] [--prompt <prompt>] [<prompt>...]
+      Sends a prompt and prints the answer. Text piped to stdin is sent before the prompt as its context.
+  chat --interactive [--model <model>] [--system <prompt>] [--temperature <t>] [--max-tokens <n>]
+      Starts a conversation, streaming the answers.
+  transcribe [--model <model>] [--language <code>] [--translate] <file>
+      Prints the transcript of an audio file, translated into English with --translate.
+  models
+      Lists the models served by the API.
+  tts [--model <model>] [--voice <voice>] [--format <format>] --output <file> <text>...
+      Speaks a text into an audio file.
+
+Profiles:
+  Profiles are read from groq/config.json in the configuration directory of the platform, such as
+  ~/.config/groq/config.json on Linux:
+    {\"default_profile\": \"work\", \"profiles\": {\"work\": {\"api_key\": \"gsk_...\", \"model\": \"llama-3.1-8b-instant\"}}}
+  A profile can set api_key, endpoint and model, the default model of chat.
+
+Environment:
+  GROQ_PROFILE   The profile used without --profile.
+  GROQ_CONFIG    The path of the configuration file.
+  GROQ_API_KEY   The API key, if the profile has none.
+  GROQ_ENDPOINT  The URL of the API, if the profile has none; https://api.groq.com/openai/v1 by default.
+
+Exit status:
+  0  Success.
+  1  An IO error, or a request rejected by the API.
+  2  Invalid arguments.
+  3  The API key was rejected.
+  4  A rate limit, overload or connection error; the command can be retried later.";
+
+const REPL_HELP: &str = "Commands:
+  /system [prompt]  Sets the system prompt, or removes it without a prompt.
+  /model [model]    Switches to another model, or prints the current one.
+  /save <file>      Saves the conversation as JSON, in the format read by GroqChat::load.
+  /clear            Forgets the conversation.
+  /exit             Quits.";
+
+/// An error stopping the command: invalid arguments or a failed request.
+enum CliError {
+    Usage(String),
+    Io(String),
+    Api(GroqError),
+}
+
+impl From<GroqError> for CliError {
+    fn from(err: GroqError) -> Self {
+        CliError::Api(err)
+    }
+}
+
+/// The arguments of a command: options by name, flags, and the remaining positional arguments.
+struct Args {
+    options: HashMap<String, String>,
+    flags: Vec<String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    /// Parses `--name value`, `--name=value` and the given value-less flags; everything else is positional.
+    fn parse(args: impl Iterator<Item = String>, flags: &[&str]) -> Result<Self, CliError> {
+        let mut parsed = Args {
+            options: HashMap::new(),
+            flags: Vec::new(),
+            positional: Vec::new(),
+        };
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                parsed.positional.extend(args.by_ref());
+                break;
+            }
+            let Some(name) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg);
+                continue;
+            };
+            if let Some((name, value)) = name.split_once('=') {
+                parsed.options.insert(name.to_string(), value.to_string());
+            } else if flags.contains(&name) {
+                parsed.flags.push(name.to_string());
+            } else {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError::Usage(format!("--{} needs a value", name)))?;
+                parsed.options.insert(name.to_string(), value);
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    /// Parses an option into a number or another type.
+    fn parsed<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, CliError> {
+        self.option(name)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    CliError::Usage(format!("invalid value for --{}: {}", name, value))
+                })
+            })
+            .transpose()
+    }
+
+    /// Returns the positional arguments joined with spaces, failing if there are none.
+    fn text(&self, what: &str) -> Result<String, CliError> {
+        if self.positional.is_empty() {
+            return Err(CliError::Usage(format!("missing {}", what)));
+        }
+        Ok(self.positional.join(" "))
+    }
+}
+
+/// Loads the profile selected by `--profile` or `GROQ_PROFILE`.
+fn profile(args: &Args) -> Result<Profile, CliError> {
+    let config =
+        GroqConfig::load().map_err(|err| CliError::Io(format!("configuration file: {}", err)))?;
+    config
+        .profile(args.option("profile"))
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => CliError::Usage(err.to_string()),
+            _ => CliError::Io(err.to_string()),
+        })
+}
+
+fn client(profile: &Profile) -> Result<GroqClient, CliError> {
+    profile.client().ok_or_else(missing_api_key)
+}
+
+fn missing_api_key() -> CliError {
+    CliError::Usage("no API key: set GROQ_API_KEY or add an api_key to the profile".to_string())
+}
+
+/// Returns the chat model given by `--model`, else the one of the profile, else the default one.
+fn chat_model<'a>(args: &'a Args, profile: &'a Profile) -> &'a str {
+    args.option("model")
+        .or(profile.model.as_deref())
+        .unwrap_or(LLAMA_3_3_70B_VERSATILE)
+}
+
+/// Builds a chat completion request with the sampling options of the command line.
+fn chat_request(
+    args: &Args,
+    model: &str,
+    system: Option<&str>,
+    conversation: &[ChatCompletionMessage],
+) -> Result<ChatCompletionRequest, CliError> {
+    let mut messages = Vec::new();
+    if let Some(system) = system {
+        messages.extend(messages![system: system]);
+    }
+    messages.extend_from_slice(conversation);
+    let mut request = ChatCompletionRequest::new(model, messages);
+    if let Some(temperature) = args.parsed("temperature")? {
+        request = request.temperature(temperature);
+    }
+    if let Some(max_tokens) = args.parsed("max-tokens")? {
+        request = request.max_tokens(max_tokens);
+    }
+    Ok(request)
+}
+
+/// Reads the text piped to stdin, or returns `None` if stdin is a terminal or empty.
+fn piped_stdin() -> Result<Option<String>, CliError> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(None);
+    }
+    let mut text = String::new();
+    stdin
+        .read_to_string(&mut text)
+        .map_err(|err| CliError::Io(format!("stdin: {}", err)))?;
+    Ok(Some(text).filter(|text| !text.trim().is_empty()))
+}
+
+/// Combines the text piped to stdin and the prompt of the command line into one user message.
+fn prompt_with_context(
+    prompt: Option<String>,
+    context: Option<String>,
+) -> Result<String, CliError> {
+    match (prompt, context) {
+        (Some(prompt), Some(context)) => Ok(format!("{}\n\n{}", context.trim_end(), prompt)),
+        (Some(text), None) | (None, Some(text)) => Ok(text),
+        (None, None) => Err(CliError::Usage("missing prompt".to_string())),
+    }
+}
+
+/// Writes the output of a command to stdout, ignoring a reader that closed the pipe early.
+fn print_output(output: &str) -> Result<(), CliError> {
+    let mut stdout = std::io::stdout().lock();
+    match writeln!(stdout, "{}", output).and_then(|()| stdout.flush()) {
+        Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => {
+            Err(CliError::Io(format!("stdout: {}", err)))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn chat(args: Args) -> Result<(), CliError> {
+    if args.flag("interactive") {
+        return interactive(args);
+    }
+    let mut words: Vec<&str> = args.option("prompt").into_iter().collect();
+    words.extend(args.positional.iter().map(String::as_str));
+    let prompt = Some(words.join(" ")).filter(|prompt| !prompt.is_empty());
+    let prompt = prompt_with_context(prompt, piped_stdin()?)?;
+    let profile = profile(&args)?;
+    let model = chat_model(&args, &profile);
+    let request = chat_request(
+        &args,
+        model,
+        args.option("system"),
+        &messages![user: prompt],
+    )?;
+    let response = client(&profile)?.chat_completion(request)?;
+    print_output(&response.to_string())
+}
+
+/// Runs a conversation reading prompts and commands from stdin, and streaming the answers.
+///
+/// The conversation is a `GroqChat`, so `/save` writes the file read by `GroqChat::load`.
+fn interactive(args: Args) -> Result<(), CliError> {
+    let profile = profile(&args)?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| CliError::Io(format!("runtime: {}", err)))?;
+    let client = runtime
+        .block_on(profile.async_client())
+        .ok_or_else(missing_api_key)?;
+    let request = chat_request(&args, chat_model(&args, &profile), None, &[])?;
+    let mut chat = GroqChat::from_request(&client, request);
+    if let Some(system) = args.option("system") {
+        chat.set_system_prompt(system);
+    }
+    eprintln!("Chatting with {}. Type /help for commands.", chat.model());
+    let stdin = std::io::stdin();
+    loop {
+        eprint!("> ");
+        let mut line = String::new();
+        if stdin
+            .read_line(&mut line)
+            .map_err(|err| CliError::Io(err.to_string()))?
+            == 0
+        {
+            return Ok(());
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(command) = line.strip_prefix('/') {
+            let (name, value) = command
+                .split_once(' ')
+                .map_or((command, ""), |(name, value)| (name, value.trim()));
+            match name {
+                "system" if value.is_empty() => {
+                    chat.remove_system_prompt();
+                    eprintln!("System prompt removed.");
+                }
+                "system" => {
+                    chat.set_system_prompt(value);
+                    eprintln!("System prompt set.");
+                }
+                "model" if value.is_empty() => eprintln!("{}", chat.model()),
+                "model" => {
+                    chat.set_model(value);
+                    eprintln!("Switched to {}.", chat.model());
+                }
+                "save" if value.is_empty() => eprintln!("usage: /save <file>"),
+                "save" => match chat.save(value) {
+                    Ok(()) => eprintln!("Saved to {}.", value),
+                    Err(err) => eprintln!("error: {}: {}", value, err),
+                },
+                "clear" => {
+                    chat.clear();
+                    eprintln!("History cleared.");
+                }
+                "exit" | "quit" => return Ok(()),
+                "help" => eprintln!("{}", REPL_HELP),
+                _ => eprintln!("unknown command /{}; /help lists the commands", name),
+            }
+            continue;
+        }
+
+        let mut stdout = std::io::stdout();
+        let answer = runtime.block_on(chat.send_streaming(&client, line, |content| {
+            print!("{}", content);
+            let _ = stdout.flush();
+        }));
+        println!();
+        if let Err(err) = answer {
+            eprintln!("error: {}", err);
+        }
+    }
+}
+
+fn transcribe(args: Args) -> Result<(), CliError> {
+    let path = args.text("audio file")?;
+    let file = std::fs::read(&path).map_err(|err| CliError::Io(format!("{}: {}", path, err)))?;
+    let mut request = SpeechToTextRequest::new(file)
+        .model(args.option("model").unwrap_or(WHISPER_LARGE_V3_TURBO))
+        .english_text(args.flag("translate"));
+    if let Some(language) = args.option("language") {
+        request = request.language(language);
+    }
+    println!("{}", client(&profile(&args)?)?.speech_to_text(request)?);
+    Ok(())
+}
+
+fn models(args: Args) -> Result<(), CliError> {
+    let mut models = client(&profile(&args)?)?.list_models()?;
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    for model in models {
+        let status = if model.active { "" } else { " (inactive)" };
+        println!("{}\t{}{}", model.id, model.context_window, status);
+    }
+    Ok(())
+}
+
+fn tts(args: Args) -> Result<(), CliError> {
+    let text = args.text("text")?;
+    let output = args
+        .option("output")
+        .ok_or_else(|| CliError::Usage("missing --output".to_string()))?;
+    let format = args.option("format").unwrap_or_else(|| {
+        output
+            .rsplit_once('.')
+            .map_or("wav", |(_, extension)| extension)
+    });
+    let request = TextToSpeechRequest::new(
+        args.option("model").unwrap_or("playai-tts"),
+        &text,
+        args.option("voice").unwrap_or("Fritz-PlayAI"),
+    )
+    .response_format(format);
+    let audio = client(&profile(&args)?)?.text_to_speech(request)?;
+    std::fs::write(output, audio).map_err(|err| CliError::Io(format!("{}: {}", output, err)))
+}
+
+fn run() -> Result<(), CliError> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    match command.as_str() {
+        "chat" => chat(Args::parse(args, &["interactive"])?),
+        "transcribe" => transcribe(Args::parse(args, &["translate"])?),
+        "models" => models(Args::parse(args, &[])?),
+        "tts" => tts(Args::parse(args, &[])?),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        "" => Err(CliError::Usage("missing command".to_string())),
+        other => Err(CliError::Usage(format!("unknown command `{}`", other))),
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(CliError::Io(message)) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+        Err(CliError::Api(err)) => {
+            eprintln!("error: {}", err);
+            if err.is_auth_error() {
+                ExitCode::from(3)
+            } else if err.is_retryable() {
+                ExitCode::from(4)
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str], flags: &[&str]) -> Args {
+        let Ok(args) = Args::parse(args.iter().map(|arg| arg.to_string()), flags) else {
+            panic!("the arguments should parse");
+        };
+        args
+    }
+
+    #[test]
+    fn test_args_parse_options_and_flags() {
+        let parsed = args(
+            &[
+                "--model",
+                "llama-3.1-8b-instant",
+                "--translate",
+                "--max-tokens=10",
+                "hello",
+                "--",
+                "--world",
+            ],
+            &["translate"],
+        );
+        assert_eq!(parsed.option("model"), Some("llama-3.1-8b-instant"));
+        assert!(parsed.flag("translate"));
+        assert_eq!(parsed.parsed::<u32>("max-tokens").ok().flatten(), Some(10));
+        assert_eq!(parsed.text("prompt").ok().as_deref(), Some("hello --world"));
+
+        assert!(matches!(
+            Args::parse(["--model".to_string()].into_iter(), &[]),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            args(&["--temperature", "hot"], &[]).parsed::<f64>("temperature"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_prompt_with_context_puts_stdin_first() {
+        let prompt =
+            prompt_with_context(Some("summarize".to_string()), Some("notes\n".to_string()));
+        assert_eq!(prompt.ok().as_deref(), Some("notes\n\nsummarize"));
+        let prompt = prompt_with_context(None, Some("what is 2 + 2?".to_string()));
+        assert_eq!(prompt.ok().as_deref(), Some("what is 2 + 2?"));
+        assert!(matches!(
+            prompt_with_context(None, None),
+            Err(CliError::Usage(_))
+        ));
+    }
+}
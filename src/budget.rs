@@ -0,0 +1,352 @@
+use crate::tokens::count_tokens;
+use crate::{ChatCompletionRequest, GroqError, PricingTable, Usage};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Represents the rolling window a `Budget` applies to.
+///
+/// - `Hour`: The last 60 minutes.
+/// - `Day`: The last 24 hours.
+pub enum BudgetWindow {
+    Hour,
+    Day,
+}
+
+impl BudgetWindow {
+    /// Returns the length of the window.
+    pub fn duration(&self) -> Duration {
+        match self {
+            BudgetWindow::Hour => Duration::from_secs(60 * 60),
+            BudgetWindow::Day => Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl std::fmt::Display for BudgetWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetWindow::Hour => write!(f, "hour"),
+            BudgetWindow::Day => write!(f, "day"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A guard capping the tokens and estimated cost spent by chat completions over a rolling window.
+///
+/// Before a chat completion is sent, its estimated size (the prompt plus `max_tokens`) is reserved against the budget;
+/// if that would exceed a limit the call fails immediately with `GroqError::BudgetExceeded`. Once the response arrives,
+/// the reservation is replaced by the tokens actually used, and failed calls are refunded.
+/// Costs are estimated from the `PricingTable` of the client, with its separate prompt and completion prices, unless the
+/// budget sets its own flat prices per million tokens, per model or as a default. A model without any price costs
+/// nothing, and a warning is logged for it when `max_cost` is set.
+/// Cloning a `Budget` shares its state, so one budget can be used by several clients.
+pub struct Budget {
+    window: BudgetWindow,
+    max_tokens: Option<u64>,
+    max_cost: Option<f64>,
+    default_price: Option<f64>,
+    model_prices: Arc<HashMap<String, f64>>,
+    ledger: Arc<Mutex<Ledger>>,
+}
+
+impl Budget {
+    /// Creates a new `Budget` without limits over the given window.
+    ///
+    /// # Arguments
+    /// * `window` - The rolling window the limits apply to.
+    pub fn new(window: BudgetWindow) -> Self {
+        Self {
+            window,
+            max_tokens: None,
+            max_cost: None,
+            default_price: None,
+            model_prices: Arc::default(),
+            ledger: Arc::new(Mutex::new(Ledger::default())),
+        }
+    }
+
+    /// Sets the maximum number of tokens spent within the window.
+    ///
+    /// # Arguments
+    /// * `max_tokens` - The maximum number of prompt and completion tokens.
+    pub fn max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Sets the maximum estimated cost spent within the window.
+    ///
+    /// # Arguments
+    /// * `max_cost` - The maximum cost, in the currency of the configured prices.
+    pub fn max_cost(mut self, max_cost: f64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Sets the price per million tokens of models without a specific price, instead of the prices of the client.
+    ///
+    /// # Arguments
+    /// * `price` - The price of one million tokens.
+    pub fn price_per_million_tokens(mut self, price: f64) -> Self {
+        self.default_price = Some(price);
+        self
+    }
+
+    /// Sets the price per million tokens of a specific model, instead of its prices in the client's `PricingTable`.
+    ///
+    /// # Arguments
+    /// * `model` - The model the price applies to.
+    /// * `price` - The price of one million tokens.
+    pub fn model_price(mut self, model: impl Into<String>, price: f64) -> Self {
+        Arc::make_mut(&mut self.model_prices).insert(model.into(), price);
+        self
+    }
+
+    /// Returns the tokens and estimated cost spent within the current window, including pending reservations.
+    pub fn spent(&self) -> (u64, f64) {
+        let mut ledger = self.ledger();
+        ledger.prune(Instant::now(), self.window.duration());
+        ledger.totals()
+    }
+
+    /// Reserves the estimated size of a chat completion request, its prompt plus `max_tokens`, failing if that would
+    /// exceed the budget.
+    pub(crate) fn reserve(
+        &self,
+        request: &ChatCompletionRequest,
+        pricing: &Arc<PricingTable>,
+    ) -> Result<BudgetReservation, GroqError> {
+        let completion_tokens = u64::from(request.max_tokens_for(&request.model));
+        self.reserve_tokens(
+            &request.model,
+            count_tokens(request),
+            completion_tokens,
+            pricing,
+        )
+    }
+
+    /// Reserves the given numbers of prompt and completion tokens, failing if that would exceed the budget.
+    fn reserve_tokens(
+        &self,
+        model: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        pricing: &Arc<PricingTable>,
+    ) -> Result<BudgetReservation, GroqError> {
+        let now = Instant::now();
+        let tokens = prompt_tokens + completion_tokens;
+        let cost = self.cost(model, prompt_tokens, completion_tokens, pricing);
+        let mut ledger = self.ledger();
+        ledger.prune(now, self.window.duration());
+
+        let (spent_tokens, spent_cost) = ledger.totals();
+        let over_tokens = self
+            .max_tokens
+            .is_some_and(|max| spent_tokens + tokens > max);
+        let over_cost = self.max_cost.is_some_and(|max| spent_cost + cost > max);
+        if over_tokens || over_cost {
+            return Err(GroqError::BudgetExceeded {
+                window: self.window,
+                spent_tokens,
+                spent_cost,
+            });
+        }
+
+        let id = ledger.next_id;
+        ledger.next_id += 1;
+        ledger.entries.push_back(Spend {
+            id,
+            at: now,
+            tokens,
+            cost,
+        });
+        Ok(BudgetReservation {
+            budget: self.clone(),
+            pricing: pricing.clone(),
+            id,
+            prompt_tokens,
+            completion_tokens,
+            settled: false,
+        })
+    }
+
+    fn cost(
+        &self,
+        model: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        pricing: &PricingTable,
+    ) -> f64 {
+        if let Some(price) = self.model_prices.get(model).copied().or(self.default_price) {
+            return (prompt_tokens + completion_tokens) as f64 * price / 1_000_000.0;
+        }
+        if let Some(price) = pricing.get(model) {
+            return price.cost(prompt_tokens, completion_tokens);
+        }
+        if self.max_cost.is_some() && self.ledger().unpriced.insert(model.to_string()) {
+            log::warn!(
+                "No price is known for {model}, so its cost is not counted against the budget"
+            );
+        }
+        0.0
+    }
+
+    fn ledger(&self) -> std::sync::MutexGuard<'_, Ledger> {
+        self.ledger.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Tokens reserved against a `Budget`, refunded when dropped without being settled.
+pub(crate) struct BudgetReservation {
+    budget: Budget,
+    pricing: Arc<PricingTable>,
+    id: u64,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    settled: bool,
+}
+
+impl BudgetReservation {
+    /// Replaces the reserved tokens with the tokens actually used by the given model, or keeps the estimate if the
+    /// response reported no usage.
+    pub(crate) fn settle(mut self, model: &str, usage: Option<&Usage>) {
+        let (prompt_tokens, completion_tokens) = usage
+            .map_or((self.prompt_tokens, self.completion_tokens), |usage| {
+                (usage.prompt_tokens, usage.completion_tokens)
+            });
+        let cost = self
+            .budget
+            .cost(model, prompt_tokens, completion_tokens, &self.pricing);
+        let mut ledger = self.budget.ledger();
+        if let Some(spend) = ledger.entries.iter_mut().find(|spend| spend.id == self.id) {
+            spend.tokens = prompt_tokens + completion_tokens;
+            spend.cost = cost;
+        }
+        self.settled = true;
+    }
+}
+
+impl Drop for BudgetReservation {
+    fn drop(&mut self) {
+        if !self.settled {
+            self.budget
+                .ledger()
+                .entries
+                .retain(|spend| spend.id != self.id);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Ledger {
+    next_id: u64,
+    entries: VecDeque<Spend>,
+    unpriced: HashSet<String>,
+}
+
+impl Ledger {
+    fn prune(&mut self, now: Instant, window: Duration) {
+        while self
+            .entries
+            .front()
+            .is_some_and(|spend| now.saturating_duration_since(spend.at) >= window)
+        {
+            self.entries.pop_front();
+        }
+    }
+
+    fn totals(&self) -> (u64, f64) {
+        self.entries.iter().fold((0, 0.0), |(tokens, cost), spend| {
+            (tokens + spend.tokens, cost + spend.cost)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Spend {
+    id: u64,
+    at: Instant,
+    tokens: u64,
+    cost: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt_tokens: u64, completion_tokens: u64) -> Usage {
+        serde_json::from_value(serde_json::json!({
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+            "total_tokens": prompt_tokens + completion_tokens,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_budget_fails_fast_and_refunds() {
+        let budget = Budget::new(BudgetWindow::Hour)
+            .max_tokens(3000)
+            .max_cost(0.01)
+            .price_per_million_tokens(1.0)
+            .model_price("llama3-70b-8192", 5.0);
+
+        let pricing = Arc::new(PricingTable::default());
+        budget
+            .reserve_tokens("llama3-8b-8192", 1000, 1000, &pricing)
+            .unwrap()
+            .settle("llama3-8b-8192", Some(&usage(400, 100)));
+        assert_eq!(budget.spent().0, 500);
+
+        let pending = budget
+            .reserve_tokens("llama3-8b-8192", 1000, 1000, &pricing)
+            .unwrap();
+        assert!(matches!(
+            budget.reserve_tokens("llama3-8b-8192", 500, 500, &pricing),
+            Err(GroqError::BudgetExceeded {
+                spent_tokens: 2500,
+                ..
+            })
+        ));
+        drop(pending);
+        assert_eq!(budget.spent().0, 500);
+
+        // 2000 tokens at 5 per million cost 0.01, which exceeds the cost limit with what was already spent.
+        assert!(budget
+            .reserve_tokens("llama3-70b-8192", 1000, 1000, &pricing)
+            .is_err());
+        assert!(budget
+            .reserve_tokens("llama3-8b-8192", 1000, 1000, &pricing)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_budget_costs_from_pricing_table() {
+        let budget = Budget::new(BudgetWindow::Day).max_cost(0.001);
+        let pricing = Arc::new(PricingTable::default());
+
+        // 1000 prompt tokens at 0.59 and 1000 completion tokens at 0.79 per million cost 0.00138.
+        assert!(matches!(
+            budget.reserve_tokens("llama3-70b-8192", 1000, 1000, &pricing),
+            Err(GroqError::BudgetExceeded { .. })
+        ));
+        let reservation = budget
+            .reserve_tokens("llama3-8b-8192", 1000, 1000, &pricing)
+            .unwrap();
+        assert!((budget.spent().1 - 0.00013).abs() < 1e-12);
+        reservation.settle("llama3-8b-8192", Some(&usage(2000, 0)));
+        assert!((budget.spent().1 - 0.0001).abs() < 1e-12);
+
+        // A model without a price is not counted against the cost limit.
+        let unpriced = Arc::new(PricingTable::empty());
+        budget
+            .reserve_tokens("my-model", 1_000_000, 0, &unpriced)
+            .unwrap()
+            .settle("my-model", Some(&usage(1_000_000, 0)));
+        assert!((budget.spent().1 - 0.0001).abs() < 1e-12);
+        assert!(budget.ledger().unpriced.contains("my-model"));
+    }
+}
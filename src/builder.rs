@@ -0,0 +1,144 @@
+//! A builder of `ChatCompletionRequest`s that only compiles once a model and a message are set.
+//!
+//! `ChatCompletionRequest::builder()` starts with both missing; `model` and the message methods record them in the
+//! type of the builder, and `build` is only available when both are set. The optional parameters are set on the built
+//! request, with the usual methods of `ChatCompletionRequest`.
+//!
+//! ```
+//! use groq_api_rust::ChatCompletionRequest;
+//!
+//! let request = ChatCompletionRequest::builder()
+//!     .model("llama-3.3-70b-versatile")
+//!     .system("You are a helpful assistant.")
+//!     .user("What is the capital of France?")
+//!     .build()
+//!     .temperature(0.2);
+//! assert_eq!(request.messages.len(), 2);
+//! ```
+//!
+//! Forgetting the messages is a compile error rather than an error returned by the API:
+//!
+//! ```compile_fail
+//! use groq_api_rust::ChatCompletionRequest;
+//!
+//! let request = ChatCompletionRequest::builder().model("llama-3.3-70b-versatile").build();
+//! ```
+
+use crate::{ChatCompletionMessage, ChatCompletionRequest, ChatCompletionRoles, MessageContent};
+use std::marker::PhantomData;
+
+/// Marks a required part of the request that is not set yet.
+#[derive(Debug, Clone, Copy)]
+pub struct Missing;
+
+/// Marks a required part of the request that is set.
+#[derive(Debug, Clone, Copy)]
+pub struct Set;
+
+/// A builder of `ChatCompletionRequest`, tracking whether its model (`M`) and messages (`S`) are set.
+#[derive(Debug, Clone)]
+pub struct ChatCompletionRequestBuilder<M, S> {
+    model: String,
+    messages: Vec<ChatCompletionMessage>,
+    state: PhantomData<(M, S)>,
+}
+
+impl ChatCompletionRequestBuilder<Missing, Missing> {
+    pub(crate) fn new() -> Self {
+        Self {
+            model: String::new(),
+            messages: Vec::new(),
+            state: PhantomData,
+        }
+    }
+}
+
+impl<S> ChatCompletionRequestBuilder<Missing, S> {
+    /// Sets the model of the request.
+    ///
+    /// # Arguments
+    /// * `model` - The language model to use, such as `Model::Llama33_70bVersatile`.
+    pub fn model(self, model: impl Into<String>) -> ChatCompletionRequestBuilder<Set, S> {
+        ChatCompletionRequestBuilder {
+            model: model.into(),
+            messages: self.messages,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<M, S> ChatCompletionRequestBuilder<M, S> {
+    /// Appends a message to the request.
+    ///
+    /// # Arguments
+    /// * `message` - The message to append.
+    pub fn message(
+        mut self,
+        message: ChatCompletionMessage,
+    ) -> ChatCompletionRequestBuilder<M, Set> {
+        self.messages.push(message);
+        ChatCompletionRequestBuilder {
+            model: self.model,
+            messages: self.messages,
+            state: PhantomData,
+        }
+    }
+
+    /// Appends a system message to the request.
+    ///
+    /// # Arguments
+    /// * `content` - The content of the message.
+    pub fn system(
+        self,
+        content: impl Into<MessageContent>,
+    ) -> ChatCompletionRequestBuilder<M, Set> {
+        self.message(text(ChatCompletionRoles::System, content.into()))
+    }
+
+    /// Appends a user message to the request.
+    ///
+    /// # Arguments
+    /// * `content` - The content of the message.
+    pub fn user(self, content: impl Into<MessageContent>) -> ChatCompletionRequestBuilder<M, Set> {
+        self.message(text(ChatCompletionRoles::User, content.into()))
+    }
+
+    /// Appends an assistant message to the request.
+    ///
+    /// # Arguments
+    /// * `content` - The content of the message.
+    pub fn assistant(
+        self,
+        content: impl Into<MessageContent>,
+    ) -> ChatCompletionRequestBuilder<M, Set> {
+        self.message(text(ChatCompletionRoles::Assistant, content.into()))
+    }
+
+    /// Appends messages to the request, such as a conversation history.
+    ///
+    /// The messages may be empty, so they do not count as the required message.
+    ///
+    /// # Arguments
+    /// * `messages` - The messages to append.
+    pub fn messages(mut self, messages: impl IntoIterator<Item = ChatCompletionMessage>) -> Self {
+        self.messages.extend(messages);
+        self
+    }
+}
+
+impl ChatCompletionRequestBuilder<Set, Set> {
+    /// Builds the request, with the default values of `ChatCompletionRequest::new` for the optional parameters.
+    pub fn build(self) -> ChatCompletionRequest {
+        ChatCompletionRequest::new(self.model, self.messages)
+    }
+}
+
+fn text(role: ChatCompletionRoles, content: MessageContent) -> ChatCompletionMessage {
+    ChatCompletionMessage {
+        role,
+        content,
+        name: None,
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
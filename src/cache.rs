@@ -0,0 +1,381 @@
+use crate::audit::request_hash;
+use crate::{ChatCompletionRequest, ChatCompletionResponse, ChatCompletionRoles, GroqError};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A store of cached chat completion responses, keyed by a hash of the request.
+///
+/// Implement it to keep the cache in a shared store such as Redis; `MemoryCacheStore` keeps it in memory.
+pub trait CacheStore: Send + Sync {
+    /// Returns the response cached under the key, if any and still valid.
+    fn get(&self, key: &str) -> Option<ChatCompletionResponse>;
+
+    /// Caches a response under the key, replacing the existing one.
+    fn put(&self, key: &str, response: &ChatCompletionResponse);
+}
+
+/// An in-memory `CacheStore` evicting the least recently used responses, and expiring them after a time to live.
+///
+/// Cloning a `MemoryCacheStore` shares its entries.
+#[derive(Debug, Clone)]
+pub struct MemoryCacheStore {
+    capacity: usize,
+    ttl: Duration,
+    entries: Arc<Mutex<MemoryEntries>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryEntries {
+    entries: HashMap<String, MemoryEntry>,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct MemoryEntry {
+    response: ChatCompletionResponse,
+    stored_at: Instant,
+    last_used: u64,
+}
+
+impl MemoryCacheStore {
+    /// Creates a new `MemoryCacheStore`.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of cached responses.
+    /// * `ttl` - How long a response stays valid after it was cached.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            entries: Arc::new(Mutex::new(MemoryEntries::default())),
+        }
+    }
+
+    /// Returns the number of cached responses, including expired ones not evicted yet.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().entries.len()
+    }
+
+    /// Returns true if no response is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    fn get(&self, key: &str) -> Option<ChatCompletionResponse> {
+        let mut state = self.entries.lock().unwrap();
+        state.clock += 1;
+        let clock = state.clock;
+        match state.entries.get_mut(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => {
+                entry.last_used = clock;
+                Some(entry.response.clone())
+            }
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn put(&self, key: &str, response: &ChatCompletionResponse) {
+        let mut state = self.entries.lock().unwrap();
+        state.clock += 1;
+        let clock = state.clock;
+        if !state.entries.contains_key(key) && state.entries.len() >= self.capacity {
+            let ttl = self.ttl;
+            state
+                .entries
+                .retain(|_, entry| entry.stored_at.elapsed() < ttl);
+            if state.entries.len() >= self.capacity {
+                let oldest = state
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    state.entries.remove(&oldest);
+                }
+            }
+        }
+        state.entries.insert(
+            key.to_string(),
+            MemoryEntry {
+                response: response.clone(),
+                stored_at: Instant::now(),
+                last_used: clock,
+            },
+        );
+    }
+}
+
+/// A provider of text embeddings, used by the semantic mode of a `ResponseCache`.
+///
+/// It is called inline before chat completion requests are sent, so it should be a local model or a fast blocking
+/// call.
+pub trait EmbeddingProvider: Send + Sync {
+    /// Returns the embedding vector of the text.
+    ///
+    /// # Arguments
+    /// * `text` - The text to embed.
+    fn embed(&self, text: &str) -> Result<Vec<f32>, GroqError>;
+}
+
+/// An opt-in cache of chat completion responses, returning cached responses for identical deterministic requests.
+///
+/// Only requests with a temperature of 0 and without streaming are cached, keyed by a hash of their model, messages,
+/// seed and every other parameter sent. Cached responses are returned without sending a request, with
+/// `ResponseMeta::cached` set, and are not charged against budgets or rate limits. Cloning a `ResponseCache` shares
+/// its store.
+///
+/// In semantic mode, enabled with `semantic`, a request whose last user message is similar enough to the one of a
+/// cached request also gets its response, as long as every other parameter and message is identical.
+#[derive(Clone)]
+pub struct ResponseCache {
+    store: Arc<dyn CacheStore>,
+    semantic: Option<Arc<SemanticIndex>>,
+}
+
+/// The embeddings of the prompts of cached responses.
+struct SemanticIndex {
+    provider: Box<dyn EmbeddingProvider>,
+    threshold: f32,
+    entries: Mutex<Vec<SemanticEntry>>,
+}
+
+struct SemanticEntry {
+    scope: String,
+    embedding: Vec<f32>,
+    key: String,
+}
+
+/// The outcome of looking a request up in a `ResponseCache`, used to cache its response once received.
+pub(crate) struct CacheLookup {
+    key: String,
+    prompt: Option<(String, Vec<f32>)>,
+    response: Option<ChatCompletionResponse>,
+}
+
+impl CacheLookup {
+    /// Returns the cached response, if any.
+    pub(crate) fn response(&self) -> Option<ChatCompletionResponse> {
+        self.response.clone()
+    }
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache").finish_non_exhaustive()
+    }
+}
+
+impl ResponseCache {
+    /// Creates a new `ResponseCache` keeping the responses in the given store.
+    ///
+    /// # Arguments
+    /// * `store` - The store of the cached responses.
+    pub fn new(store: impl CacheStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            semantic: None,
+        }
+    }
+
+    /// Creates a new `ResponseCache` keeping the responses in a `MemoryCacheStore`.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of cached responses.
+    /// * `ttl` - How long a response stays valid after it was cached.
+    pub fn memory(capacity: usize, ttl: Duration) -> Self {
+        Self::new(MemoryCacheStore::new(capacity, ttl))
+    }
+
+    /// Enables the semantic mode, matching requests by the similarity of their last user message.
+    ///
+    /// # Arguments
+    /// * `provider` - The provider of the embeddings of the messages.
+    /// * `threshold` - The minimum cosine similarity of two messages for a cache hit, such as 0.95.
+    pub fn semantic(mut self, provider: impl EmbeddingProvider + 'static, threshold: f32) -> Self {
+        self.semantic = Some(Arc::new(SemanticIndex {
+            provider: Box::new(provider),
+            threshold,
+            entries: Mutex::new(Vec::new()),
+        }));
+        self
+    }
+
+    /// Looks a request up, returning `None` if the request is not deterministic and can't be cached.
+    ///
+    /// # Arguments
+    /// * `request` - The request.
+    /// * `body` - The JSON body sent for the request.
+    pub(crate) fn lookup(
+        &self,
+        request: &ChatCompletionRequest,
+        body: &Value,
+    ) -> Option<CacheLookup> {
+        if request.temperature != Some(0.0) || request.stream == Some(true) {
+            return None;
+        }
+        let key = request_hash(&[body.to_string().as_bytes()]);
+        let mut lookup = CacheLookup {
+            response: self.store.get(&key),
+            key,
+            prompt: None,
+        };
+        if let (Some(index), Some(position)) = (&self.semantic, last_user_message(request)) {
+            let mut scope = body.clone();
+            scope["messages"][position]["content"] = Value::Null;
+            let scope = request_hash(&[scope.to_string().as_bytes()]);
+            match index.provider.embed(&request.messages[position].content) {
+                Ok(embedding) => {
+                    if lookup.response.is_none() {
+                        lookup.response = index.nearest(&scope, &embedding, self.store.as_ref());
+                    }
+                    lookup.prompt = Some((scope, embedding));
+                }
+                Err(err) => log::warn!("Failed to embed the prompt for the semantic cache: {err}"),
+            }
+        }
+        Some(lookup)
+    }
+
+    /// Caches the response of a request looked up with `lookup`.
+    pub(crate) fn store(&self, lookup: CacheLookup, response: &ChatCompletionResponse) {
+        self.store.put(&lookup.key, response);
+        if let (Some(index), Some((scope, embedding))) = (&self.semantic, lookup.prompt) {
+            let mut entries = index.entries.lock().unwrap();
+            entries.retain(|entry| entry.key != lookup.key);
+            entries.push(SemanticEntry {
+                scope,
+                embedding,
+                key: lookup.key,
+            });
+        }
+    }
+}
+
+impl SemanticIndex {
+    /// Returns the cached response of the most similar prompt in the scope, if similar enough.
+    ///
+    /// Entries whose response is no longer in the store are forgotten.
+    fn nearest(
+        &self,
+        scope: &str,
+        embedding: &[f32],
+        store: &dyn CacheStore,
+    ) -> Option<ChatCompletionResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        loop {
+            let (position, _) = entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.scope == scope)
+                .map(|(position, entry)| (position, cosine_similarity(&entry.embedding, embedding)))
+                .filter(|(_, similarity)| *similarity >= self.threshold)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+            match store.get(&entries[position].key) {
+                Some(response) => return Some(response),
+                None => {
+                    entries.remove(position);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the position of the last user message of a request.
+fn last_user_message(request: &ChatCompletionRequest) -> Option<usize> {
+    request
+        .messages
+        .iter()
+        .rposition(|message| matches!(message.role, ChatCompletionRoles::User))
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response(id: &str) -> ChatCompletionResponse {
+        serde_json::from_value(json!({
+            "id": id, "object": "chat.completion", "created": 0, "model": "llama3-8b-8192",
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}, "logprobs": null, "finish_reason": "stop"}]
+        }))
+        .unwrap()
+    }
+
+    /// Embeds texts as the counts of a few keywords.
+    struct KeywordEmbeddings;
+
+    impl EmbeddingProvider for KeywordEmbeddings {
+        fn embed(&self, text: &str) -> Result<Vec<f32>, GroqError> {
+            let text = text.to_lowercase();
+            Ok(["refund", "shipping", "password"]
+                .iter()
+                .map(|keyword| text.matches(keyword).count() as f32)
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_semantic_cache() {
+        let cache =
+            ResponseCache::memory(16, Duration::from_secs(60)).semantic(KeywordEmbeddings, 0.9);
+        let request = |prompt: &str| {
+            ChatCompletionRequest::new("llama3-8b-8192", crate::messages![user: prompt])
+                .temperature(0.0)
+        };
+        let body = |request: &ChatCompletionRequest| json!({"messages": [{"content": request.messages[0].content}]});
+
+        let first = request("How do I get a refund?");
+        let lookup = cache.lookup(&first, &body(&first)).unwrap();
+        assert!(lookup.response().is_none());
+        cache.store(lookup, &response("refund"));
+
+        let similar = request("Refund policy, please");
+        let hit = cache.lookup(&similar, &body(&similar)).unwrap().response();
+        assert_eq!(hit.unwrap().id, "refund");
+        let other = request("I forgot my password");
+        assert!(cache
+            .lookup(&other, &body(&other))
+            .unwrap()
+            .response()
+            .is_none());
+        assert!(cache
+            .lookup(&first.clone().temperature(1.0), &body(&first))
+            .is_none());
+    }
+
+    #[test]
+    fn test_memory_cache_store_evicts_least_recently_used() {
+        let store = MemoryCacheStore::new(2, Duration::from_secs(60));
+        store.put("a", &response("a"));
+        store.put("b", &response("b"));
+        assert!(store.get("a").is_some());
+        store.put("c", &response("c"));
+        assert_eq!(store.len(), 2);
+        assert!(store.get("b").is_none());
+        assert_eq!(store.get("a").unwrap().id, "a");
+
+        let expired = MemoryCacheStore::new(2, Duration::ZERO);
+        expired.put("a", &response("a"));
+        assert!(expired.get("a").is_none());
+        assert!(expired.is_empty());
+    }
+}
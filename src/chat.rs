@@ -0,0 +1,772 @@
+use crate::tokens::MESSAGE_OVERHEAD_TOKENS;
+#[cfg(feature = "stream")]
+use crate::StreamApi;
+use crate::{
+    ChatApi, ChatCompletionMessage, ChatCompletionRequest, ChatCompletionResponse,
+    ChatCompletionRoles, GroqError, MessageContent, Usage,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents how a `GroqChat` shortens a conversation that exceeds its token budget.
+///
+/// - `DropOldest`: The oldest messages are left out until the conversation fits. This is the default.
+/// - `TruncateOldest`: The oldest messages are left out, and the oldest one kept is cut to its most recent text to use
+///   the remaining budget.
+pub enum TrimStrategy {
+    #[default]
+    DropOldest,
+    TruncateOldest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Configures how a `GroqChat` replaces older turns with a summary once its history grows too long.
+///
+/// Before a turn is sent, if the estimated tokens of the history exceed `threshold_tokens`, every message but the
+/// `keep_recent` most recent ones is summarized by `model` (usually a small, cheap model) and replaced with a single
+/// system message holding the summary.
+///
+/// - `threshold_tokens`: The estimated number of history tokens above which the history is compacted.
+/// - `model`: The model writing the summary.
+/// - `keep_recent`: The number of most recent messages kept verbatim.
+/// - `instructions`: The system prompt of the summary request.
+pub struct Compaction {
+    pub threshold_tokens: u64,
+    pub model: String,
+    pub keep_recent: usize,
+    pub instructions: String,
+}
+
+impl Compaction {
+    /// Creates a new `Compaction` keeping the 4 most recent messages.
+    ///
+    /// # Arguments
+    /// * `threshold_tokens` - The estimated number of history tokens above which the history is compacted.
+    /// * `model` - The model writing the summary.
+    pub fn new(threshold_tokens: u64, model: impl Into<String>) -> Self {
+        Self {
+            threshold_tokens,
+            model: model.into(),
+            keep_recent: 4,
+            instructions: "Summarize the following conversation in a few sentences. Keep names, facts, \
+                decisions and open questions, so that the conversation can continue from the summary."
+                .to_string(),
+        }
+    }
+
+    /// Sets the number of most recent messages kept verbatim.
+    ///
+    /// # Arguments
+    /// * `keep_recent` - The number of messages.
+    pub fn keep_recent(mut self, keep_recent: usize) -> Self {
+        self.keep_recent = keep_recent;
+        self
+    }
+
+    /// Sets the system prompt of the summary request.
+    ///
+    /// # Arguments
+    /// * `instructions` - The instructions given to the summarizing model.
+    pub fn instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = instructions.into();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Represents the usage accumulated by a `GroqChat`, including the requests summarizing its history.
+///
+/// - `turns`: The number of successful turns.
+/// - `prompt_tokens`: The number of prompt tokens reported by the API.
+/// - `completion_tokens`: The number of completion tokens reported by the API.
+pub struct ChatUsage {
+    pub turns: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl ChatUsage {
+    /// Returns the number of prompt and completion tokens.
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    fn add(&mut self, response: &ChatCompletionResponse) {
+        if let Some(usage) = &response.usage {
+            self.add_usage(usage);
+        }
+    }
+
+    fn add_usage(&mut self, usage: &Usage) {
+        self.prompt_tokens += usage.prompt_tokens;
+        self.completion_tokens += usage.completion_tokens;
+    }
+}
+
+/// A conversation with a model, keeping the message history between turns.
+///
+/// Every call to `send` sends the system prompt and the history, then stores the assistant reply, so chatbots don't
+/// have to do the bookkeeping themselves. With `max_context_tokens`, only the most recent messages fitting in the budget
+/// are sent, as estimated at roughly four characters per token; the full history is still kept. With `compaction`,
+/// older turns are summarized instead, so that long-running conversations stay coherent and affordable.
+///
+/// `to_json` and `save` persist the conversation with its settings and usage, so that it can be restored with
+/// `from_json` or `load` after a restart. Request options that only apply to one call, such as the idempotency key,
+/// are not persisted.
+///
+/// ```no_run
+/// use groq_api_rust::{AsyncGroqClient, GroqChat};
+///
+/// # async fn example() -> Result<(), groq_api_rust::GroqError> {
+/// let client = AsyncGroqClient::new("my_api_key".to_string(), None).await;
+/// let mut chat = GroqChat::new(&client, "llama3-8b-8192").system_prompt("You are a helpful assistant.");
+/// let reply = chat.send("What is the capital of France?").await?;
+/// let follow_up = chat.send("And of Italy?").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct GroqChat<'a> {
+    client: &'a dyn ChatApi,
+    request: ChatCompletionRequest,
+    system_prompt: Option<MessageContent>,
+    history: Vec<ChatCompletionMessage>,
+    max_context_tokens: Option<u64>,
+    trim_strategy: TrimStrategy,
+    keep_system_prompt: bool,
+    compaction: Option<Compaction>,
+    usage: ChatUsage,
+}
+
+impl<'a> GroqChat<'a> {
+    /// Creates a new `GroqChat` with an empty history.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+    /// * `model` - The model to chat with.
+    pub fn new(client: &'a dyn ChatApi, model: impl Into<String>) -> Self {
+        Self::from_request(client, ChatCompletionRequest::new(model, Vec::new()))
+    }
+
+    /// Creates a new `GroqChat` sending every turn with the model and parameters of the given request.
+    ///
+    /// The messages of the request are ignored.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+    /// * `request` - The request holding the model, temperature, max tokens and other options.
+    pub fn from_request(client: &'a dyn ChatApi, mut request: ChatCompletionRequest) -> Self {
+        request.messages.clear();
+        Self {
+            client,
+            request,
+            system_prompt: None,
+            history: Vec::new(),
+            max_context_tokens: None,
+            trim_strategy: TrimStrategy::default(),
+            keep_system_prompt: true,
+            compaction: None,
+            usage: ChatUsage::default(),
+        }
+    }
+
+    /// Sets the system prompt sent before the history on every turn.
+    ///
+    /// # Arguments
+    /// * `prompt` - The content of the system message.
+    pub fn system_prompt(mut self, prompt: impl Into<MessageContent>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Sets the maximum estimated number of tokens of the messages sent on every turn.
+    ///
+    /// Leave room for the completion: the budget should be the context window of the model minus `max_tokens`.
+    /// The latest message is always sent, even if it exceeds the budget on its own.
+    ///
+    /// # Arguments
+    /// * `max_tokens` - The maximum number of prompt tokens.
+    pub fn max_context_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_context_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Sets how the conversation is shortened when it exceeds `max_context_tokens`.
+    ///
+    /// # Arguments
+    /// * `strategy` - The `TrimStrategy` to apply.
+    pub fn trim_strategy(mut self, strategy: TrimStrategy) -> Self {
+        self.trim_strategy = strategy;
+        self
+    }
+
+    /// Sets whether the system prompt is always sent when the conversation is trimmed.
+    ///
+    /// When disabled, the system prompt is treated as the oldest message and left out first.
+    ///
+    /// # Arguments
+    /// * `keep` - If true (the default), the system prompt counts against the budget but is never left out.
+    pub fn keep_system_prompt(mut self, keep: bool) -> Self {
+        self.keep_system_prompt = keep;
+        self
+    }
+
+    /// Sets how older turns are summarized once the history grows too long.
+    ///
+    /// # Arguments
+    /// * `compaction` - The `Compaction` settings.
+    pub fn compaction(mut self, compaction: Compaction) -> Self {
+        self.compaction = Some(compaction);
+        self
+    }
+
+    /// Replaces the system prompt of the next turns.
+    ///
+    /// # Arguments
+    /// * `prompt` - The content of the system message.
+    pub fn set_system_prompt(&mut self, prompt: impl Into<MessageContent>) {
+        self.system_prompt = Some(prompt.into());
+    }
+
+    /// Removes the system prompt, so that the next turns only send the history.
+    pub fn remove_system_prompt(&mut self) {
+        self.system_prompt = None;
+    }
+
+    /// Returns the model the conversation is sent to.
+    pub fn model(&self) -> &str {
+        &self.request.model
+    }
+
+    /// Switches the next turns to another model, keeping the history.
+    ///
+    /// # Arguments
+    /// * `model` - The model to chat with.
+    pub fn set_model(&mut self, model: impl Into<String>) {
+        self.request.model = model.into();
+    }
+
+    /// Returns the usage accumulated by this conversation.
+    pub fn usage(&self) -> ChatUsage {
+        self.usage
+    }
+
+    /// Returns the user and assistant messages exchanged so far.
+    pub fn history(&self) -> &[ChatCompletionMessage] {
+        &self.history
+    }
+
+    /// Removes every message from the history, keeping the system prompt.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// Returns the messages sent on the next turn: the system prompt, if any, followed by the history trimmed to
+    /// `max_context_tokens`.
+    pub fn messages(&self) -> Vec<ChatCompletionMessage> {
+        let system = self
+            .system_prompt
+            .as_ref()
+            .map(|prompt| ChatCompletionMessage {
+                role: ChatCompletionRoles::System,
+                content: prompt.clone(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            });
+        let Some(budget) = self.max_context_tokens else {
+            return system
+                .into_iter()
+                .chain(self.history.iter().cloned())
+                .collect();
+        };
+
+        let mut remaining = budget;
+        if let Some(system) = system.as_ref().filter(|_| self.keep_system_prompt) {
+            remaining = remaining.saturating_sub(system.token_estimate());
+        }
+        let mut kept = Vec::new();
+        let mut complete = true;
+        for message in self.history.iter().rev() {
+            let tokens = message.token_estimate();
+            if tokens <= remaining || kept.is_empty() {
+                remaining = remaining.saturating_sub(tokens);
+                kept.push(message.clone());
+                continue;
+            }
+            complete = false;
+            if self.trim_strategy == TrimStrategy::TruncateOldest
+                && remaining > MESSAGE_OVERHEAD_TOKENS
+            {
+                kept.push(truncate_start(message, remaining - MESSAGE_OVERHEAD_TOKENS));
+            }
+            break;
+        }
+        let system = system.filter(|system| {
+            self.keep_system_prompt || (complete && system.token_estimate() <= remaining)
+        });
+        system.into_iter().chain(kept.into_iter().rev()).collect()
+    }
+
+    /// Sends a user message and returns the assistant reply, adding both to the history.
+    ///
+    /// If the request fails, the history is left unchanged so that the turn can be sent again.
+    ///
+    /// # Arguments
+    /// * `text` - The content of the user message.
+    pub async fn send(&mut self, text: impl Into<MessageContent>) -> Result<String, GroqError> {
+        let request = self.start_turn(text.into()).await?;
+        let response = match self.client.chat_completion(request).await {
+            Ok(response) => response,
+            Err(err) => {
+                self.history.pop();
+                return Err(err);
+            }
+        };
+        self.usage.add(&response);
+        let reply = response.to_string();
+        self.finish_turn(reply.clone());
+        Ok(reply)
+    }
+
+    /// Sends a user message and streams the assistant reply, adding both to the history once the stream ends.
+    ///
+    /// `on_content` is called with the new text of every chunk, such as to print the reply as it is generated. If the
+    /// request or the stream fails, the history is left unchanged so that the turn can be sent again.
+    ///
+    /// # Arguments
+    /// * `client` - The client streaming the reply, usually the client of the conversation.
+    /// * `text` - The content of the user message.
+    /// * `on_content` - Called with the new text of every chunk.
+    #[cfg(feature = "stream")]
+    pub async fn send_streaming(
+        &mut self,
+        client: &dyn StreamApi,
+        text: impl Into<MessageContent>,
+        mut on_content: impl FnMut(&str),
+    ) -> Result<String, GroqError> {
+        let request = self.start_turn(text.into()).await?;
+        let mut stream = match client.chat_completion_stream(request).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                self.history.pop();
+                return Err(err);
+            }
+        };
+        let mut reply = String::new();
+        let mut usage = None;
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    self.history.pop();
+                    return Err(err);
+                }
+            };
+            on_content(chunk.content());
+            reply.push_str(chunk.content());
+            usage = usage.or(chunk.usage());
+        }
+        if let Some(usage) = &usage {
+            self.usage.add_usage(usage);
+        }
+        self.finish_turn(reply.clone());
+        Ok(reply)
+    }
+
+    /// Compacts the history if needed and adds the user message, returning the request of the turn.
+    async fn start_turn(
+        &mut self,
+        text: MessageContent,
+    ) -> Result<ChatCompletionRequest, GroqError> {
+        let over_threshold = self.compaction.as_ref().is_some_and(|compaction| {
+            self.history
+                .iter()
+                .map(ChatCompletionMessage::token_estimate)
+                .sum::<u64>()
+                > compaction.threshold_tokens
+        });
+        if over_threshold {
+            self.compact().await?;
+        }
+        self.history.push(ChatCompletionMessage {
+            role: ChatCompletionRoles::User,
+            content: text,
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        });
+        let mut request = self.request.clone();
+        request.messages = self.messages();
+        Ok(request)
+    }
+
+    /// Adds the assistant reply of a successful turn to the history.
+    fn finish_turn(&mut self, reply: String) {
+        self.usage.turns += 1;
+        self.history.push(ChatCompletionMessage {
+            role: ChatCompletionRoles::Assistant,
+            content: reply.into(),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        });
+    }
+
+    /// Replaces every message but the most recent ones with a summary written by the compaction model.
+    ///
+    /// Called by `send` once the history exceeds the compaction threshold. Does nothing without `compaction` or when
+    /// there are no older messages to summarize. If the summary request fails, the history is left unchanged.
+    pub async fn compact(&mut self) -> Result<(), GroqError> {
+        let Some(compaction) = &self.compaction else {
+            return Ok(());
+        };
+        let older = self.history.len().saturating_sub(compaction.keep_recent);
+        if older == 0 {
+            return Ok(());
+        }
+        let transcript = self.history[..older]
+            .iter()
+            .map(|message| format!("{}: {}", role_name(&message.role), message.content))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let request = ChatCompletionRequest::new(
+            &compaction.model,
+            vec![
+                ChatCompletionMessage {
+                    role: ChatCompletionRoles::System,
+                    content: compaction.instructions.clone().into(),
+                    name: None,
+                    tool_calls: None,
+                    tool_call_id: None,
+                },
+                ChatCompletionMessage {
+                    role: ChatCompletionRoles::User,
+                    content: transcript.into(),
+                    name: None,
+                    tool_calls: None,
+                    tool_call_id: None,
+                },
+            ],
+        );
+        let response = self.client.chat_completion(request).await?;
+        self.usage.add(&response);
+        let summary = response.to_string();
+        self.history.splice(
+            ..older,
+            [ChatCompletionMessage {
+                role: ChatCompletionRoles::System,
+                content: format!("Summary of the earlier conversation:\n{summary}").into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        );
+        Ok(())
+    }
+}
+
+impl<'a> GroqChat<'a> {
+    /// Serializes the conversation, with its settings and usage, to JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&SavedChat {
+            model: self.request.model.clone(),
+            temperature: self.request.temperature,
+            max_tokens: self.request.max_tokens,
+            auto_max_tokens: self.request.auto_max_tokens,
+            top_p: self.request.top_p,
+            stop: self.request.stop.clone(),
+            seed: self.request.seed,
+            fallback_models: self.request.fallback_models.clone(),
+            system_prompt: self.system_prompt.clone(),
+            history: self.history.clone(),
+            max_context_tokens: self.max_context_tokens,
+            trim_strategy: self.trim_strategy,
+            keep_system_prompt: self.keep_system_prompt,
+            compaction: self.compaction.clone(),
+            usage: self.usage,
+        })
+    }
+
+    /// Restores a conversation serialized with `to_json`.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+    /// * `json` - The serialized conversation.
+    pub fn from_json(client: &'a dyn ChatApi, json: &str) -> serde_json::Result<Self> {
+        let saved: SavedChat = serde_json::from_str(json)?;
+        let mut request = ChatCompletionRequest::new(&saved.model, Vec::new());
+        request.temperature = saved.temperature;
+        request.max_tokens = saved.max_tokens;
+        request.auto_max_tokens = saved.auto_max_tokens;
+        request.top_p = saved.top_p;
+        request.stop = saved.stop;
+        request.seed = saved.seed;
+        request.fallback_models = saved.fallback_models;
+        Ok(Self {
+            client,
+            request,
+            system_prompt: saved.system_prompt,
+            history: saved.history,
+            max_context_tokens: saved.max_context_tokens,
+            trim_strategy: saved.trim_strategy,
+            keep_system_prompt: saved.keep_system_prompt,
+            compaction: saved.compaction,
+            usage: saved.usage,
+        })
+    }
+
+    /// Writes the conversation to the given file as JSON, replacing its content.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json()?)
+    }
+
+    /// Restores a conversation written with `save`.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+    /// * `path` - The path of the file.
+    pub fn load(client: &'a dyn ChatApi, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::from_json(client, &std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// The persisted form of a `GroqChat`.
+#[derive(Serialize, Deserialize)]
+struct SavedChat {
+    model: String,
+    temperature: Option<f64>,
+    max_tokens: Option<u32>,
+    #[serde(default)]
+    auto_max_tokens: Option<u32>,
+    top_p: Option<f64>,
+    stop: Option<Vec<String>>,
+    seed: Option<u64>,
+    #[serde(default)]
+    fallback_models: Vec<String>,
+    system_prompt: Option<MessageContent>,
+    history: Vec<ChatCompletionMessage>,
+    max_context_tokens: Option<u64>,
+    #[serde(default)]
+    trim_strategy: TrimStrategy,
+    keep_system_prompt: bool,
+    compaction: Option<Compaction>,
+    #[serde(default)]
+    usage: ChatUsage,
+}
+
+fn role_name(role: &ChatCompletionRoles) -> &'static str {
+    match role {
+        ChatCompletionRoles::System => "system",
+        ChatCompletionRoles::User => "user",
+        ChatCompletionRoles::Assistant => "assistant",
+        ChatCompletionRoles::Tool => "tool",
+    }
+}
+
+/// Keeps the end of the message content fitting in the given number of tokens.
+fn truncate_start(message: &ChatCompletionMessage, tokens: u64) -> ChatCompletionMessage {
+    let max_len = usize::try_from(tokens.saturating_mul(4)).unwrap_or(usize::MAX);
+    let content = &message.content;
+    let start = content
+        .char_indices()
+        .map(|(index, _)| index)
+        .find(|index| content.len() - index <= max_len)
+        .unwrap_or(content.len());
+    ChatCompletionMessage {
+        content: content[start..].into(),
+        ..message.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{no_choices, reply, server_error, Answer, ChatFn};
+
+    /// Replies with the number of messages received, failing on "fail" and without choices on "empty".
+    fn counting_api() -> ChatFn<impl Answer> {
+        ChatFn(|request: ChatCompletionRequest| {
+            match request.messages.last().unwrap().content.as_str() {
+                "fail" => Err(server_error()),
+                "empty" => no_choices(&request.model),
+                _ => reply(&request.model, &request.messages.len().to_string()),
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_session_keeps_history() {
+        let client = counting_api();
+        let mut chat = GroqChat::new(&client, "llama3-8b-8192").system_prompt("Be brief.");
+        assert_eq!(chat.send("Hello").await.unwrap(), "2");
+        assert!(chat.send("fail").await.is_err());
+        assert_eq!(chat.send("How are you?").await.unwrap(), "4");
+
+        let history = chat.history();
+        assert_eq!(history.len(), 4);
+        assert!(matches!(history[1].role, ChatCompletionRoles::Assistant));
+        assert_eq!(history[2].content, "How are you?");
+        assert!(matches!(
+            chat.messages()[0].role,
+            ChatCompletionRoles::System
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_session_handles_empty_and_failed_replies() {
+        let client = counting_api();
+        let mut chat = GroqChat::new(&client, "llama3-8b-8192");
+        assert_eq!(chat.send("empty").await.unwrap(), "");
+        assert_eq!(chat.history().len(), 2);
+        assert_eq!(chat.history()[1].content, "");
+
+        assert!(matches!(
+            chat.send("fail").await,
+            Err(GroqError::ServerError { status: 500, .. })
+        ));
+        assert_eq!(chat.history().len(), 2);
+        assert_eq!(chat.usage().turns, 1);
+        chat.clear();
+        assert!(chat.history().is_empty());
+        assert_eq!(chat.send("Hello").await.unwrap(), "1");
+    }
+
+    #[cfg(all(feature = "mock", feature = "stream"))]
+    #[tokio::test]
+    async fn test_session_streams_replies() {
+        let client = crate::MockGroqClient::new().reply("Hello there!").rule(
+            |request| request.model == "unknown-model",
+            |_| {
+                Err(GroqError::CassetteMiss {
+                    method: "POST".to_string(),
+                    url: "/chat/completions".to_string(),
+                })
+            },
+        );
+        let mut chat = GroqChat::new(&client, "llama3-8b-8192");
+        chat.set_system_prompt("Be brief.");
+        let mut printed = String::new();
+        let reply = chat
+            .send_streaming(&client, "Hi", |text| printed.push_str(text))
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello there!");
+        assert_eq!(printed, reply);
+        assert_eq!(chat.history()[1].content, "Hello there!");
+        assert_eq!(chat.usage().turns, 1);
+        assert!(chat.usage().completion_tokens > 0);
+
+        chat.set_model("unknown-model");
+        assert!(chat.send_streaming(&client, "Hi", |_| {}).await.is_err());
+        assert_eq!(chat.history().len(), 2);
+        chat.remove_system_prompt();
+        assert_eq!(chat.messages().len(), 2);
+    }
+
+    fn message(role: ChatCompletionRoles, content: &str) -> ChatCompletionMessage {
+        ChatCompletionMessage {
+            role,
+            content: content.into(),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    #[test]
+    fn test_session_trims_to_budget() {
+        let client = counting_api();
+        // The system prompt takes 4 + 4 = 8 estimated tokens, and every other message 3 + 4 = 7.
+        let mut chat = GroqChat::new(&client, "llama3-8b-8192")
+            .system_prompt("Be brief, ok?")
+            .max_context_tokens(29);
+        for content in [
+            "first turn!!",
+            "first reply!",
+            "second turn!",
+            "second reply",
+        ] {
+            chat.history
+                .push(message(ChatCompletionRoles::User, content));
+        }
+
+        let contents = |chat: &GroqChat| {
+            chat.messages()
+                .into_iter()
+                .map(|message| message.content)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            contents(&chat),
+            [
+                "Be brief, ok?",
+                "first reply!",
+                "second turn!",
+                "second reply"
+            ]
+        );
+
+        let truncated = chat
+            .clone()
+            .trim_strategy(TrimStrategy::TruncateOldest)
+            .max_context_tokens(35);
+        assert_eq!(contents(&truncated)[1], "t turn!!");
+
+        // Without the system prompt, the whole history fits.
+        let chat = chat.keep_system_prompt(false);
+        assert_eq!(contents(&chat).len(), 4);
+        assert_eq!(contents(&chat)[0], "first turn!!");
+        assert_eq!(chat.history().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_session_compacts_history() {
+        let client = counting_api();
+        let mut chat = GroqChat::new(&client, "llama3-70b-8192")
+            .compaction(Compaction::new(20, "llama3-8b-8192").keep_recent(2));
+        chat.send("Hello").await.unwrap();
+        chat.send("How are you?").await.unwrap();
+        assert_eq!(chat.history().len(), 4);
+
+        // The history now takes 2 + 1 + 3 + 1 + 4 * 4 = 23 estimated tokens, so the first turn is summarized.
+        chat.send("Bye").await.unwrap();
+        let history = chat.history();
+        assert_eq!(history.len(), 5);
+        assert!(matches!(history[0].role, ChatCompletionRoles::System));
+        // The summary request holds the instructions and the transcript of the first turn.
+        assert_eq!(
+            history[0].content,
+            "Summary of the earlier conversation:\n2"
+        );
+        assert_eq!(history[1].content, "How are you?");
+    }
+
+    #[tokio::test]
+    async fn test_session_round_trips_through_json() {
+        let client = counting_api();
+        let mut chat = GroqChat::from_request(
+            &client,
+            ChatCompletionRequest::new("llama3-8b-8192", Vec::new()).temperature(0.2),
+        )
+        .system_prompt("Be brief.")
+        .max_context_tokens(1000)
+        .compaction(Compaction::new(500, "llama3-8b-8192"));
+        chat.send("Hello").await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("groq-chat-{}.json", std::process::id()));
+        chat.save(&path).unwrap();
+        let restored = GroqChat::load(&client, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.to_json().unwrap(), chat.to_json().unwrap());
+        assert_eq!(restored.history().len(), 2);
+        assert_eq!(restored.request.temperature, Some(0.2));
+        assert_eq!(restored.usage().turns, 1);
+    }
+}
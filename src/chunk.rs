@@ -0,0 +1,201 @@
+//! Splitting of long texts into chunks fitting a token budget, for retrieval pipelines and map-reduce processing.
+//!
+//! ```
+//! use groq_api_rust::chunk::TextChunker;
+//!
+//! let text = "Rust is fast. It is also safe.\n\nGroq serves models quickly.";
+//! let chunks = TextChunker::new(12).overlap(5).split(text);
+//! assert_eq!(chunks, vec!["Rust is fast. It is also safe.", "It is also safe.\n\nGroq serves models quickly."]);
+//! ```
+
+use crate::tokens::estimate_text_tokens;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Splits text into chunks of at most `max_tokens` estimated tokens, optionally overlapping.
+///
+/// Chunks are cut between paragraphs where possible, then between sentences, and only cut inside a sentence when a
+/// single sentence exceeds the budget. With an overlap, every chunk but the first starts with the last sentences of
+/// the previous one, up to `overlap_tokens`, so that context spanning a cut is found in both chunks. Whitespace around
+/// the chunks is trimmed. Tokens are estimated with the same heuristic as `count_tokens`.
+pub struct TextChunker {
+    max_tokens: u64,
+    overlap_tokens: u64,
+}
+
+impl TextChunker {
+    /// Creates a new `TextChunker` without overlap.
+    ///
+    /// # Arguments
+    /// * `max_tokens` - The maximum number of estimated tokens of a chunk.
+    pub fn new(max_tokens: u64) -> Self {
+        Self {
+            max_tokens: max_tokens.max(1),
+            overlap_tokens: 0,
+        }
+    }
+
+    /// Sets how many tokens of the end of a chunk are repeated at the start of the next one.
+    ///
+    /// Only whole sentences are repeated, and an overlap leaving no room for new text in a chunk is dropped.
+    ///
+    /// # Arguments
+    /// * `tokens` - The maximum number of estimated tokens of the overlap.
+    pub fn overlap(mut self, tokens: u64) -> Self {
+        self.overlap_tokens = tokens;
+        self
+    }
+
+    /// Splits the text into chunks.
+    ///
+    /// # Arguments
+    /// * `text` - The text to split.
+    pub fn split(&self, text: &str) -> Vec<String> {
+        let max_tokens = self.max_tokens;
+        let mut units = Vec::new();
+        for paragraph in text.split_inclusive("\n\n") {
+            if estimate_text_tokens(paragraph) <= max_tokens {
+                units.push(paragraph);
+                continue;
+            }
+            for sentence in sentences(paragraph) {
+                if estimate_text_tokens(sentence) <= max_tokens {
+                    units.push(sentence);
+                } else {
+                    units.extend(hard_split(sentence, max_tokens));
+                }
+            }
+        }
+
+        let mut chunks = Vec::new();
+        let mut current = String::new();
+        let mut fresh = false;
+        for unit in units {
+            if fresh && estimate_text_tokens(&current) + estimate_text_tokens(unit) > max_tokens {
+                let tail = self.overlap_tail(&current);
+                let tail = if estimate_text_tokens(tail) + estimate_text_tokens(unit) <= max_tokens
+                {
+                    tail.to_string()
+                } else {
+                    String::new()
+                };
+                chunks.push(std::mem::replace(&mut current, tail));
+            }
+            current.push_str(unit);
+            fresh = true;
+        }
+        chunks.push(current);
+        chunks
+            .into_iter()
+            .map(|chunk| chunk.trim().to_string())
+            .filter(|chunk| !chunk.is_empty())
+            .collect()
+    }
+
+    /// Returns the last sentences of a chunk fitting in the overlap.
+    fn overlap_tail<'t>(&self, chunk: &'t str) -> &'t str {
+        let mut start = chunk.len();
+        let mut tokens = 0;
+        for sentence in sentences(chunk).into_iter().rev() {
+            tokens += estimate_text_tokens(sentence);
+            if tokens > self.overlap_tokens {
+                break;
+            }
+            start -= sentence.len();
+        }
+        &chunk[start..]
+    }
+}
+
+/// Splits text into chunks of at most `max_tokens` estimated tokens, without overlap.
+///
+/// # Arguments
+/// * `text` - The text to split.
+/// * `max_tokens` - The maximum number of estimated tokens of a chunk.
+pub fn chunk_text(text: &str, max_tokens: u64) -> Vec<String> {
+    TextChunker::new(max_tokens).split(text)
+}
+
+/// Splits text after every `.`, `!` or `?` followed by whitespace, keeping the whitespace with the sentence.
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?')
+            || !chars.peek().is_some_and(|(_, next)| next.is_whitespace())
+        {
+            continue;
+        }
+        while let Some((_, next)) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            chars.next();
+        }
+        let end = chars.peek().map_or(text.len(), |(offset, _)| *offset);
+        sentences.push(&text[start..end]);
+        start = end;
+    }
+    if start < text.len() {
+        sentences.push(&text[start..]);
+    }
+    sentences
+}
+
+/// Splits text into pieces of at most `max_tokens` estimated tokens, on character boundaries.
+fn hard_split(text: &str, max_tokens: u64) -> Vec<&str> {
+    let max_bytes = (max_tokens * 4) as usize;
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = (start + max_bytes).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == start {
+            end = start + text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        pieces.push(&text[start..end]);
+        start = end;
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_text_respects_boundaries() {
+        let text = "First paragraph, short.\n\nSecond one. It has two sentences!\n\n".to_string()
+            + &"x".repeat(50);
+        let chunks = chunk_text(&text, 8);
+        assert_eq!(
+            chunks,
+            vec![
+                "First paragraph, short.",
+                "Second one.",
+                "It has two sentences!",
+                &"x".repeat(32),
+                &"x".repeat(18),
+            ]
+        );
+        assert!(chunks.iter().all(|chunk| estimate_text_tokens(chunk) <= 8));
+        assert_eq!(chunk_text(&text, 1000).len(), 1);
+    }
+
+    #[test]
+    fn test_chunk_text_overlaps() {
+        let text = "One two three. Four five six. Seven eight nine. Ten eleven.";
+        let chunks = TextChunker::new(10).overlap(5).split(text);
+        assert_eq!(
+            chunks,
+            vec![
+                "One two three. Four five six.",
+                "Four five six. Seven eight nine.",
+                "Seven eight nine. Ten eleven.",
+            ]
+        );
+        assert_eq!(TextChunker::new(10).split(text).len(), 2);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::GroqError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+/// An opt-in circuit breaker that stops sending requests to an endpoint that keeps failing.
+///
+/// After `failure_threshold` consecutive server errors, timeouts or connection failures on an endpoint, the circuit opens
+/// and requests to that endpoint fail immediately with `GroqError::CircuitOpen`. Once `cooldown` has elapsed, a single
+/// probe request is let through: if it succeeds the circuit closes again, otherwise it stays open for another cooldown.
+/// A probe that is cancelled, or fails before reaching the API, keeps the circuit open too.
+/// Cloning a `CircuitBreaker` shares its state.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    circuits: Arc<Mutex<HashMap<String, CircuitState>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CircuitState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+impl CircuitBreaker {
+    /// Creates a new `CircuitBreaker`.
+    ///
+    /// # Arguments
+    /// * `failure_threshold` - The number of consecutive failures after which the circuit opens.
+    /// * `cooldown` - How long the circuit stays open before a probe request is allowed.
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            circuits: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns an error if requests to the given endpoint must not be sent right now, or the permit to send one.
+    pub(crate) fn check(&self, endpoint: &str) -> Result<CircuitPermit<'_>, GroqError> {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|err| err.into_inner());
+        let state = circuits
+            .entry(endpoint.to_string())
+            .or_insert(CircuitState::Closed { failures: 0 });
+        let now = Instant::now();
+        let permit = |probe| CircuitPermit {
+            breaker: self,
+            endpoint: endpoint.to_string(),
+            probe,
+            recorded: false,
+        };
+        match *state {
+            CircuitState::Closed { .. } => Ok(permit(false)),
+            CircuitState::Open { until } if now >= until => {
+                *state = CircuitState::HalfOpen;
+                Ok(permit(true))
+            }
+            CircuitState::Open { until } => Err(GroqError::CircuitOpen {
+                endpoint: endpoint.to_string(),
+                retry_in: until - now,
+            }),
+            // A probe is already in flight.
+            CircuitState::HalfOpen => Err(GroqError::CircuitOpen {
+                endpoint: endpoint.to_string(),
+                retry_in: Duration::ZERO,
+            }),
+        }
+    }
+
+    /// Records the outcome of a request sent to the given endpoint.
+    fn record(&self, endpoint: &str, failed: bool) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|err| err.into_inner());
+        let state = circuits
+            .entry(endpoint.to_string())
+            .or_insert(CircuitState::Closed { failures: 0 });
+        let open = CircuitState::Open {
+            until: Instant::now() + self.cooldown,
+        };
+        *state = match (*state, failed) {
+            (CircuitState::Closed { .. }, false) | (CircuitState::HalfOpen, false) => {
+                CircuitState::Closed { failures: 0 }
+            }
+            (CircuitState::Closed { failures }, true) if failures + 1 >= self.failure_threshold => {
+                open
+            }
+            (CircuitState::Closed { failures }, true) => CircuitState::Closed {
+                failures: failures + 1,
+            },
+            (CircuitState::HalfOpen, true) => open,
+            // Requests sent before the circuit opened don't change its state.
+            (state @ CircuitState::Open { .. }, _) => state,
+        };
+    }
+}
+
+/// The permission to send one request through a `CircuitBreaker`, returned by `check`.
+///
+/// Dropping the permit of a probe without recording its outcome, as when building the request fails or its future is
+/// cancelled, opens the circuit for another cooldown instead of leaving it half-open for good.
+pub(crate) struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    endpoint: String,
+    probe: bool,
+    recorded: bool,
+}
+
+impl CircuitPermit<'_> {
+    /// Records the outcome of the request.
+    pub(crate) fn record(mut self, failed: bool) {
+        self.recorded = true;
+        self.breaker.record(&self.endpoint, failed);
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if !self.probe || self.recorded {
+            return;
+        }
+        let mut circuits = self
+            .breaker
+            .circuits
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if let Some(state @ CircuitState::HalfOpen) = circuits.get_mut(&self.endpoint) {
+            *state = CircuitState::Open {
+                until: Instant::now() + self.breaker.cooldown,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_opens_and_half_opens() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(20));
+        let endpoint = "https://api.groq.com/openai/v1/chat/completions";
+
+        breaker.check(endpoint).unwrap().record(true);
+        breaker.check(endpoint).unwrap().record(true);
+        assert!(matches!(
+            breaker.check(endpoint),
+            Err(GroqError::CircuitOpen { .. })
+        ));
+        assert!(breaker
+            .check("https://api.groq.com/openai/v1/audio/transcriptions")
+            .is_ok());
+
+        std::thread::sleep(Duration::from_millis(25));
+        let probe = breaker.check(endpoint).unwrap();
+        assert!(breaker.check(endpoint).is_err());
+        probe.record(false);
+        assert!(breaker.check(endpoint).is_ok());
+    }
+
+    #[test]
+    fn test_dropped_probe_reopens_circuit() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+        let endpoint = "https://api.groq.com/openai/v1/chat/completions";
+        breaker.check(endpoint).unwrap().record(true);
+
+        std::thread::sleep(Duration::from_millis(25));
+        drop(breaker.check(endpoint).unwrap());
+        match breaker.check(endpoint) {
+            Err(GroqError::CircuitOpen { retry_in, .. }) => assert!(retry_in > Duration::ZERO),
+            _ => panic!("the circuit should be open again"),
+        }
+
+        std::thread::sleep(Duration::from_millis(25));
+        breaker.check(endpoint).unwrap().record(false);
+        drop(breaker.check(endpoint).unwrap());
+        assert!(breaker.check(endpoint).is_ok());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{messages, ChatApi, ChatCompletionRequest, GroqError};
+use std::str::FromStr;
+
+/// A type whose values are named by a fixed list of labels, such as a fieldless enum.
+///
+/// It has the same shape as the trait of the same name of the `strum` crate, so an enum deriving it can implement this
+/// one with `const VARIANTS: &'static [&'static str] = <Self as strum::VariantNames>::VARIANTS;`.
+pub trait VariantNames {
+    /// The names of the values, as parsed by `FromStr`.
+    const VARIANTS: &'static [&'static str];
+}
+
+/// Classifies a text into one of the labels of `L`.
+///
+/// The model is asked to answer with one of the names in `L::VARIANTS` only, and its answer is parsed back with
+/// `FromStr`. Surrounding whitespace, quotes and a final period are ignored, and labels are matched case-insensitively.
+///
+/// ```no_run
+/// use groq_api_rust::{classify, AsyncGroqClient, VariantNames};
+///
+/// #[derive(Debug)]
+/// enum Sentiment {
+///     Positive,
+///     Negative,
+/// }
+///
+/// impl VariantNames for Sentiment {
+///     const VARIANTS: &'static [&'static str] = &["positive", "negative"];
+/// }
+///
+/// impl std::str::FromStr for Sentiment {
+///     type Err = String;
+///
+///     fn from_str(label: &str) -> Result<Self, Self::Err> {
+///         match label {
+///             "positive" => Ok(Sentiment::Positive),
+///             "negative" => Ok(Sentiment::Negative),
+///             _ => Err(label.to_string()),
+///         }
+///     }
+/// }
+///
+/// # async fn example() -> Result<(), groq_api_rust::GroqError> {
+/// let client = AsyncGroqClient::new("my_api_key".to_string(), None).await;
+/// let sentiment: Sentiment = classify(&client, "llama-3.1-8b-instant", "I love this crate!").await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Arguments
+/// * `client` - The client sending the request, such as an `AsyncGroqClient`.
+/// * `model` - The model classifying the text.
+/// * `text` - The text to classify.
+///
+/// # Errors
+///
+/// Returns the `GroqError` of the request, or `GroqError::UnexpectedAnswer` if the model answers with no label of `L`.
+pub async fn classify<L: VariantNames + FromStr>(
+    client: &dyn ChatApi,
+    model: &str,
+    text: &str,
+) -> Result<L, GroqError> {
+    let labels = L::VARIANTS.join(", ");
+    let instructions = format!(
+        "Classify the text given by the user into exactly one of these labels: {}. Answer with the label only, \
+        without explanation.",
+        labels
+    );
+    let request = ChatCompletionRequest::new(model, messages![system: instructions, user: text])
+        .temperature(0.0);
+    let answer = client.chat_completion(request).await?.to_string();
+    parse_label(&answer).ok_or_else(|| GroqError::UnexpectedAnswer {
+        expected: format!("one of {}", labels),
+        answer,
+    })
+}
+
+/// Parses an answer into a label, ignoring case and surrounding punctuation.
+fn parse_label<L: VariantNames + FromStr>(answer: &str) -> Option<L> {
+    let answer = answer
+        .trim()
+        .trim_end_matches('.')
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`'))
+        .trim();
+    L::from_str(answer).ok().or_else(|| {
+        L::VARIANTS
+            .iter()
+            .find(|label| label.eq_ignore_ascii_case(answer))
+            .and_then(|label| L::from_str(label).ok())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{no_choices, reply, server_error, ChatFn};
+    use crate::ChatCompletionResponse;
+
+    #[derive(Debug, PartialEq)]
+    enum Priority {
+        Low,
+        High,
+    }
+
+    impl VariantNames for Priority {
+        const VARIANTS: &'static [&'static str] = &["Low", "High"];
+    }
+
+    impl FromStr for Priority {
+        type Err = ();
+
+        fn from_str(label: &str) -> Result<Self, Self::Err> {
+            match label {
+                "Low" => Ok(Priority::Low),
+                "High" => Ok(Priority::High),
+                _ => Err(()),
+            }
+        }
+    }
+
+    /// Answers with the user message, checking that the labels are in the prompt, failing on "fail" and without any
+    /// choice to "empty".
+    fn echo(request: ChatCompletionRequest) -> Result<ChatCompletionResponse, GroqError> {
+        assert!(request.messages[0].content.contains("labels: Low, High."));
+        match request.messages[1].content.as_str() {
+            "fail" => Err(server_error()),
+            "empty" => no_choices(&request.model),
+            content => reply(&request.model, content),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_classify_parses_labels() {
+        let client = ChatFn(echo);
+        let model = "llama-3.1-8b-instant";
+        assert_eq!(
+            classify::<Priority>(&client, model, "High").await.unwrap(),
+            Priority::High
+        );
+        assert_eq!(
+            classify::<Priority>(&client, model, " \"low\".\n")
+                .await
+                .unwrap(),
+            Priority::Low
+        );
+
+        let Err(GroqError::UnexpectedAnswer { expected, answer }) =
+            classify::<Priority>(&client, model, "Urgent").await
+        else {
+            panic!("an off-label answer should fail");
+        };
+        assert_eq!(expected, "one of Low, High");
+        assert_eq!(answer, "Urgent");
+    }
+
+    #[tokio::test]
+    async fn test_classify_handles_empty_and_failed_responses() {
+        let client = ChatFn(echo);
+        let model = "llama-3.1-8b-instant";
+        let Err(GroqError::UnexpectedAnswer { answer, .. }) =
+            classify::<Priority>(&client, model, "empty").await
+        else {
+            panic!("a response without choices should fail");
+        };
+        assert_eq!(answer, "");
+        assert!(matches!(
+            classify::<Priority>(&client, model, "fail").await,
+            Err(GroqError::ServerError { status: 500, .. })
+        ));
+    }
+}
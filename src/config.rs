@@ -0,0 +1,193 @@
+use crate::AsyncGroqClient;
+#[cfg(feature = "blocking")]
+use crate::GroqClient;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Represents a named set of settings of a `GroqConfig`.
+///
+/// - `api_key`: The API key of the profile.
+/// - `endpoint`: The URL of the API, `https://api.groq.com/openai/v1` by default.
+/// - `model`: The chat model used when none is given.
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl Profile {
+    #[cfg(feature = "blocking")]
+    /// Creates a `GroqClient` with the API key and endpoint of the profile, or `None` if it has no API key.
+    pub fn client(&self) -> Option<GroqClient> {
+        let api_key = self.api_key.clone()?;
+        Some(GroqClient::new(api_key, self.endpoint.clone()))
+    }
+
+    /// Creates an `AsyncGroqClient` with the API key and endpoint of the profile, or `None` if it has no API key.
+    pub async fn async_client(&self) -> Option<AsyncGroqClient> {
+        let api_key = self.api_key.clone()?;
+        Some(AsyncGroqClient::new(api_key, self.endpoint.clone()).await)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Represents the configuration file shared by the `groq` command line and applications, holding named profiles.
+///
+/// The file is JSON, stored in `groq/config.json` under the configuration directory of the platform:
+/// `$XDG_CONFIG_HOME` or `~/.config` on Linux, `~/Library/Application Support` on macOS and `%APPDATA%` on Windows.
+/// `GROQ_CONFIG` can point to another file.
+///
+/// ```json
+/// {
+///   "default_profile": "work",
+///   "profiles": {
+///     "work": { "api_key": "gsk_...", "model": "llama-3.3-70b-versatile" },
+///     "local": { "api_key": "test", "endpoint": "http://localhost:8080/openai/v1" }
+///   }
+/// }
+/// ```
+///
+/// - `default_profile`: The profile used when none is selected, `default` if unset.
+/// - `profiles`: The profiles, keyed by name.
+pub struct GroqConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+impl GroqConfig {
+    /// Returns the path of the configuration file, or `None` if the configuration directory is unknown.
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("GROQ_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let dir = if cfg!(windows) {
+            PathBuf::from(std::env::var_os("APPDATA")?)
+        } else if cfg!(target_os = "macos") {
+            PathBuf::from(std::env::var_os("HOME")?).join("Library/Application Support")
+        } else if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty())
+        {
+            PathBuf::from(dir)
+        } else {
+            PathBuf::from(std::env::var_os("HOME")?).join(".config")
+        };
+        Some(dir.join("groq").join("config.json"))
+    }
+
+    /// Loads the configuration file, or returns an empty configuration if there is none.
+    pub fn load() -> std::io::Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::from_file(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Loads a configuration file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the configuration file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file, such as `GroqConfig::path()`.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Returns the settings of a profile, completed by the environment.
+    ///
+    /// The profile is `name` if given, else the one named by `GROQ_PROFILE`, else `default_profile`, else `default`.
+    /// Settings missing from the profile are read from `GROQ_API_KEY` and `GROQ_ENDPOINT`.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the profile, such as the value of a `--profile` option.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `NotFound` if the selected profile or the `default_profile` does not exist.
+    pub fn profile(&self, name: Option<&str>) -> std::io::Result<Profile> {
+        self.resolve(name, |name| std::env::var(name).ok())
+    }
+
+    fn resolve(
+        &self,
+        name: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> std::io::Result<Profile> {
+        let selected = name.map(str::to_string).or_else(|| env("GROQ_PROFILE"));
+        let name = selected
+            .clone()
+            .or_else(|| self.default_profile.clone())
+            .unwrap_or_else(|| "default".to_string());
+        let mut profile = match self.profiles.get(&name) {
+            Some(profile) => profile.clone(),
+            None if selected.is_some() || self.default_profile.is_some() => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("unknown profile `{}`", name),
+                ));
+            }
+            None => Profile::default(),
+        };
+        profile.api_key = profile.api_key.or_else(|| env("GROQ_API_KEY"));
+        profile.endpoint = profile.endpoint.or_else(|| env("GROQ_ENDPOINT"));
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_config_resolves_profiles() {
+        let config: GroqConfig = serde_json::from_value(json!({
+            "default_profile": "work",
+            "profiles": {
+                "work": {"api_key": "gsk_work", "model": "llama-3.3-70b-versatile"},
+                "local": {"endpoint": "http://localhost:8080/openai/v1"}
+            }
+        }))
+        .unwrap();
+        let env = |name: &str| match name {
+            "GROQ_API_KEY" => Some("gsk_env".to_string()),
+            _ => None,
+        };
+
+        let work = config.resolve(None, env).unwrap();
+        assert_eq!(work.api_key.as_deref(), Some("gsk_work"));
+        assert_eq!(work.model.as_deref(), Some("llama-3.3-70b-versatile"));
+
+        let local = config.resolve(Some("local"), env).unwrap();
+        assert_eq!(local.api_key.as_deref(), Some("gsk_env"));
+        assert_eq!(
+            local.endpoint.as_deref(),
+            Some("http://localhost:8080/openai/v1")
+        );
+
+        let from_env = config.resolve(None, |name| {
+            (name == "GROQ_PROFILE").then(|| "local".to_string())
+        });
+        assert_eq!(from_env.unwrap().api_key, None);
+
+        let missing = config.resolve(Some("staging"), env).unwrap_err();
+        assert_eq!(missing.kind(), ErrorKind::NotFound);
+        let empty = GroqConfig::default().resolve(None, env).unwrap();
+        assert_eq!(empty.api_key.as_deref(), Some("gsk_env"));
+    }
+}
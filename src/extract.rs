@@ -0,0 +1,185 @@
+use crate::{messages, ChatApi, ChatCompletionRequest, GroqError, ResponseFormat, ToolParameter};
+use serde::de::DeserializeOwned;
+
+/// Extracts structured data from a text in one call.
+///
+/// The model is asked in JSON mode to extract the fields described by the JSON schema of `T` from the text, and its
+/// answer is deserialized into `T`. If the answer is not valid JSON of type `T`, the model is shown the error and
+/// asked once more.
+///
+/// ```no_run
+/// use groq_api_rust::{extract, AsyncGroqClient, ToolParameter};
+/// use serde::Deserialize;
+/// use serde_json::{json, Value};
+///
+/// #[derive(Deserialize)]
+/// struct Invoice {
+///     number: String,
+///     total: f64,
+/// }
+///
+/// impl ToolParameter for Invoice {
+///     fn schema() -> Value {
+///         json!({
+///             "type": "object",
+///             "properties": {"number": {"type": "string"}, "total": {"type": "number"}},
+///             "required": ["number", "total"]
+///         })
+///     }
+/// }
+///
+/// # async fn example() -> Result<(), groq_api_rust::GroqError> {
+/// let client = AsyncGroqClient::new("my_api_key".to_string(), None).await;
+/// let invoice: Invoice = extract(&client, "llama-3.3-70b-versatile", "Invoice #42, total due: $99.50").await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Arguments
+/// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+/// * `model` - The model extracting the data, which must support JSON mode.
+/// * `text` - The text to extract the data from.
+///
+/// # Errors
+///
+/// Returns the `GroqError` of the requests, or the `GroqError::DeserializationError` of the second answer if it is
+/// still not valid.
+pub async fn extract<T: ToolParameter + DeserializeOwned>(
+    client: &dyn ChatApi,
+    model: &str,
+    text: &str,
+) -> Result<T, GroqError> {
+    let instructions = format!(
+        "Extract the following fields from the text given by the user. Answer with a single JSON object matching \
+        this JSON schema, and nothing else. Use null for optional fields missing from the text.\n\n{}",
+        T::schema()
+    );
+    let mut request =
+        ChatCompletionRequest::new(model, messages![system: instructions, user: text])
+            .temperature(0.0)
+            .response_format(ResponseFormat::JsonObject);
+    let response = client.chat_completion(request.clone()).await?;
+    let error = match response.parse_json() {
+        Ok(value) => return Ok(value),
+        Err(error) => error,
+    };
+
+    let source = match &error {
+        GroqError::DeserializationError { source, .. } => source.to_string(),
+        _ => error.to_string(),
+    };
+    request.messages.extend(messages![
+        assistant: response,
+        user: format!(
+            "This answer is invalid: {}. Answer again with only the corrected JSON object.",
+            source
+        ),
+    ]);
+    client.chat_completion(request).await?.parse_json()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{reply, server_error};
+    use crate::ChatCompletionResponse;
+    use async_trait::async_trait;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Person {
+        name: String,
+        age: u32,
+    }
+
+    impl ToolParameter for Person {
+        fn schema() -> Value {
+            json!({
+                "type": "object",
+                "properties": {"name": {"type": "string"}, "age": {"type": "integer"}},
+                "required": ["name", "age"]
+            })
+        }
+    }
+
+    /// Answers with the queued contents, failing on "fail", and records the requests.
+    struct Answers {
+        contents: Mutex<Vec<&'static str>>,
+        requests: Mutex<Vec<ChatCompletionRequest>>,
+    }
+
+    #[async_trait]
+    impl ChatApi for Answers {
+        async fn chat_completion(
+            &self,
+            request: ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, GroqError> {
+            let content = self.contents.lock().unwrap().remove(0);
+            self.requests.lock().unwrap().push(request.clone());
+            match content {
+                "fail" => Err(server_error()),
+                content => reply(&request.model, content),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_retries_invalid_json_once() {
+        let client = Answers {
+            contents: Mutex::new(vec![r#"{"name": "Ada"}"#, r#"{"name": "Ada", "age": 36}"#]),
+            requests: Mutex::new(Vec::new()),
+        };
+        let text = "Ada Lovelace was 36.";
+
+        let person: Person = extract(&client, "llama-3.3-70b-versatile", text)
+            .await
+            .unwrap();
+        assert_eq!(
+            person,
+            Person {
+                name: "Ada".to_string(),
+                age: 36
+            }
+        );
+        let requests = client.requests.into_inner().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(matches!(
+            requests[0].response_format,
+            Some(ResponseFormat::JsonObject)
+        ));
+        assert!(requests[0].messages[0]
+            .content
+            .contains(r#""required":["name","age"]"#));
+        assert_eq!(requests[0].messages[1].content, text);
+        assert_eq!(requests[1].messages[2].content, r#"{"name": "Ada"}"#);
+        assert!(requests[1].messages[3]
+            .content
+            .contains("missing field `age`"));
+
+        let client = Answers {
+            contents: Mutex::new(vec!["not json", "still not json"]),
+            requests: Mutex::new(Vec::new()),
+        };
+        let result = extract::<Person>(&client, "llama-3.3-70b-versatile", text).await;
+        assert!(matches!(
+            result,
+            Err(GroqError::DeserializationError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_extract_returns_api_errors_without_retrying() {
+        let client = Answers {
+            contents: Mutex::new(vec!["fail", r#"{"name": "Ada", "age": 36}"#]),
+            requests: Mutex::new(Vec::new()),
+        };
+        let result = extract::<Person>(&client, "llama-3.3-70b-versatile", "Ada was 36.").await;
+        assert!(matches!(
+            result,
+            Err(GroqError::ServerError { status: 500, .. })
+        ));
+        assert_eq!(client.requests.into_inner().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::models::LLAMA_GUARD_3_8B;
+use crate::{
+    ChatApi, ChatCompletionMessage, ChatCompletionRequest, ChatCompletionResponse,
+    ChatCompletionRoles, GroqError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Represents the step of a guarded chat completion at which a policy was violated.
+///
+/// - `Input`: The conversation sent by the user was flagged, and the model was not called.
+/// - `Output`: The answer of the model was flagged.
+pub enum GuardStage {
+    Input,
+    Output,
+}
+
+#[derive(Debug, Clone)]
+/// Represents a chat completion blocked by the guard model.
+///
+/// - `stage`: Whether the input or the output was flagged.
+/// - `categories`: The violated hazard categories reported by the guard model, such as `S1`.
+/// - `response`: The flagged answer of the model, for `GuardStage::Output`.
+pub struct Refusal {
+    pub stage: GuardStage,
+    pub categories: Vec<String>,
+    pub response: Option<ChatCompletionResponse>,
+}
+
+impl Refusal {
+    /// Returns the names of the violated categories of the Llama Guard 3 taxonomy, keeping unknown codes as they are.
+    pub fn category_names(&self) -> Vec<&str> {
+        self.categories
+            .iter()
+            .map(|code| category_name(code).unwrap_or(code))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Represents the result of a guarded chat completion.
+///
+/// - `Allowed`: Neither the input nor the output was flagged.
+/// - `Blocked`: The guard model flagged the input or the output.
+pub enum GuardedResponse {
+    Allowed(ChatCompletionResponse),
+    Blocked(Refusal),
+}
+
+impl GuardedResponse {
+    /// Returns the response if it was allowed.
+    pub fn allowed(self) -> Option<ChatCompletionResponse> {
+        match self {
+            GuardedResponse::Allowed(response) => Some(response),
+            GuardedResponse::Blocked(_) => None,
+        }
+    }
+
+    /// Returns `true` if the guard model flagged the input or the output.
+    pub fn is_blocked(&self) -> bool {
+        matches!(self, GuardedResponse::Blocked(_))
+    }
+}
+
+/// A client running a Llama Guard model on the user input and the model output around every chat completion.
+///
+/// The user and assistant messages of the request are classified first; if they are flagged, the model is not called.
+/// The answer of the model is then classified with the conversation, unless `check_output(false)` is set. Blocked
+/// completions are returned as a `Refusal` listing the violated categories.
+///
+/// ```no_run
+/// use groq_api_rust::{messages, AsyncGroqClient, ChatCompletionRequest, GuardedClient, GuardedResponse};
+///
+/// # async fn example() -> Result<(), groq_api_rust::GroqError> {
+/// let client = AsyncGroqClient::new("my_api_key".to_string(), None).await;
+/// let guarded = GuardedClient::new(&client);
+/// let request = ChatCompletionRequest::new("llama-3.1-8b-instant", messages![user: "Hello"]);
+/// match guarded.chat_completion(request).await? {
+///     GuardedResponse::Allowed(response) => println!("{}", response),
+///     GuardedResponse::Blocked(refusal) => println!("Blocked: {:?}", refusal.category_names()),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct GuardedClient<'a> {
+    client: &'a dyn ChatApi,
+    guard_model: String,
+    check_input: bool,
+    check_output: bool,
+}
+
+impl<'a> GuardedClient<'a> {
+    /// Creates a new `GuardedClient` checking the input and the output with `llama-guard-3-8b`.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+    pub fn new(client: &'a dyn ChatApi) -> Self {
+        Self {
+            client,
+            guard_model: LLAMA_GUARD_3_8B.to_string(),
+            check_input: true,
+            check_output: true,
+        }
+    }
+
+    /// Sets the model classifying the conversations.
+    ///
+    /// # Arguments
+    /// * `model` - A Llama Guard model answering `safe`, or `unsafe` followed by the violated categories.
+    pub fn guard_model(mut self, model: impl Into<String>) -> Self {
+        self.guard_model = model.into();
+        self
+    }
+
+    /// Sets whether the user input is classified before calling the model.
+    ///
+    /// # Arguments
+    /// * `check` - Whether to classify the input.
+    pub fn check_input(mut self, check: bool) -> Self {
+        self.check_input = check;
+        self
+    }
+
+    /// Sets whether the answer of the model is classified.
+    ///
+    /// # Arguments
+    /// * `check` - Whether to classify the output.
+    pub fn check_output(mut self, check: bool) -> Self {
+        self.check_output = check;
+        self
+    }
+
+    /// Sends a chat completion request, guarded by the guard model.
+    ///
+    /// # Arguments
+    /// * `request` - The request to send.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `GroqError` of the guard or chat requests, or `GroqError::UnexpectedAnswer` if the guard model
+    /// answers neither `safe` nor `unsafe`.
+    pub async fn chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<GuardedResponse, GroqError> {
+        let mut conversation: Vec<ChatCompletionMessage> = request
+            .messages
+            .iter()
+            .filter(|message| {
+                matches!(
+                    message.role,
+                    ChatCompletionRoles::User | ChatCompletionRoles::Assistant
+                )
+            })
+            .cloned()
+            .collect();
+        if self.check_input {
+            if let Some(categories) = self.classify(&conversation).await? {
+                return Ok(GuardedResponse::Blocked(Refusal {
+                    stage: GuardStage::Input,
+                    categories,
+                    response: None,
+                }));
+            }
+        }
+
+        let response = self.client.chat_completion(request).await?;
+        if self.check_output {
+            if let Some(choice) = response.choices.first() {
+                conversation.push(ChatCompletionMessage {
+                    role: ChatCompletionRoles::Assistant,
+                    content: choice.message.content.clone().into(),
+                    name: None,
+                    tool_calls: None,
+                    tool_call_id: None,
+                });
+                if let Some(categories) = self.classify(&conversation).await? {
+                    return Ok(GuardedResponse::Blocked(Refusal {
+                        stage: GuardStage::Output,
+                        categories,
+                        response: Some(response),
+                    }));
+                }
+            }
+        }
+        Ok(GuardedResponse::Allowed(response))
+    }
+
+    /// Classifies a conversation, returning the violated categories if it is unsafe.
+    async fn classify(
+        &self,
+        conversation: &[ChatCompletionMessage],
+    ) -> Result<Option<Vec<String>>, GroqError> {
+        if conversation.is_empty() {
+            return Ok(None);
+        }
+        let request = ChatCompletionRequest::new(self.guard_model.as_str(), conversation.to_vec())
+            .temperature(0.0);
+        let verdict = self.client.chat_completion(request).await?.to_string();
+        parse_verdict(&verdict).ok_or_else(|| GroqError::UnexpectedAnswer {
+            expected: "a `safe` or `unsafe` verdict".to_string(),
+            answer: verdict.clone(),
+        })
+    }
+}
+
+/// Parses the answer of a Llama Guard model: `safe`, or `unsafe` followed by comma-separated categories.
+fn parse_verdict(verdict: &str) -> Option<Option<Vec<String>>> {
+    let mut lines = verdict.trim().lines();
+    match lines.next()?.trim() {
+        "safe" => Some(None),
+        "unsafe" => Some(Some(
+            lines
+                .flat_map(|line| line.split(','))
+                .map(str::trim)
+                .filter(|category| !category.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+/// Returns the name of a category of the Llama Guard 3 taxonomy.
+fn category_name(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "S1" => "Violent Crimes",
+        "S2" => "Non-Violent Crimes",
+        "S3" => "Sex-Related Crimes",
+        "S4" => "Child Sexual Exploitation",
+        "S5" => "Defamation",
+        "S6" => "Specialized Advice",
+        "S7" => "Privacy",
+        "S8" => "Intellectual Property",
+        "S9" => "Indiscriminate Weapons",
+        "S10" => "Hate",
+        "S11" => "Suicide & Self-Harm",
+        "S12" => "Sexual Content",
+        "S13" => "Elections",
+        "S14" => "Code Interpreter Abuse",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages;
+    use crate::test_support::{no_choices, reply, ChatFn};
+
+    /// Flags conversations mentioning weapons and doubts those mentioning riddles; answers with the last user
+    /// message otherwise, or without any choice to "empty".
+    fn guarded(request: ChatCompletionRequest) -> Result<ChatCompletionResponse, GroqError> {
+        let last = request.messages.last().unwrap().content.clone();
+        if request.model != LLAMA_GUARD_3_8B {
+            return match last.as_str() {
+                "empty" => no_choices(&request.model),
+                _ => reply(&request.model, &last.replace("echo ", "")),
+            };
+        }
+        let verdict = if last.contains("weapon") {
+            "unsafe\nS1,S9"
+        } else if last.contains("riddle") {
+            "maybe"
+        } else {
+            "safe"
+        };
+        reply(&request.model, verdict)
+    }
+
+    fn request(prompt: &str) -> ChatCompletionRequest {
+        ChatCompletionRequest::new(
+            "llama-3.1-8b-instant",
+            messages![system: "Be nice", user: prompt],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_guarded_client_blocks_input_and_output() {
+        let client = ChatFn(guarded);
+        let guarded = GuardedClient::new(&client);
+
+        let allowed = guarded.chat_completion(request("Hello")).await.unwrap();
+        assert_eq!(allowed.allowed().unwrap().to_string(), "Hello");
+
+        let GuardedResponse::Blocked(refusal) =
+            guarded.chat_completion(request("weapon")).await.unwrap()
+        else {
+            panic!("the input should be blocked");
+        };
+        assert_eq!(refusal.stage, GuardStage::Input);
+        assert_eq!(refusal.categories, vec!["S1", "S9"]);
+        assert_eq!(
+            refusal.category_names(),
+            vec!["Violent Crimes", "Indiscriminate Weapons"]
+        );
+
+        let output = guarded.check_input(false);
+        let GuardedResponse::Blocked(refusal) = output
+            .chat_completion(request("echo weapon"))
+            .await
+            .unwrap()
+        else {
+            panic!("the output should be blocked");
+        };
+        assert_eq!(refusal.stage, GuardStage::Output);
+        assert_eq!(refusal.response.unwrap().to_string(), "weapon");
+    }
+
+    #[tokio::test]
+    async fn test_guarded_client_rejects_off_label_verdicts() {
+        let client = ChatFn(guarded);
+        let guarded = GuardedClient::new(&client);
+        let Err(GroqError::UnexpectedAnswer { expected, answer }) =
+            guarded.chat_completion(request("Tell me a riddle")).await
+        else {
+            panic!("an off-label verdict should fail");
+        };
+        assert_eq!(expected, "a `safe` or `unsafe` verdict");
+        assert_eq!(answer, "maybe");
+
+        let allowed = guarded.chat_completion(request("empty")).await.unwrap();
+        assert!(allowed.allowed().unwrap().choices.is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+#[cfg(feature = "audio")]
+use crate::SpeechToTextRequest;
+use crate::{ChatCompletionRequest, GroqError, ResponseMeta};
+use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+/// Represents an attempt about to be sent, as seen by the `on_request` hook.
+///
+/// The API key is redacted from the headers, so the view can be logged as-is.
+///
+/// - `method`: The HTTP method of the request.
+/// - `url`: The URL of the request.
+/// - `attempt`: The number of the attempt, starting at 1.
+/// - `headers`: The headers of the request, with secrets replaced by `[REDACTED]` after their scheme, such as
+///   `Bearer [REDACTED]`.
+/// - `body`: The body of the request, unless it is streamed (such as multipart audio uploads).
+/// - `correlation_id`: The correlation ID of the request, if one was set.
+pub struct RequestInfo {
+    pub method: String,
+    pub url: String,
+    pub attempt: u32,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub correlation_id: Option<String>,
+}
+
+impl RequestInfo {
+    fn new(
+        method: &Method,
+        url: &Url,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+        attempt: u32,
+        correlation_id: Option<&str>,
+    ) -> Self {
+        Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            attempt,
+            headers: redacted_headers(headers),
+            body: body.map(|body| String::from_utf8_lossy(body).into_owned()),
+            correlation_id: correlation_id.map(|id| id.to_string()),
+        }
+    }
+
+    /// Returns an equivalent `curl` command, reading the API key from the `GROQ_API_KEY` environment variable.
+    ///
+    /// Redacted headers keep the scheme they were sent with, such as `Bearer` or a custom prefix, before the key.
+    /// JSON bodies are pretty-printed. Streamed bodies, such as multipart audio uploads, cannot be reproduced and are
+    /// replaced by a comment.
+    pub fn to_curl(&self) -> String {
+        let quote = |text: &str| format!("'{}'", text.replace('\'', "'\\''"));
+        // A redacted API key in the URL is read from the environment too, outside the quotes.
+        let url = quote(&self.url).replace("%5BREDACTED%5D", "'\"$GROQ_API_KEY\"'");
+        let mut command = format!("curl -X {} {}", self.method, url);
+        for (name, value) in &self.headers {
+            let scheme = value.strip_suffix("[REDACTED]");
+            let header = format!("{name}: {}", scheme.unwrap_or(value));
+            command.push_str(" \\\n  -H ");
+            command.push_str(&quote(&header));
+            if scheme.is_some() {
+                // Left outside the quotes so that the shell expands it.
+                command.push_str("\"$GROQ_API_KEY\"");
+            }
+        }
+        match &self.body {
+            Some(body) => {
+                let body = serde_json::from_str::<serde_json::Value>(body)
+                    .and_then(|json| serde_json::to_string_pretty(&json))
+                    .unwrap_or_else(|_| body.clone());
+                command.push_str(" \\\n  -d ");
+                command.push_str(&quote(&body));
+            }
+            None if self.headers.iter().any(|(name, value)| {
+                name.eq_ignore_ascii_case("content-type") && value.starts_with("multipart/")
+            }) =>
+            {
+                command.push_str("\n# The multipart body is streamed and cannot be shown.");
+            }
+            None => {}
+        }
+        command
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Represents a request built by `dry_run` without being sent.
+///
+/// - `method`: The HTTP method of the request.
+/// - `url`: The URL of the request.
+/// - `headers`: The headers of the request, with secrets replaced by `[REDACTED]` after their scheme, such as
+///   `Bearer [REDACTED]`.
+/// - `body`: The JSON body of the request.
+pub struct DryRun {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl DryRun {
+    /// Returns the value of the given header, if it is set.
+    ///
+    /// # Arguments
+    /// * `name` - The case-insensitive name of the header.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Returns the headers as name and value pairs, with the `Authorization` header and other sensitive headers, such as
+/// the one carrying the API key, replaced by `[REDACTED]`. A scheme before the key, such as `Bearer`, is kept.
+fn redacted_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == reqwest::header::AUTHORIZATION || value.is_sensitive() {
+                let value = String::from_utf8_lossy(value.as_bytes());
+                match value.rsplit_once(' ') {
+                    Some((scheme, _)) => format!("{} [REDACTED]", scheme),
+                    None => "[REDACTED]".to_string(),
+                }
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+/// Represents a response received for an attempt, as seen by the `on_response` hook.
+///
+/// - `url`: The URL of the request.
+/// - `attempt`: The number of the attempt, starting at 1.
+/// - `latency`: The time until the response headers were received.
+/// - `meta`: The status, request ID and rate limit state of the response.
+/// - `correlation_id`: The correlation ID of the request, if one was set.
+pub struct ResponseInfo {
+    pub url: String,
+    pub attempt: u32,
+    pub latency: Duration,
+    pub meta: ResponseMeta,
+    pub correlation_id: Option<String>,
+}
+
+#[derive(Debug)]
+/// Represents a failed attempt about to be retried, as seen by the `on_retry` hook.
+///
+/// - `url`: The URL of the request.
+/// - `attempt`: The number of the failed attempt, starting at 1.
+/// - `delay`: How long the client waits before the next attempt.
+/// - `error`: The error of the failed attempt.
+/// - `correlation_id`: The correlation ID of the request, if one was set.
+pub struct RetryInfo<'a> {
+    pub url: &'a str,
+    pub attempt: u32,
+    pub delay: Duration,
+    pub error: &'a GroqError,
+    pub correlation_id: Option<&'a str>,
+}
+
+type Hook<T> = Arc<dyn Fn(&T) + Send + Sync>;
+type RetryHook = Arc<dyn for<'a> Fn(&RetryInfo<'a>) + Send + Sync>;
+type Scrubber = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// The observer hooks registered on a client.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_request: Option<Hook<RequestInfo>>,
+    pub(crate) on_response: Option<Hook<ResponseInfo>>,
+    pub(crate) on_retry: Option<RetryHook>,
+    pub(crate) debug_curl: bool,
+    pub(crate) redact_content: bool,
+    pub(crate) scrubber: Option<Scrubber>,
+}
+
+impl Hooks {
+    /// Calls the `on_request` hook, if any.
+    pub(crate) fn request(
+        &self,
+        method: &Method,
+        url: &Url,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+        attempt: u32,
+        correlation_id: Option<&str>,
+    ) {
+        if self.on_request.is_none() && !self.debug_curl {
+            return;
+        }
+        let mut info = RequestInfo::new(method, url, headers, body, attempt, correlation_id);
+        if self.redact_content {
+            info.body = info.body.map(|body| crate::redact::redact_text(&body));
+        }
+        if self.debug_curl {
+            log::info!(target: "groq_api_rust::curl", "{}", info.to_curl());
+        }
+        if let Some(hook) = &self.on_request {
+            hook(&info);
+        }
+    }
+
+    /// Rewrites the content of the chat messages with the scrubber, if any.
+    pub(crate) fn scrub_chat(&self, mut request: ChatCompletionRequest) -> ChatCompletionRequest {
+        if let Some(scrub) = &self.scrubber {
+            for message in &mut request.messages {
+                message.content = scrub(&message.content).into();
+            }
+        }
+        request
+    }
+
+    #[cfg(feature = "audio")]
+    /// Rewrites the prompt of the transcription with the scrubber, if any.
+    pub(crate) fn scrub_speech(&self, mut request: SpeechToTextRequest) -> SpeechToTextRequest {
+        if let Some(scrub) = &self.scrubber {
+            request.prompt = request.prompt.map(|prompt| scrub(&prompt));
+        }
+        request
+    }
+
+    /// Describes a request built by `dry_run`, redacting its content when content redaction is enabled.
+    pub(crate) fn dry_run(
+        &self,
+        method: &Method,
+        url: &Url,
+        headers: &HeaderMap,
+        mut body: Value,
+    ) -> DryRun {
+        if self.redact_content {
+            crate::redact::redact_json(&mut body);
+        }
+        DryRun {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: redacted_headers(headers),
+            body,
+        }
+    }
+
+    /// Replaces the message content held by the error with placeholders when content redaction is enabled.
+    pub(crate) fn redact_error(&self, error: GroqError) -> GroqError {
+        if self.redact_content {
+            error.redact_content()
+        } else {
+            error
+        }
+    }
+
+    /// Calls the `on_response` hook, if any.
+    pub(crate) fn response(
+        &self,
+        url: &str,
+        attempt: u32,
+        latency: Duration,
+        status: StatusCode,
+        headers: &HeaderMap,
+        correlation_id: Option<&str>,
+    ) {
+        if let Some(hook) = &self.on_response {
+            hook(&ResponseInfo {
+                url: url.to_string(),
+                attempt,
+                latency,
+                meta: ResponseMeta::from_response(status, headers),
+                correlation_id: correlation_id.map(|id| id.to_string()),
+            });
+        }
+    }
+
+    /// Calls the `on_retry` hook, if any.
+    pub(crate) fn retry(
+        &self,
+        url: &str,
+        attempt: u32,
+        delay: Duration,
+        error: &GroqError,
+        correlation_id: Option<&str>,
+    ) {
+        if let Some(hook) = &self.on_retry {
+            hook(&RetryInfo {
+                url,
+                attempt,
+                delay,
+                error,
+                correlation_id,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curl_command() {
+        let info = RequestInfo {
+            method: "POST".to_string(),
+            url: "https://api.groq.com/openai/v1/chat/completions".to_string(),
+            attempt: 1,
+            headers: vec![
+                ("authorization".to_string(), "Bearer [REDACTED]".to_string()),
+                ("content-type".to_string(), "application/json".to_string()),
+            ],
+            correlation_id: None,
+            body: Some(
+                r#"{"model":"llama3-8b-8192","messages":[{"role":"user","content":"It's me"}]}"#
+                    .to_string(),
+            ),
+        };
+        let curl = info.to_curl();
+        assert!(curl.starts_with("curl -X POST 'https://api.groq.com/openai/v1/chat/completions'"));
+        assert!(curl.contains("-H 'authorization: Bearer '\"$GROQ_API_KEY\""));
+        assert!(curl.contains("\"model\": \"llama3-8b-8192\""));
+        assert!(curl.contains("It'\\''s me"));
+
+        let sensitive = |value: &'static str| {
+            let mut value = reqwest::header::HeaderValue::from_static(value);
+            value.set_sensitive(true);
+            value
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", sensitive("Token gsk_secret"));
+        headers.insert("api-key", sensitive("gsk_secret"));
+        let url = Url::parse("https://gateway.example.com/v1/chat/completions").unwrap();
+        let info = RequestInfo::new(&Method::POST, &url, &headers, None, 1, None);
+        assert_eq!(info.headers[0].1, "Token [REDACTED]");
+        assert_eq!(info.headers[1].1, "[REDACTED]");
+        let curl = info.to_curl();
+        assert!(curl.contains("-H 'x-api-key: Token '\"$GROQ_API_KEY\""));
+        assert!(curl.contains("-H 'api-key: '\"$GROQ_API_KEY\""));
+        assert!(!curl.contains("Bearer"));
+    }
+}
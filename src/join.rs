@@ -0,0 +1,74 @@
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::Poll;
+
+/// Polls the futures concurrently and returns the first successful output, dropping and so cancelling the others.
+///
+/// If every future fails, the error of the last one to fail is returned.
+///
+/// # Panics
+///
+/// Panics if there is no future.
+pub(crate) async fn first_ok<F, T, E>(futures: impl IntoIterator<Item = F>) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    let mut pending: Vec<Pin<Box<F>>> = futures.into_iter().map(Box::pin).collect();
+    assert!(!pending.is_empty(), "`first_ok` needs at least one future");
+    let mut last_error = None;
+    poll_fn(|cx| {
+        let mut i = 0;
+        while i < pending.len() {
+            match pending[i].as_mut().poll(cx) {
+                Poll::Ready(Ok(output)) => return Poll::Ready(Ok(output)),
+                Poll::Ready(Err(err)) => {
+                    pending.swap_remove(i);
+                    last_error = Some(err);
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+        match last_error.take() {
+            Some(err) if pending.is_empty() => Poll::Ready(Err(err)),
+            err => {
+                last_error = err;
+                Poll::Pending
+            }
+        }
+    })
+    .await
+}
+
+/// Polls the futures concurrently and returns all their outputs, in the order of the futures.
+pub(crate) async fn join_all<F: Future>(futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+    let mut pending: Vec<Option<Pin<Box<F>>>> = futures
+        .into_iter()
+        .map(|future| Some(Box::pin(future)))
+        .collect();
+    let mut outputs: Vec<Option<F::Output>> = pending.iter().map(|_| None).collect();
+    poll_fn(|cx| {
+        let mut done = true;
+        for (slot, output) in pending.iter_mut().zip(outputs.iter_mut()) {
+            if let Some(future) = slot {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(value) => {
+                        *output = Some(value);
+                        *slot = None;
+                    }
+                    Poll::Pending => done = false,
+                }
+            }
+        }
+        if done {
+            Poll::Ready(
+                outputs
+                    .iter_mut()
+                    .map(|output| output.take().unwrap())
+                    .collect(),
+            )
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
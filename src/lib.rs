@@ -1,14 +1,158 @@
+// `GroqError` keeps the details of API errors inline so that they can be matched on directly.
+#![allow(clippy::result_large_err)]
+
+mod agent;
+mod api;
+mod audit;
+mod auth;
+#[cfg(feature = "bench")]
+mod bench;
+mod budget;
+pub mod builder;
+mod cache;
+mod chat;
+pub mod chunk;
+mod circuit_breaker;
+mod classify;
+mod config;
+mod extract;
+mod guard;
+mod hooks;
+mod join;
 mod message;
+mod meta;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "mock")]
+mod mock;
+pub mod models;
+mod pricing;
+#[cfg(feature = "stream")]
+mod progress;
+mod race;
+mod random;
+mod rate_limit;
+mod redact;
+mod retry;
+#[cfg(feature = "stream")]
+mod stream;
+mod summarize;
+mod telemetry;
+mod template;
+#[cfg(test)]
+mod test_support;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tokenizer")]
+mod tokenizer;
+mod tokens;
+mod tool;
+mod translate;
+mod usage;
+mod usage_log;
+#[cfg(feature = "vcr")]
+mod vcr;
+mod voice;
+pub use agent::*;
+pub use api::*;
+pub use audit::*;
+pub use auth::*;
+#[cfg(feature = "bench")]
+pub use bench::*;
+pub use budget::*;
+pub use builder::ChatCompletionRequestBuilder;
+use cache::CacheLookup;
+pub use cache::*;
+pub use chat::*;
+pub use circuit_breaker::*;
+pub use classify::*;
+pub use config::*;
+pub use extract::*;
+pub use guard::*;
+pub use hooks::*;
 pub use message::*;
+pub use meta::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+#[cfg(feature = "mock")]
+pub use mock::*;
+pub use models::{Modality, Model, ModelInfo, ModelRegistry};
+pub use pricing::*;
+#[cfg(feature = "stream")]
+pub use progress::*;
+pub use race::*;
+pub use rate_limit::*;
+use rate_limit::{estimate_request_tokens, Reservation};
+pub use redact::mask_pii;
+#[cfg(all(feature = "blocking", feature = "audio"))]
+use reqwest::blocking::multipart::{Form, Part};
+#[cfg(feature = "blocking")]
+use reqwest::blocking::{Client, RequestBuilder, Response};
+#[cfg(feature = "audio")]
+use reqwest::multipart::{Form as AForm, Part as APart};
 use reqwest::{
-    blocking::multipart::{Form, Part},
-    blocking::{Client, Response},
-    multipart::{Form as AForm, Part as APart},
-    Client as AClient, Response as AResponse,
+    Client as AClient, Method, RequestBuilder as ARequestBuilder, Response as AResponse,
 };
-use serde_json::{json, Value};
+pub use retry::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+#[cfg(feature = "stream")]
+pub use stream::*;
+pub use summarize::*;
+pub use template::*;
+#[cfg(feature = "tokenizer")]
+pub use tokenizer::*;
+pub use tokens::count_tokens;
+use tokio::sync::{Semaphore, SemaphorePermit};
+pub use tool::*;
+pub use translate::*;
+pub use usage::*;
+pub use usage_log::*;
+#[cfg(feature = "vcr")]
+pub use vcr::*;
+pub use voice::*;
+
+#[cfg(feature = "macros")]
+pub use groq_api_rust_macros::groq_tool;
+
+// Lets the code generated by `#[groq_tool]` be used within this crate's tests.
+#[cfg(all(test, feature = "macros"))]
+extern crate self as groq_api_rust;
+
+/// Dependencies used by the code generated by `#[groq_tool]`. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+    pub use serde_json;
+}
+
+/// The hot paths timed by the `hot_paths` benchmark. Not part of the public API.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod __bench {
+    use crate::{ChatCompletionRequest, GroqError};
+
+    /// Serializes the body a chat completion request is sent with.
+    pub fn chat_completion_body(request: &ChatCompletionRequest) -> Result<Vec<u8>, GroqError> {
+        Ok(serde_json::to_vec(&crate::chat_completion_body(
+            request,
+            &request.model,
+        ))?)
+    }
+
+    /// Decodes the body of a streamed response read `read_size` bytes at a time into its chunks.
+    #[cfg(feature = "stream")]
+    pub fn decode_transcript(
+        transcript: &[u8],
+        read_size: usize,
+    ) -> Result<Vec<crate::ChatCompletionChunk>, GroqError> {
+        crate::stream::decode_transcript(transcript, read_size)
+    }
+}
 
+#[derive(Clone)]
 /// An asynchronous client for interacting with the Groq API.
 ///
 /// # Parameters
@@ -23,402 +167,3884 @@ use std::sync::Arc;
 /// # Example
 ///
 ///```
-/// use groq_client::AsyncGroqClient;
+/// # async fn run() {
+/// use groq_api_rust::AsyncGroqClient;
 ///
 /// let client = AsyncGroqClient::new("my_api_key".to_string(), None).await;
+/// # }
 ///```
+///
+/// # Sharing
+///
+/// `AsyncGroqClient` is `Send` and `Sync`, and cloning it does not allocate: the clones share the connection pool,
+/// the API key and the state of the rate limiter, circuit breaker, budget, cache, usage tracking and concurrency
+/// limit. A server can hand a clone to every connection or task instead of wrapping the client in an `Arc`.
 pub struct AsyncGroqClient {
-    api_key: String,
+    api_key: Arc<str>,
+    auth_scheme: Arc<AuthScheme>,
     client: Arc<AClient>,
-    endpoint: String,
+    endpoint: Arc<str>,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
+    rate_limiter: Option<RateLimiter>,
+    circuit_breaker: Option<CircuitBreaker>,
+    budget: Option<Budget>,
+    response_cache: Option<ResponseCache>,
+    auto_idempotency_keys: bool,
+    timeout: Option<Duration>,
+    deadline: Option<Duration>,
+    hooks: Hooks,
+    telemetry: telemetry::Recorder,
+    usage: UsageTracker,
+    usage_log: Option<UsageLog>,
+    audit: Option<Arc<dyn AuditSink>>,
+    #[cfg(feature = "vcr")]
+    cassette: Option<Arc<Cassette>>,
+    concurrency_limit: Option<Arc<Semaphore>>,
 }
 
-impl AsyncGroqClient {
-    /// Creates a new `AsyncGroqClient`
-    pub async fn new(api_key: String, endpoint: Option<String>) -> Self {
-        let ep = endpoint.unwrap_or_else(|| String::from("https://api.groq.com/openai/v1"));
-        Self {
-            api_key,
-            client: Arc::new(AClient::new()),
-            endpoint: ep,
-        }
+impl AsyncGroqClient {
+    /// Creates a new `AsyncGroqClient`
+    pub async fn new(api_key: impl Into<String>, endpoint: Option<String>) -> Self {
+        let ep = endpoint.unwrap_or_else(|| String::from("https://api.groq.com/openai/v1"));
+        Self {
+            api_key: Arc::from(api_key.into()),
+            auth_scheme: Arc::new(AuthScheme::Bearer),
+            client: Arc::new(AClient::new()),
+            endpoint: ep.into(),
+            retry_policy: None,
+            rate_limiter: None,
+            circuit_breaker: None,
+            budget: None,
+            response_cache: None,
+            auto_idempotency_keys: false,
+            timeout: None,
+            deadline: None,
+            hooks: Hooks::default(),
+            telemetry: telemetry::Recorder::default(),
+            usage: UsageTracker::default(),
+            usage_log: None,
+            audit: None,
+            #[cfg(feature = "vcr")]
+            cassette: None,
+            concurrency_limit: None,
+        }
+    }
+
+    /// Creates a client for an OpenAI-compatible server other than Groq, such as vLLM, Ollama or LiteLLM.
+    ///
+    /// The API key is sent as a bearer token when one is given, and not at all otherwise; `auth_scheme` can change
+    /// how it is sent. Responses missing fields that Groq always sends, such as the timings of `Usage`, are accepted.
+    ///
+    /// # Parameters
+    ///
+    /// - `endpoint`: The base URL of the API, such as <http://localhost:11434/v1> for Ollama.
+    /// - `api_key`: The API key of the server, if it requires one.
+    ///
+    /// # Returns
+    ///
+    /// A new `AsyncGroqClient` instance sending every request to `endpoint`.
+    pub async fn openai_compatible(endpoint: impl Into<String>, api_key: Option<String>) -> Self {
+        let auth_scheme = match api_key {
+            Some(_) => AuthScheme::Bearer,
+            None => AuthScheme::None,
+        };
+        Self::new(api_key.unwrap_or_default(), Some(endpoint.into()))
+            .await
+            .auth_scheme(auth_scheme)
+    }
+
+    /// Sets how the API key is sent, for gateways and servers that do not expect a bearer token.
+    ///
+    /// The header carrying the key is redacted from hooks, dry runs and the `curl` commands built from them.
+    ///
+    /// # Parameters
+    ///
+    /// - `scheme`: The `AuthScheme` applied to every request, `AuthScheme::Bearer` by default.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn auth_scheme(mut self, scheme: AuthScheme) -> Self {
+        self.auth_scheme = Arc::new(scheme);
+        self
+    }
+
+    /// Enables automatic retries of failed requests according to the given policy.
+    ///
+    /// # Parameters
+    ///
+    /// - `policy`: The `RetryPolicy` deciding which failures are retried and how long to wait between attempts,
+    ///   such as `ExponentialBackoff`.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn retry_policy(mut self, policy: impl RetryPolicy + 'static) -> Self {
+        self.retry_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Enables client-side rate limiting, waiting for capacity before each request is sent.
+    ///
+    /// # Parameters
+    ///
+    /// - `limiter`: The `RateLimiter` holding the request and token budgets per model.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Enables a circuit breaker failing requests fast while an endpoint keeps failing.
+    ///
+    /// # Parameters
+    ///
+    /// - `breaker`: The `CircuitBreaker` tracking failures per endpoint.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Enables a budget capping the tokens and estimated cost spent by chat completions.
+    ///
+    /// Calls that would exceed the budget fail immediately with `GroqError::BudgetExceeded`.
+    ///
+    /// # Parameters
+    ///
+    /// - `budget`: The `Budget` holding the limits and the spending so far.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Enables a cache returning the responses of identical deterministic chat completion requests.
+    ///
+    /// # Parameters
+    ///
+    /// - `cache`: The `ResponseCache` holding the cached responses, shared with its clones.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn response_cache(mut self, cache: ResponseCache) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Sets whether an idempotency key is generated for requests that don't have one.
+    ///
+    /// Generated keys are random UUIDs, sent as the `Idempotency-Key` header and reused for every retry of the request.
+    ///
+    /// # Parameters
+    ///
+    /// - `enabled`: If true, a key is generated for every request without an explicit `idempotency_key`.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn auto_idempotency_keys(mut self, enabled: bool) -> Self {
+        self.auto_idempotency_keys = enabled;
+        self
+    }
+
+    /// Sets the timeout of each attempt, from sending the request until the response headers are received.
+    ///
+    /// Timed out attempts fail with `GroqError::Timeout`.
+    ///
+    /// # Parameters
+    ///
+    /// - `timeout`: The maximum duration of one attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the total time a call may take, covering every attempt and the delays between retries.
+    ///
+    /// Each attempt is cut short so that the call ends by the deadline. When it is reached, or when the next retry
+    /// would start after it, the call fails with `GroqError::DeadlineExceeded`.
+    ///
+    /// # Parameters
+    ///
+    /// - `deadline`: The maximum duration of a call.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Registers a callback called before every attempt is sent, with a view of the request where secrets are redacted.
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: The callback receiving the `RequestInfo` of the attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn on_request(mut self, hook: impl Fn(&RequestInfo) + Send + Sync + 'static) -> Self {
+        self.hooks.on_request = Some(Arc::new(hook));
+        self
+    }
+
+    /// Registers a callback called for every response received, successful or not.
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: The callback receiving the `ResponseInfo` of the attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn on_response(mut self, hook: impl Fn(&ResponseInfo) + Send + Sync + 'static) -> Self {
+        self.hooks.on_response = Some(Arc::new(hook));
+        self
+    }
+
+    /// Registers a callback called before a failed attempt is retried.
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: The callback receiving the `RetryInfo` of the failed attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn on_retry(mut self, hook: impl Fn(&RetryInfo<'_>) + Send + Sync + 'static) -> Self {
+        self.hooks.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Logs an equivalent `curl` command for every attempt, at info level under the `groq_api_rust::curl` target.
+    ///
+    /// The API key is never logged: the commands read it from the `GROQ_API_KEY` environment variable instead.
+    ///
+    /// # Parameters
+    ///
+    /// - `enabled`: If true, a `curl` command is logged before each attempt is sent.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn debug_curl(mut self, enabled: bool) -> Self {
+        self.hooks.debug_curl = enabled;
+        self
+    }
+
+    /// Replaces message content with placeholders holding its length and hash wherever this client exposes it.
+    ///
+    /// This covers the bodies seen by `on_request` hooks and `debug_curl`, and the response bodies kept in errors,
+    /// for applications that must not log personal data.
+    ///
+    /// # Parameters
+    ///
+    /// - `enabled`: If true, message content is redacted.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn redact_content(mut self, enabled: bool) -> Self {
+        self.hooks.redact_content = enabled;
+        self
+    }
+
+    /// Rewrites message content with the given scrubber before it leaves the process.
+    ///
+    /// The scrubber is applied to every chat message and to transcription prompts, before the request is hashed,
+    /// logged or sent. `mask_pii` masks email addresses and card numbers.
+    ///
+    /// # Parameters
+    ///
+    /// - `scrubber`: The function returning the text to send in place of the given one.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn scrub_content(
+        mut self,
+        scrubber: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.scrubber = Some(Arc::new(scrubber));
+        self
+    }
+
+    /// Records Prometheus metrics of the requests sent by this client.
+    ///
+    /// # Parameters
+    ///
+    /// - `metrics`: The `GroqMetrics` to record into.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: GroqMetrics) -> Self {
+        self.telemetry.metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the prices used to estimate the cost accumulated in `usage_summary`.
+    ///
+    /// # Parameters
+    ///
+    /// - `pricing`: The `PricingTable` to use instead of the default Groq prices.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn pricing(mut self, pricing: PricingTable) -> Self {
+        self.usage.pricing = Arc::new(pricing);
+        self
+    }
+
+    /// Appends one record per API call to the given log.
+    ///
+    /// # Parameters
+    ///
+    /// - `log`: The `UsageLog` receiving the records.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn usage_log(mut self, log: UsageLog) -> Self {
+        self.usage_log = Some(log);
+        self
+    }
+
+    /// Records an `AuditEntry` for every chat completion and speech-to-text call.
+    ///
+    /// Entries hold the `audit_context` of the request and a hash of it, never its content.
+    ///
+    /// # Parameters
+    ///
+    /// - `sink`: The `AuditSink` storing the entries, such as a `FileAuditSink`.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn audit(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Records the requests sent by this client to a cassette, or answers them from it without network access.
+    ///
+    /// # Parameters
+    ///
+    /// - `cassette`: The `Cassette` to record to or replay from.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    #[cfg(feature = "vcr")]
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Returns the usage of every model accumulated by this client since it was created or last reset.
+    ///
+    /// # Returns
+    ///
+    /// The `UsageSummary` holding the requests, tokens and processing time per model.
+    pub fn usage_summary(&self) -> UsageSummary {
+        self.usage.summary()
+    }
+
+    /// Resets the usage accumulated by this client.
+    ///
+    /// # Returns
+    ///
+    /// The `UsageSummary` accumulated before the reset.
+    pub fn reset_usage(&self) -> UsageSummary {
+        self.usage.reset()
+    }
+
+    /// Builds the options shared by every attempt of a call.
+    fn call_options(
+        &self,
+        idempotency_key: Option<&str>,
+        correlation_id: Option<&str>,
+    ) -> CallOptions {
+        CallOptions {
+            idempotency_key: idempotency_key
+                .map(|key| key.to_string())
+                .or_else(|| self.auto_idempotency_keys.then(random::uuid_v4)),
+            correlation_id: correlation_id.map(|id| id.to_string()),
+        }
+    }
+
+    /// Limits how many requests this client sends at the same time.
+    ///
+    /// Calls beyond the limit wait for a running one to finish, which keeps large `tokio::join!` fan-outs
+    /// from exhausting connections or tripping the API limits.
+    ///
+    /// # Parameters
+    ///
+    /// - `max`: The maximum number of requests in flight.
+    ///
+    /// # Returns
+    ///
+    /// The modified `AsyncGroqClient` instance.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.concurrency_limit = Some(Arc::new(Semaphore::new(max.max(1))));
+        self
+    }
+
+    /// Waits until the `RateLimiter`, if any, has capacity for the request.
+    ///
+    /// # Parameters
+    ///
+    /// - `model`: The model the request is sent to.
+    /// - `tokens`: The number of tokens the request may consume.
+    /// - `priority`: The priority of the request.
+    async fn wait_for_capacity(&self, model: &str, tokens: u64, priority: RequestPriority) {
+        let Some(limiter) = &self.rate_limiter else {
+            return;
+        };
+        loop {
+            match limiter.reserve(model, tokens, priority) {
+                Reservation::Granted(wait) => return tokio::time::sleep(wait).await,
+                Reservation::Denied(wait) => {
+                    tokio::time::sleep(wait.max(Duration::from_millis(1))).await
+                }
+            }
+        }
+    }
+
+    /// Waits for a free slot when `max_concurrent_requests` is configured.
+    ///
+    /// # Returns
+    ///
+    /// The permit holding the slot until it is dropped, or `None` when concurrency is not limited.
+    async fn acquire_slot(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.concurrency_limit {
+            // The semaphore is never closed, so acquiring only fails if that changes.
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        }
+    }
+
+    /// Prepares a request with the authorization, idempotency and correlation headers of the call.
+    fn request_builder(
+        &self,
+        method: Method,
+        link: &str,
+        options: &CallOptions,
+    ) -> ARequestBuilder {
+        let mut req = self.client.request(method, link);
+        if let Some((name, value)) = self.auth_scheme.header(&self.api_key) {
+            req = match auth::sensitive(name, value) {
+                Ok(headers) => req.headers(headers),
+                Err(value) => req.header(name, value),
+            };
+        }
+        if let Some(query) = self.auth_scheme.query(&self.api_key) {
+            req = req.query(&[query]);
+        }
+        if let Some(key) = &options.idempotency_key {
+            req = req.header("Idempotency-Key", key);
+        }
+        if let Some(id) = &options.correlation_id {
+            req = req.header("X-Correlation-ID", id);
+        }
+        req
+    }
+
+    /// Sends an authenticated request to `link`, retrying it according to the configured `RetryPolicy`.
+    ///
+    /// The request is rebuilt for every attempt, so bodies that can only be sent once (such as multipart forms) are recreated.
+    /// Every attempt goes through the configured `CircuitBreaker`, if any.
+    ///
+    /// # Parameters
+    ///
+    /// - `method`: The HTTP method of the request.
+    /// - `link`: The URL link to send the request to.
+    /// - `options`: The per-call options, such as the idempotency key, applied to every attempt.
+    /// - `build`: A closure adding the body and any extra headers to the request.
+    ///
+    /// # Returns
+    ///
+    /// The HTTP response and the time the last attempt took to receive its headers. Without a retry policy,
+    /// unsuccessful responses are returned as-is; with one, the error of the last failed attempt is returned once the
+    /// failure is not retried.
+    async fn execute<F>(
+        &self,
+        method: Method,
+        link: &str,
+        options: &CallOptions,
+        build: F,
+    ) -> Result<(AResponse, Duration), GroqError>
+    where
+        F: Fn(ARequestBuilder) -> ARequestBuilder,
+    {
+        let policy = self.retry_policy.as_deref();
+        let call_started = Instant::now();
+        let mut attempt = 1;
+        loop {
+            let permit = self
+                .circuit_breaker
+                .as_ref()
+                .map(|breaker| breaker.check(link))
+                .transpose()?;
+            let mut req = self.request_builder(method.clone(), link, options);
+            if let Some(timeout) = attempt_timeout(self.timeout, self.deadline, call_started) {
+                req = req.timeout(timeout);
+            }
+            let request = build(req).build()?;
+            self.hooks.request(
+                request.method(),
+                &self.auth_scheme.redact_url(request.url()),
+                request.headers(),
+                request.body().and_then(|body| body.as_bytes()),
+                attempt,
+                options.correlation_id.as_deref(),
+            );
+            let started = Instant::now();
+            let span = self.telemetry.attempt(link);
+            #[cfg(feature = "vcr")]
+            let result = match &self.cassette {
+                Some(cassette) => {
+                    let url = self.auth_scheme.redact_url(request.url()).to_string();
+                    cassette.execute_async(&self.client, request, url).await?
+                }
+                None => self.client.execute(request).await,
+            };
+            #[cfg(not(feature = "vcr"))]
+            let result = self.client.execute(request).await;
+            span.finish(
+                result.as_ref().ok().map(|res| res.status().as_u16()),
+                started.elapsed(),
+            );
+            if let Ok(res) = &result {
+                self.hooks.response(
+                    link,
+                    attempt,
+                    started.elapsed(),
+                    res.status(),
+                    res.headers(),
+                    options.correlation_id.as_deref(),
+                );
+            }
+            if let Some(permit) = permit {
+                let failed = match &result {
+                    Ok(res) => res.status().is_server_error(),
+                    Err(err) => err.is_timeout() || err.is_connect(),
+                };
+                permit.record(failed);
+            }
+            let error = match result {
+                Ok(res) if res.status().is_success() || policy.is_none() => {
+                    return Ok((res, started.elapsed()))
+                }
+                Ok(res) => {
+                    let status = res.status();
+                    let headers = res.headers().clone();
+                    let body = res.text().await.unwrap_or_default();
+                    GroqError::from_body(status, &headers, &body)
+                }
+                Err(err) => {
+                    GroqError::from_transport(self.auth_scheme.redact_error(err), started.elapsed())
+                }
+            };
+            let error = self.hooks.redact_error(error);
+            self.telemetry.record_error(link, &error);
+            let delay = policy.and_then(|policy| policy.decide(attempt, &error));
+            let error = past_deadline(self.deadline, call_started, attempt, delay, error)?;
+            match delay {
+                Some(delay) => {
+                    telemetry::retrying(attempt, delay, &error);
+                    self.hooks.retry(
+                        link,
+                        attempt,
+                        delay,
+                        &error,
+                        options.correlation_id.as_deref(),
+                    );
+                    tokio::time::sleep(delay).await
+                }
+                None => return Err(error),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends a request to the Groq API with the provided JSON body and returns the parsed response.
+    ///
+    /// # Parameters
+    ///
+    /// - `body`: The JSON body to send in the request.
+    /// - `link`: The URL link to send the request to.
+    /// - `options`: The per-call options applied to every attempt.
+    ///
+    /// # Returns
+    ///
+    /// The parsed JSON response from the Groq API and its `ResponseMeta`.
+    async fn send_request(
+        &self,
+        body: impl Serialize,
+        link: &str,
+        options: &CallOptions,
+    ) -> Result<(Value, ResponseMeta), GroqError> {
+        let (res, time_to_first_byte) = self
+            .execute(Method::POST, link, options, |req| {
+                req.header("Content-Type", "application/json").json(&body)
+            })
+            .await?;
+
+        self.parse_response(res, time_to_first_byte).await
+    }
+
+    #[cfg(feature = "audio")]
+    /// Sends a speech-to-text request to the Groq API and returns the parsed response.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `SpeechToTextRequest` containing the audio file, temperature, language, and other options.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `SpeechToTextResponse` from the Groq API.
+    pub async fn speech_to_text(
+        &self,
+        request: SpeechToTextRequest,
+    ) -> Result<SpeechToTextResponse, GroqError> {
+        let (response, _) = self.speech_to_text_with_meta(request).await?;
+        Ok(response)
+    }
+
+    #[cfg(feature = "audio")]
+    /// Sends a speech-to-text request to the Groq API and returns the parsed response along with its HTTP metadata.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `SpeechToTextRequest` containing the audio file, temperature, language, and other options.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `SpeechToTextResponse` from the Groq API and the `ResponseMeta` holding its headers, request ID and rate limit state.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "groq.speech_to_text",
+            skip_all,
+            fields(
+                endpoint = %self.endpoint,
+                model = request.model.as_deref().unwrap_or_default(),
+                correlation_id = request.correlation_id.as_deref().unwrap_or_default(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn speech_to_text_with_meta(
+        &self,
+        request: SpeechToTextRequest,
+    ) -> Result<(SpeechToTextResponse, ResponseMeta), GroqError> {
+        request.validate()?;
+        let request = self.hooks.scrub_speech(request);
+        let started = Instant::now();
+        let model = request.model.clone().unwrap_or_default();
+        let correlation_id = request.correlation_id.clone();
+        let audit = self
+            .audit
+            .as_ref()
+            .map(|_| (request.audit_context.clone(), speech_request_hash(&request)));
+        let endpoint = if request.english_text {
+            "/audio/translations"
+        } else {
+            "/audio/transcriptions"
+        };
+        let result = self
+            .send_speech_to_text(request)
+            .await
+            .map(|(response, meta)| (response, meta.with_total(started.elapsed(), None)))
+            .map_err(|err| {
+                self.hooks
+                    .redact_error(err)
+                    .with_correlation_id(correlation_id.as_deref())
+            });
+        if let Some(log) = &self.usage_log {
+            let record = UsageRecord::new(endpoint, &model, started.elapsed());
+            log.write(&match &result {
+                Ok((_, meta)) => record.success(meta, None),
+                Err(err) => record.failure(err),
+            });
+        }
+        if let (Some(sink), Some((context, hash))) = (&self.audit, audit) {
+            sink.record(&AuditEntry::new(
+                context.as_deref(),
+                endpoint,
+                &model,
+                hash,
+                &result,
+            ));
+        }
+        result
+    }
+
+    #[cfg(feature = "audio")]
+    /// Sends a speech-to-text request and returns the parsed response along with its HTTP metadata.
+    async fn send_speech_to_text(
+        &self,
+        request: SpeechToTextRequest,
+    ) -> Result<(SpeechToTextResponse, ResponseMeta), GroqError> {
+        let link_addition = if request.english_text {
+            "/audio/translations"
+        } else {
+            "/audio/transcriptions"
+        };
+
+        self.wait_for_capacity(
+            request.model.as_deref().unwrap_or_default(),
+            0,
+            request.priority,
+        )
+        .await;
+        let _slot = self.acquire_slot().await;
+        let link = format!("{}{}", self.endpoint, link_addition);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let (response, time_to_first_byte) = self
+            .execute(Method::POST, &link, &options, |req| {
+                req.multipart(speech_form::<AForm>(&request))
+            })
+            .await?;
+
+        let (body, meta) = self.read_response(response, time_to_first_byte).await?;
+        let format = request.response_format.unwrap_or_default();
+        Ok((format.parse(&body)?, meta))
+    }
+
+    /// Sends a text-to-speech request to the Groq API and returns the synthesized audio.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `TextToSpeechRequest` containing the model, text, voice, and audio format.
+    ///
+    /// # Returns
+    ///
+    /// The audio file returned by the Groq API, in the requested format.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "groq.text_to_speech",
+            skip_all,
+            fields(
+                endpoint = %self.endpoint,
+                model = %request.model,
+                correlation_id = request.correlation_id.as_deref().unwrap_or_default(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn text_to_speech(&self, request: TextToSpeechRequest) -> Result<Vec<u8>, GroqError> {
+        request.validate()?;
+        let started = Instant::now();
+        let audit = self
+            .audit
+            .as_ref()
+            .map(|_| (request.audit_context.clone(), tts_request_hash(&request)));
+        let result = self
+            .send_text_to_speech(&request)
+            .await
+            .map(|(audio, meta)| (audio, meta.with_total(started.elapsed(), None)))
+            .map_err(|err| {
+                self.hooks
+                    .redact_error(err)
+                    .with_correlation_id(request.correlation_id.as_deref())
+            });
+        if result.is_ok() {
+            self.usage.record(&request.model, None);
+        }
+        if let Some(log) = &self.usage_log {
+            let record = UsageRecord::new("/audio/speech", &request.model, started.elapsed());
+            log.write(&match &result {
+                Ok((_, meta)) => record.success(meta, None),
+                Err(err) => record.failure(err),
+            });
+        }
+        if let (Some(sink), Some((context, hash))) = (&self.audit, audit) {
+            sink.record(&AuditEntry::new(
+                context.as_deref(),
+                "/audio/speech",
+                &request.model,
+                hash,
+                &result,
+            ));
+        }
+        result.map(|(audio, _)| audio)
+    }
+
+    /// Sends a text-to-speech request and returns the audio along with its HTTP metadata.
+    async fn send_text_to_speech(
+        &self,
+        request: &TextToSpeechRequest,
+    ) -> Result<(Vec<u8>, ResponseMeta), GroqError> {
+        let body = request.to_json();
+        self.wait_for_capacity(&request.model, 0, request.priority)
+            .await;
+        let _slot = self.acquire_slot().await;
+        let link = format!("{}/audio/speech", self.endpoint);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let (response, time_to_first_byte) = self
+            .execute(Method::POST, &link, &options, |req| {
+                req.header("Content-Type", "application/json").json(&body)
+            })
+            .await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(GroqError::from_body(status, &headers, &text));
+        }
+        let audio = response
+            .bytes()
+            .await
+            .map_err(|err| GroqError::from(err).with_response(status, &headers))?;
+        let mut meta = ResponseMeta::from_response(status, &headers);
+        meta.timing.time_to_first_byte = time_to_first_byte;
+        Ok((audio.to_vec(), meta))
+    }
+
+    /// Checks that the API is reachable and accepts the API key, with a cheap authenticated request listing the models.
+    ///
+    /// Meant for startup checks: an invalid key fails with `GroqError::Unauthorized`, an unreachable host with
+    /// `GroqError::RequestFailed` or `GroqError::Timeout`, an unavailable API with `GroqError::ServerError`, and an
+    /// endpoint that is not an OpenAI-compatible API with `GroqError::NotFound` or `GroqError::JsonParseError`.
+    ///
+    /// # Returns
+    ///
+    /// The `ResponseMeta` of the check, holding its latency and the rate limit state.
+    pub async fn ping(&self) -> Result<ResponseMeta, GroqError> {
+        let started = Instant::now();
+        let link = format!("{}/models", self.endpoint);
+        let (response, time_to_first_byte) = self
+            .execute(Method::GET, &link, &CallOptions::default(), |req| req)
+            .await?;
+        let (_, meta) = self.parse_response(response, time_to_first_byte).await?;
+        Ok(meta.with_total(started.elapsed(), None))
+    }
+
+    /// Lists the models currently served by the API, with their context windows and output limits.
+    ///
+    /// Merge the listing into a `ModelRegistry` with `update` to refresh the built-in capabilities.
+    ///
+    /// # Returns
+    ///
+    /// The listed models, with default capabilities besides the ones reported by the API.
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, GroqError> {
+        let link = format!("{}/models", self.endpoint);
+        let (response, time_to_first_byte) = self
+            .execute(Method::GET, &link, &CallOptions::default(), |req| req)
+            .await?;
+        let (body, _) = self.parse_response(response, time_to_first_byte).await?;
+        Ok(models::parse_listing(&body))
+    }
+
+    /// Builds a chat completion request exactly as `chat_completion` would send it, without sending it.
+    ///
+    /// Meant for asserting request construction in unit tests and for debugging parameters. No network I/O is done,
+    /// and budgets, rate limits and hooks are not involved. The `Authorization` header is redacted, and message
+    /// content is replaced by placeholders when `redact_content` is enabled.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `ChatCompletionRequest` to build.
+    ///
+    /// # Returns
+    ///
+    /// The `DryRun` holding the method, URL, headers and JSON body of the request.
+    pub fn dry_run(&self, request: &ChatCompletionRequest) -> Result<DryRun, GroqError> {
+        let request = &self.hooks.scrub_chat(request.clone());
+        let link = format!("{}/chat/completions", self.endpoint);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let body = chat_completion_body(request, &request.model);
+        let built = self
+            .request_builder(Method::POST, &link, &options)
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .build()?;
+        Ok(self.hooks.dry_run(
+            built.method(),
+            &self.auth_scheme.redact_url(built.url()),
+            built.headers(),
+            body.to_value(),
+        ))
+    }
+
+    /// Sends a chat completion request to the Groq API and returns the parsed response.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `ChatCompletionRequest` containing the model, messages, temperature, max tokens, top-p, and other options.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `ChatCompletionResponse` from the Groq API.
+    pub async fn chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, GroqError> {
+        let (response, _) = self.chat_completion_with_meta(request).await?;
+        Ok(response)
+    }
+
+    #[cfg(feature = "stream")]
+    /// Sends a chat completion request and returns the answer as a stream of chunks, read as they are generated.
+    ///
+    /// The budget is settled, and the call recorded by the usage tracker, the usage log and the audit sink, once the
+    /// stream ends, from the usage reported by its last chunk. Response caches, fallback models and continuations only
+    /// apply to `chat_completion`.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `ChatCompletionRequest` containing the model, messages, temperature, max tokens, top-p, and other options.
+    ///
+    /// # Returns
+    ///
+    /// The `AsyncChatCompletionStream` of the answer. Errors while streaming are returned by the stream.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "groq.chat_completion_stream",
+            skip_all,
+            fields(
+                endpoint = %self.endpoint,
+                model = %request.model,
+                correlation_id = request.correlation_id.as_deref().unwrap_or_default(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                prompt_tokens = tracing::field::Empty,
+                completion_tokens = tracing::field::Empty,
+                total_tokens = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<AsyncChatCompletionStream, GroqError> {
+        request.validate()?;
+        let request = self.hooks.scrub_chat(request);
+        let mut bookkeeping = self.stream_bookkeeping(&request)?;
+        self.wait_for_capacity(
+            &request.model,
+            estimate_request_tokens(&request),
+            request.priority,
+        )
+        .await;
+        let _slot = self.acquire_slot().await;
+        let link = format!("{}/chat/completions", self.endpoint);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let mut body = chat_completion_body(&request, &request.model);
+        body.stream = true;
+        let result = async {
+            let (response, time_to_first_byte) = self
+                .execute(Method::POST, &link, &options, |req| {
+                    req.header("Content-Type", "application/json").json(&body)
+                })
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                let headers = response.headers().clone();
+                let text = response.text().await.unwrap_or_default();
+                return Err(GroqError::from_body(status, &headers, &text));
+            }
+            Ok((response, time_to_first_byte))
+        }
+        .await
+        .map_err(|err: GroqError| {
+            self.hooks
+                .redact_error(err)
+                .with_correlation_id(request.correlation_id.as_deref())
+        });
+        match result {
+            Ok((response, time_to_first_byte)) => {
+                bookkeeping.open(time_to_first_byte);
+                Ok(
+                    AsyncChatCompletionStream::from_response(response)
+                        .with_bookkeeping(bookkeeping),
+                )
+            }
+            Err(err) => {
+                bookkeeping.finish(Err(&err), false);
+                Err(err)
+            }
+        }
+    }
+
+    #[cfg(feature = "stream")]
+    /// Starts the bookkeeping of a streamed chat completion, reserving its tokens against the budget.
+    fn stream_bookkeeping(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<StreamBookkeeping, GroqError> {
+        let audit = self.audit.as_ref().map(|sink| {
+            let hash = chat_request_hash(request);
+            (sink.clone(), request.audit_context.clone(), hash)
+        });
+        let mut bookkeeping = StreamBookkeeping::new(
+            request.model.clone(),
+            Instant::now(),
+            self.usage.clone(),
+            self.usage_log.clone(),
+            audit,
+            self.telemetry.clone(),
+            telemetry::StreamSpan::current(),
+        );
+        let reservation = self
+            .budget
+            .as_ref()
+            .map(|budget| budget.reserve(request, &self.usage.pricing))
+            .transpose();
+        match reservation {
+            Ok(reservation) => {
+                bookkeeping.reservation = reservation;
+                Ok(bookkeeping)
+            }
+            Err(err) => {
+                bookkeeping.finish(Err(&err), false);
+                Err(err)
+            }
+        }
+    }
+
+    /// Sends a chat completion request constrained to the JSON schema of `T`, and deserializes the answer into `T`.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `ChatCompletionRequest` to send, whose response format is replaced by `ResponseFormat::json_schema_for::<T>()`.
+    ///
+    /// # Returns
+    ///
+    /// The answer of the model, deserialized into `T`.
+    pub async fn chat_completion_json<T: ToolParameter + DeserializeOwned>(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<T, GroqError> {
+        let request = request.response_format(ResponseFormat::json_schema_for::<T>());
+        self.chat_completion(request).await?.parse_json()
+    }
+
+    /// Sends a chat completion request to the Groq API and returns the parsed response along with its HTTP metadata.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `ChatCompletionRequest` containing the model, messages, temperature, max tokens, top-p, and other options.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `ChatCompletionResponse` from the Groq API and the `ResponseMeta` holding its headers, request ID and rate limit state.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "groq.chat_completion",
+            skip_all,
+            fields(
+                endpoint = %self.endpoint,
+                model = %request.model,
+                correlation_id = request.correlation_id.as_deref().unwrap_or_default(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                prompt_tokens = tracing::field::Empty,
+                completion_tokens = tracing::field::Empty,
+                total_tokens = tracing::field::Empty,
+            )
+        )
+    )]
+    pub async fn chat_completion_with_meta(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<(ChatCompletionResponse, ResponseMeta), GroqError> {
+        request.validate()?;
+        let request = self.hooks.scrub_chat(request);
+        let started = Instant::now();
+        let model = request.model.clone();
+        let correlation_id = request.correlation_id.clone();
+        let audit = self
+            .audit
+            .as_ref()
+            .map(|_| (request.audit_context.clone(), chat_request_hash(&request)));
+        let result = self
+            .send_with_continuations(request)
+            .await
+            .map(|(response, meta)| {
+                let meta = meta.with_total(started.elapsed(), response.usage.as_ref());
+                (response, meta)
+            })
+            .map_err(|err| {
+                self.hooks
+                    .redact_error(err)
+                    .with_correlation_id(correlation_id.as_deref())
+            });
+        if let Some(log) = &self.usage_log {
+            let record = UsageRecord::new("/chat/completions", &model, started.elapsed());
+            log.write(&match &result {
+                Ok((response, meta)) => record.success(meta, response.usage.as_ref()),
+                Err(err) => record.failure(err),
+            });
+        }
+        if let (Some(sink), Some((context, hash))) = (&self.audit, audit) {
+            sink.record(&AuditEntry::new(
+                context.as_deref(),
+                "/chat/completions",
+                &model,
+                hash,
+                &result,
+            ));
+        }
+        result
+    }
+
+    /// Sends a chat completion request, continuing the answer as long as it is cut by the token limit and the request
+    /// allows more continuations.
+    async fn send_with_continuations(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<(ChatCompletionResponse, ResponseMeta), GroqError> {
+        let base = (request.max_continuations > 0).then(|| request.clone());
+        let (mut response, mut meta) = self.send_chat_completion(request).await?;
+        if let Some(base) = base {
+            for attempt in 1..=base.max_continuations {
+                let Some(next) = continuation_request(&base, &response, attempt) else {
+                    break;
+                };
+                let (continuation, continuation_meta) = self.send_chat_completion(next).await?;
+                merge_continuation(&mut response, continuation);
+                meta = continuation_meta;
+            }
+        }
+        Ok((response, meta))
+    }
+
+    /// Sends a chat completion request, trying the fallback models if the requested one is decommissioned.
+    async fn send_chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<(ChatCompletionResponse, ResponseMeta), GroqError> {
+        let cache_lookup = self.response_cache.as_ref().and_then(|cache| {
+            cache.lookup(
+                &request,
+                &chat_completion_body(&request, &request.model).to_value(),
+            )
+        });
+        if let Some(response) = cache_lookup.as_ref().and_then(CacheLookup::response) {
+            return Ok((response, ResponseMeta::cached()));
+        }
+        let estimated_tokens = estimate_request_tokens(&request);
+        let reservation = self
+            .budget
+            .as_ref()
+            .map(|budget| budget.reserve(&request, &self.usage.pricing))
+            .transpose()?;
+        self.wait_for_capacity(&request.model, estimated_tokens, request.priority)
+            .await;
+        let _slot = self.acquire_slot().await;
+
+        let link = format!("{}/chat/completions", self.endpoint);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let mut models = chat_completion_models(&request).peekable();
+        loop {
+            let Some(model) = models.next() else {
+                unreachable!("the requested model is always tried");
+            };
+            let body = chat_completion_body(&request, model);
+            let result = self
+                .send_request(body, &link, &options.for_model(&request, model))
+                .await;
+            match result {
+                Err(err) if is_model_decommissioned(&err) && models.peek().is_some() => {
+                    log::warn!("Model {model} is decommissioned, falling back to the next model");
+                }
+                result => {
+                    let (response, meta) = result?;
+                    let chat_completion_response: ChatCompletionResponse = deserialize(response)?;
+                    self.telemetry.record_usage(
+                        &chat_completion_response.model,
+                        chat_completion_response.usage.as_ref(),
+                    );
+                    self.usage.record(
+                        &chat_completion_response.model,
+                        chat_completion_response.usage.as_ref(),
+                    );
+                    if let Some(reservation) = reservation {
+                        reservation.settle(
+                            &chat_completion_response.model,
+                            chat_completion_response.usage.as_ref(),
+                        );
+                    }
+                    if let (Some(cache), Some(lookup)) = (&self.response_cache, cache_lookup) {
+                        cache.store(lookup, &chat_completion_response);
+                    }
+                    return Ok((chat_completion_response, meta));
+                }
+            }
+        }
+    }
+
+    /// Parses the response from a Groq API request and returns the response body as a JSON value.
+    ///
+    /// # Parameters
+    ///
+    /// - `response`: The HTTP response from the Groq API request.
+    /// - `time_to_first_byte`: The time the request took to receive the response headers.
+    ///
+    /// # Returns
+    ///
+    /// The parsed JSON value from the response body and the `ResponseMeta`, or a `GroqError` if the response was not successful.
+    async fn parse_response(
+        &self,
+        response: AResponse,
+        time_to_first_byte: Duration,
+    ) -> Result<(Value, ResponseMeta), GroqError> {
+        let (text, meta) = self.read_response(response, time_to_first_byte).await?;
+        let body: Value = serde_json::from_str(&text)?;
+        Ok((body, meta))
+    }
+
+    /// Reads the body of a response from a Groq API request as text, for responses that are not JSON.
+    ///
+    /// # Parameters
+    ///
+    /// - `response`: The HTTP response from the Groq API request.
+    /// - `time_to_first_byte`: The time the request took to receive the response headers.
+    ///
+    /// # Returns
+    ///
+    /// The response body and the `ResponseMeta`, or a `GroqError` if the response was not successful.
+    async fn read_response(
+        &self,
+        response: AResponse,
+        time_to_first_byte: Duration,
+    ) -> Result<(String, ResponseMeta), GroqError> {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response
+            .text()
+            .await
+            .map_err(|err| GroqError::from(err).with_response(status, &headers))?;
+        if !status.is_success() {
+            return Err(GroqError::from_body(status, &headers, &text));
+        }
+        let mut meta = ResponseMeta::from_response(status, &headers);
+        meta.timing.time_to_first_byte = time_to_first_byte;
+
+        Ok((text, meta))
+    }
+}
+
+#[cfg(feature = "blocking")]
+#[derive(Clone)]
+/// An client for interacting with the Groq API.
+///
+/// # Parameters
+///
+/// - `api_key`: The API key for authenticating with the Groq API.
+/// - `endpoint`: The URL of the Groq API endpoint. If not provided, it defaults to <https://api.groq.com/openai/v1>.
+///
+/// # Returns
+///
+/// An instance of `GroqClient` configured with the provided API key and endpoint.
+///
+/// # Example
+///
+///```
+/// use groq_api_rust::GroqClient;
+///
+/// let client = GroqClient::new("my_api_key".to_string(), None);
+///```
+///
+/// # Sharing
+///
+/// `GroqClient` is `Send` and `Sync`, and cloning it does not allocate: the clones share the connection pool, the
+/// API key and the state of the rate limiter, circuit breaker, budget, cache and usage tracking.
+pub struct GroqClient {
+    api_key: Arc<str>,
+    auth_scheme: Arc<AuthScheme>,
+    client: Client,
+    endpoint: Arc<str>,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
+    rate_limiter: Option<RateLimiter>,
+    circuit_breaker: Option<CircuitBreaker>,
+    budget: Option<Budget>,
+    response_cache: Option<ResponseCache>,
+    auto_idempotency_keys: bool,
+    timeout: Option<Duration>,
+    deadline: Option<Duration>,
+    hooks: Hooks,
+    telemetry: telemetry::Recorder,
+    usage: UsageTracker,
+    usage_log: Option<UsageLog>,
+    audit: Option<Arc<dyn AuditSink>>,
+    #[cfg(feature = "vcr")]
+    cassette: Option<Arc<Cassette>>,
+}
+
+#[cfg(feature = "blocking")]
+impl GroqClient {
+    /// Constructs a new `GroqClient` instance with the provided API key and optional endpoint.
+    ///
+    /// # Parameters
+    ///
+    /// - `api_key`: The API key for authenticating with the Groq API.
+    /// - `endpoint`: The URL of the Groq API endpoint. If not provided, it defaults to <https://api.groq.com/openai/v1>.
+    ///
+    /// # Returns
+    ///
+    /// A new `GroqClient` instance configured with the provided API key and endpoint.
+    pub fn new(api_key: impl Into<String>, endpoint: Option<String>) -> Self {
+        let ep = endpoint.unwrap_or_else(|| String::from("https://api.groq.com/openai/v1"));
+        Self {
+            api_key: Arc::from(api_key.into()),
+            auth_scheme: Arc::new(AuthScheme::Bearer),
+            client: Client::new(),
+            endpoint: ep.into(),
+            retry_policy: None,
+            rate_limiter: None,
+            circuit_breaker: None,
+            budget: None,
+            response_cache: None,
+            auto_idempotency_keys: false,
+            timeout: None,
+            deadline: None,
+            hooks: Hooks::default(),
+            telemetry: telemetry::Recorder::default(),
+            usage: UsageTracker::default(),
+            usage_log: None,
+            audit: None,
+            #[cfg(feature = "vcr")]
+            cassette: None,
+        }
+    }
+
+    /// Creates a client for an OpenAI-compatible server other than Groq, such as vLLM, Ollama or LiteLLM.
+    ///
+    /// The API key is sent as a bearer token when one is given, and not at all otherwise; `auth_scheme` can change
+    /// how it is sent. Responses missing fields that Groq always sends, such as the timings of `Usage`, are accepted.
+    ///
+    /// # Parameters
+    ///
+    /// - `endpoint`: The base URL of the API, such as <http://localhost:11434/v1> for Ollama.
+    /// - `api_key`: The API key of the server, if it requires one.
+    ///
+    /// # Returns
+    ///
+    /// A new `GroqClient` instance sending every request to `endpoint`.
+    pub fn openai_compatible(endpoint: impl Into<String>, api_key: Option<String>) -> Self {
+        let auth_scheme = match api_key {
+            Some(_) => AuthScheme::Bearer,
+            None => AuthScheme::None,
+        };
+        Self::new(api_key.unwrap_or_default(), Some(endpoint.into())).auth_scheme(auth_scheme)
+    }
+
+    /// Sets how the API key is sent, for gateways and servers that do not expect a bearer token.
+    ///
+    /// The header carrying the key is redacted from hooks, dry runs and the `curl` commands built from them.
+    ///
+    /// # Parameters
+    ///
+    /// - `scheme`: The `AuthScheme` applied to every request, `AuthScheme::Bearer` by default.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn auth_scheme(mut self, scheme: AuthScheme) -> Self {
+        self.auth_scheme = Arc::new(scheme);
+        self
+    }
+
+    /// Enables automatic retries of failed requests according to the given policy.
+    ///
+    /// # Parameters
+    ///
+    /// - `policy`: The `RetryPolicy` deciding which failures are retried and how long to wait between attempts,
+    ///   such as `ExponentialBackoff`.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn retry_policy(mut self, policy: impl RetryPolicy + 'static) -> Self {
+        self.retry_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Enables client-side rate limiting, blocking until there is capacity before each request is sent.
+    ///
+    /// # Parameters
+    ///
+    /// - `limiter`: The `RateLimiter` holding the request and token budgets per model.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Enables a circuit breaker failing requests fast while an endpoint keeps failing.
+    ///
+    /// # Parameters
+    ///
+    /// - `breaker`: The `CircuitBreaker` tracking failures per endpoint.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Enables a budget capping the tokens and estimated cost spent by chat completions.
+    ///
+    /// Calls that would exceed the budget fail immediately with `GroqError::BudgetExceeded`.
+    ///
+    /// # Parameters
+    ///
+    /// - `budget`: The `Budget` holding the limits and the spending so far.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Enables a cache returning the responses of identical deterministic chat completion requests.
+    ///
+    /// # Parameters
+    ///
+    /// - `cache`: The `ResponseCache` holding the cached responses, shared with its clones.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn response_cache(mut self, cache: ResponseCache) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Sets whether an idempotency key is generated for requests that don't have one.
+    ///
+    /// Generated keys are random UUIDs, sent as the `Idempotency-Key` header and reused for every retry of the request.
+    ///
+    /// # Parameters
+    ///
+    /// - `enabled`: If true, a key is generated for every request without an explicit `idempotency_key`.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn auto_idempotency_keys(mut self, enabled: bool) -> Self {
+        self.auto_idempotency_keys = enabled;
+        self
+    }
+
+    /// Sets the timeout of each attempt, from sending the request until the response headers are received.
+    ///
+    /// Timed out attempts fail with `GroqError::Timeout`.
+    ///
+    /// # Parameters
+    ///
+    /// - `timeout`: The maximum duration of one attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the total time a call may take, covering every attempt and the delays between retries.
+    ///
+    /// Each attempt is cut short so that the call ends by the deadline. When it is reached, or when the next retry
+    /// would start after it, the call fails with `GroqError::DeadlineExceeded`.
+    ///
+    /// # Parameters
+    ///
+    /// - `deadline`: The maximum duration of a call.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Registers a callback called before every attempt is sent, with a view of the request where secrets are redacted.
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: The callback receiving the `RequestInfo` of the attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn on_request(mut self, hook: impl Fn(&RequestInfo) + Send + Sync + 'static) -> Self {
+        self.hooks.on_request = Some(Arc::new(hook));
+        self
+    }
+
+    /// Registers a callback called for every response received, successful or not.
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: The callback receiving the `ResponseInfo` of the attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn on_response(mut self, hook: impl Fn(&ResponseInfo) + Send + Sync + 'static) -> Self {
+        self.hooks.on_response = Some(Arc::new(hook));
+        self
+    }
+
+    /// Registers a callback called before a failed attempt is retried.
+    ///
+    /// # Parameters
+    ///
+    /// - `hook`: The callback receiving the `RetryInfo` of the failed attempt.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn on_retry(mut self, hook: impl Fn(&RetryInfo<'_>) + Send + Sync + 'static) -> Self {
+        self.hooks.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Logs an equivalent `curl` command for every attempt, at info level under the `groq_api_rust::curl` target.
+    ///
+    /// The API key is never logged: the commands read it from the `GROQ_API_KEY` environment variable instead.
+    ///
+    /// # Parameters
+    ///
+    /// - `enabled`: If true, a `curl` command is logged before each attempt is sent.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn debug_curl(mut self, enabled: bool) -> Self {
+        self.hooks.debug_curl = enabled;
+        self
+    }
+
+    /// Replaces message content with placeholders holding its length and hash wherever this client exposes it.
+    ///
+    /// This covers the bodies seen by `on_request` hooks and `debug_curl`, and the response bodies kept in errors,
+    /// for applications that must not log personal data.
+    ///
+    /// # Parameters
+    ///
+    /// - `enabled`: If true, message content is redacted.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn redact_content(mut self, enabled: bool) -> Self {
+        self.hooks.redact_content = enabled;
+        self
+    }
+
+    /// Rewrites message content with the given scrubber before it leaves the process.
+    ///
+    /// The scrubber is applied to every chat message and to transcription prompts, before the request is hashed,
+    /// logged or sent. `mask_pii` masks email addresses and card numbers.
+    ///
+    /// # Parameters
+    ///
+    /// - `scrubber`: The function returning the text to send in place of the given one.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn scrub_content(
+        mut self,
+        scrubber: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.scrubber = Some(Arc::new(scrubber));
+        self
+    }
+
+    /// Records Prometheus metrics of the requests sent by this client.
+    ///
+    /// # Parameters
+    ///
+    /// - `metrics`: The `GroqMetrics` to record into.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: GroqMetrics) -> Self {
+        self.telemetry.metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the prices used to estimate the cost accumulated in `usage_summary`.
+    ///
+    /// # Parameters
+    ///
+    /// - `pricing`: The `PricingTable` to use instead of the default Groq prices.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn pricing(mut self, pricing: PricingTable) -> Self {
+        self.usage.pricing = Arc::new(pricing);
+        self
+    }
+
+    /// Appends one record per API call to the given log.
+    ///
+    /// # Parameters
+    ///
+    /// - `log`: The `UsageLog` receiving the records.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn usage_log(mut self, log: UsageLog) -> Self {
+        self.usage_log = Some(log);
+        self
+    }
+
+    /// Records an `AuditEntry` for every chat completion and speech-to-text call.
+    ///
+    /// Entries hold the `audit_context` of the request and a hash of it, never its content.
+    ///
+    /// # Parameters
+    ///
+    /// - `sink`: The `AuditSink` storing the entries, such as a `FileAuditSink`.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    pub fn audit(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Records the requests sent by this client to a cassette, or answers them from it without network access.
+    ///
+    /// # Parameters
+    ///
+    /// - `cassette`: The `Cassette` to record to or replay from.
+    ///
+    /// # Returns
+    ///
+    /// The modified `GroqClient` instance.
+    #[cfg(feature = "vcr")]
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Returns the usage of every model accumulated by this client since it was created or last reset.
+    ///
+    /// # Returns
+    ///
+    /// The `UsageSummary` holding the requests, tokens and processing time per model.
+    pub fn usage_summary(&self) -> UsageSummary {
+        self.usage.summary()
+    }
+
+    /// Resets the usage accumulated by this client.
+    ///
+    /// # Returns
+    ///
+    /// The `UsageSummary` accumulated before the reset.
+    pub fn reset_usage(&self) -> UsageSummary {
+        self.usage.reset()
+    }
+
+    /// Builds the options shared by every attempt of a call.
+    fn call_options(
+        &self,
+        idempotency_key: Option<&str>,
+        correlation_id: Option<&str>,
+    ) -> CallOptions {
+        CallOptions {
+            idempotency_key: idempotency_key
+                .map(|key| key.to_string())
+                .or_else(|| self.auto_idempotency_keys.then(random::uuid_v4)),
+            correlation_id: correlation_id.map(|id| id.to_string()),
+        }
+    }
+
+    /// Blocks until the `RateLimiter`, if any, has capacity for the request.
+    ///
+    /// # Parameters
+    ///
+    /// - `model`: The model the request is sent to.
+    /// - `tokens`: The number of tokens the request may consume.
+    /// - `priority`: The priority of the request.
+    fn wait_for_capacity(&self, model: &str, tokens: u64, priority: RequestPriority) {
+        let Some(limiter) = &self.rate_limiter else {
+            return;
+        };
+        loop {
+            match limiter.reserve(model, tokens, priority) {
+                Reservation::Granted(wait) => return std::thread::sleep(wait),
+                Reservation::Denied(wait) => std::thread::sleep(wait.max(Duration::from_millis(1))),
+            }
+        }
+    }
+
+    /// Prepares a request with the authorization, idempotency and correlation headers of the call.
+    fn request_builder(&self, method: Method, link: &str, options: &CallOptions) -> RequestBuilder {
+        let mut req = self.client.request(method, link);
+        if let Some((name, value)) = self.auth_scheme.header(&self.api_key) {
+            req = match auth::sensitive(name, value) {
+                Ok(headers) => req.headers(headers),
+                Err(value) => req.header(name, value),
+            };
+        }
+        if let Some(query) = self.auth_scheme.query(&self.api_key) {
+            req = req.query(&[query]);
+        }
+        if let Some(key) = &options.idempotency_key {
+            req = req.header("Idempotency-Key", key);
+        }
+        if let Some(id) = &options.correlation_id {
+            req = req.header("X-Correlation-ID", id);
+        }
+        req
+    }
+
+    /// Sends an authenticated request to `link`, retrying it according to the configured `RetryPolicy`.
+    ///
+    /// The request is rebuilt for every attempt, so bodies that can only be sent once (such as multipart forms) are recreated.
+    /// Every attempt goes through the configured `CircuitBreaker`, if any.
+    ///
+    /// # Parameters
+    ///
+    /// - `method`: The HTTP method of the request.
+    /// - `link`: The URL link to send the request to.
+    /// - `options`: The per-call options, such as the idempotency key, applied to every attempt.
+    /// - `build`: A closure adding the body and any extra headers to the request.
+    ///
+    /// # Returns
+    ///
+    /// The HTTP response and the time the last attempt took to receive its headers. Without a retry policy,
+    /// unsuccessful responses are returned as-is; with one, the error of the last failed attempt is returned once the
+    /// failure is not retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if the request could not be sent.
+    fn execute<F>(
+        &self,
+        method: Method,
+        link: &str,
+        options: &CallOptions,
+        build: F,
+    ) -> Result<(Response, Duration), GroqError>
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let policy = self.retry_policy.as_deref();
+        let call_started = Instant::now();
+        let mut attempt = 1;
+        loop {
+            let permit = self
+                .circuit_breaker
+                .as_ref()
+                .map(|breaker| breaker.check(link))
+                .transpose()?;
+            let mut req = self.request_builder(method.clone(), link, options);
+            if let Some(timeout) = attempt_timeout(self.timeout, self.deadline, call_started) {
+                req = req.timeout(timeout);
+            }
+            let request = build(req).build()?;
+            self.hooks.request(
+                request.method(),
+                &self.auth_scheme.redact_url(request.url()),
+                request.headers(),
+                request.body().and_then(|body| body.as_bytes()),
+                attempt,
+                options.correlation_id.as_deref(),
+            );
+            let started = Instant::now();
+            let span = self.telemetry.attempt(link);
+            #[cfg(feature = "vcr")]
+            let result = match &self.cassette {
+                Some(cassette) => {
+                    let url = self.auth_scheme.redact_url(request.url()).to_string();
+                    cassette.execute(&self.client, request, url)?
+                }
+                None => self.client.execute(request),
+            };
+            #[cfg(not(feature = "vcr"))]
+            let result = self.client.execute(request);
+            span.finish(
+                result.as_ref().ok().map(|res| res.status().as_u16()),
+                started.elapsed(),
+            );
+            if let Ok(res) = &result {
+                self.hooks.response(
+                    link,
+                    attempt,
+                    started.elapsed(),
+                    res.status(),
+                    res.headers(),
+                    options.correlation_id.as_deref(),
+                );
+            }
+            if let Some(permit) = permit {
+                let failed = match &result {
+                    Ok(res) => res.status().is_server_error(),
+                    Err(err) => err.is_timeout() || err.is_connect(),
+                };
+                permit.record(failed);
+            }
+            let error = match result {
+                Ok(res) if res.status().is_success() || policy.is_none() => {
+                    return Ok((res, started.elapsed()))
+                }
+                Ok(res) => {
+                    let status = res.status();
+                    let headers = res.headers().clone();
+                    let body = res.text().unwrap_or_default();
+                    GroqError::from_body(status, &headers, &body)
+                }
+                Err(err) => {
+                    GroqError::from_transport(self.auth_scheme.redact_error(err), started.elapsed())
+                }
+            };
+            let error = self.hooks.redact_error(error);
+            self.telemetry.record_error(link, &error);
+            let delay = policy.and_then(|policy| policy.decide(attempt, &error));
+            let error = past_deadline(self.deadline, call_started, attempt, delay, error)?;
+            match delay {
+                Some(delay) => {
+                    telemetry::retrying(attempt, delay, &error);
+                    self.hooks.retry(
+                        link,
+                        attempt,
+                        delay,
+                        &error,
+                        options.correlation_id.as_deref(),
+                    );
+                    std::thread::sleep(delay)
+                }
+                None => return Err(error),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends a request to the Groq API with the provided JSON body and returns the parsed response.
+    ///
+    /// # Parameters
+    ///
+    /// - `body`: The JSON body to send in the request.
+    /// - `link`: The URL link to send the request to.
+    /// - `options`: The per-call options applied to every attempt.
+    ///
+    /// # Returns
+    ///
+    /// The parsed response from the Groq API as a `Value`, along with its `ResponseMeta`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if there is an issue sending the request or parsing the response.
+    fn send_request(
+        &self,
+        body: impl Serialize,
+        link: &str,
+        options: &CallOptions,
+    ) -> Result<(Value, ResponseMeta), GroqError> {
+        let (res, time_to_first_byte) = self.execute(Method::POST, link, options, |req| {
+            req.header("Content-Type", "application/json").json(&body)
+        })?;
+
+        parse_response(res, time_to_first_byte)
+    }
+
+    #[cfg(feature = "audio")]
+    /// Sends a speech-to-text request to the Groq API and returns the parsed response.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: A `SpeechToTextRequest` containing the necessary parameters for the speech-to-text request.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `SpeechToTextResponse` from the Groq API.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if there is an issue sending the request or parsing the response.
+    pub fn speech_to_text(
+        &self,
+        request: SpeechToTextRequest,
+    ) -> Result<SpeechToTextResponse, GroqError> {
+        let (response, _) = self.speech_to_text_with_meta(request)?;
+        Ok(response)
+    }
+
+    #[cfg(feature = "audio")]
+    /// Sends a speech-to-text request to the Groq API and returns the parsed response along with its HTTP metadata.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: A `SpeechToTextRequest` containing the necessary parameters for the speech-to-text request.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `SpeechToTextResponse` from the Groq API and the `ResponseMeta` holding its headers, request ID and rate limit state.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if there is an issue sending the request or parsing the response.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "groq.speech_to_text",
+            skip_all,
+            fields(
+                endpoint = %self.endpoint,
+                model = request.model.as_deref().unwrap_or_default(),
+                correlation_id = request.correlation_id.as_deref().unwrap_or_default(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub fn speech_to_text_with_meta(
+        &self,
+        request: SpeechToTextRequest,
+    ) -> Result<(SpeechToTextResponse, ResponseMeta), GroqError> {
+        request.validate()?;
+        let request = self.hooks.scrub_speech(request);
+        let started = Instant::now();
+        let model = request.model.clone().unwrap_or_default();
+        let correlation_id = request.correlation_id.clone();
+        let audit = self
+            .audit
+            .as_ref()
+            .map(|_| (request.audit_context.clone(), speech_request_hash(&request)));
+        let endpoint = if request.english_text {
+            "/audio/translations"
+        } else {
+            "/audio/transcriptions"
+        };
+        let result = self
+            .send_speech_to_text(request)
+            .map(|(response, meta)| (response, meta.with_total(started.elapsed(), None)))
+            .map_err(|err| {
+                self.hooks
+                    .redact_error(err)
+                    .with_correlation_id(correlation_id.as_deref())
+            });
+        if let Some(log) = &self.usage_log {
+            let record = UsageRecord::new(endpoint, &model, started.elapsed());
+            log.write(&match &result {
+                Ok((_, meta)) => record.success(meta, None),
+                Err(err) => record.failure(err),
+            });
+        }
+        if let (Some(sink), Some((context, hash))) = (&self.audit, audit) {
+            sink.record(&AuditEntry::new(
+                context.as_deref(),
+                endpoint,
+                &model,
+                hash,
+                &result,
+            ));
+        }
+        result
+    }
+
+    #[cfg(feature = "audio")]
+    /// Sends a speech-to-text request and returns the parsed response along with its HTTP metadata.
+    fn send_speech_to_text(
+        &self,
+        request: SpeechToTextRequest,
+    ) -> Result<(SpeechToTextResponse, ResponseMeta), GroqError> {
+        let link_addition = if request.english_text {
+            "/audio/translations"
+        } else {
+            "/audio/transcriptions"
+        };
+
+        self.wait_for_capacity(
+            request.model.as_deref().unwrap_or_default(),
+            0,
+            request.priority,
+        );
+        let link = format!("{}{}", self.endpoint, link_addition);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let (response, time_to_first_byte) =
+            self.execute(Method::POST, &link, &options, |req| {
+                req.multipart(speech_form::<Form>(&request))
+            })?;
+
+        let (body, meta) = read_response(response, time_to_first_byte)?;
+        let format = request.response_format.unwrap_or_default();
+        Ok((format.parse(&body)?, meta))
+    }
+
+    /// Sends a text-to-speech request to the GROQ API and returns the synthesized audio.
+    ///
+    /// # Parameters
+    ///
+    /// - `request` - A `TextToSpeechRequest` containing the model, text, voice, and audio format.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if there is an issue sending the request or reading the audio.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "groq.text_to_speech",
+            skip_all,
+            fields(
+                endpoint = %self.endpoint,
+                model = %request.model,
+                correlation_id = request.correlation_id.as_deref().unwrap_or_default(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            )
+        )
+    )]
+    pub fn text_to_speech(&self, request: TextToSpeechRequest) -> Result<Vec<u8>, GroqError> {
+        request.validate()?;
+        let started = Instant::now();
+        let audit = self
+            .audit
+            .as_ref()
+            .map(|_| (request.audit_context.clone(), tts_request_hash(&request)));
+        let result = self
+            .send_text_to_speech(&request)
+            .map(|(audio, meta)| (audio, meta.with_total(started.elapsed(), None)))
+            .map_err(|err| {
+                self.hooks
+                    .redact_error(err)
+                    .with_correlation_id(request.correlation_id.as_deref())
+            });
+        if result.is_ok() {
+            self.usage.record(&request.model, None);
+        }
+        if let Some(log) = &self.usage_log {
+            let record = UsageRecord::new("/audio/speech", &request.model, started.elapsed());
+            log.write(&match &result {
+                Ok((_, meta)) => record.success(meta, None),
+                Err(err) => record.failure(err),
+            });
+        }
+        if let (Some(sink), Some((context, hash))) = (&self.audit, audit) {
+            sink.record(&AuditEntry::new(
+                context.as_deref(),
+                "/audio/speech",
+                &request.model,
+                hash,
+                &result,
+            ));
+        }
+        result.map(|(audio, _)| audio)
+    }
+
+    /// Sends a text-to-speech request and returns the audio along with its HTTP metadata.
+    fn send_text_to_speech(
+        &self,
+        request: &TextToSpeechRequest,
+    ) -> Result<(Vec<u8>, ResponseMeta), GroqError> {
+        let body = request.to_json();
+        self.wait_for_capacity(&request.model, 0, request.priority);
+        let link = format!("{}/audio/speech", self.endpoint);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let (response, time_to_first_byte) =
+            self.execute(Method::POST, &link, &options, |req| {
+                req.header("Content-Type", "application/json").json(&body)
+            })?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        if !status.is_success() {
+            let text = response.text().unwrap_or_default();
+            return Err(GroqError::from_body(status, &headers, &text));
+        }
+        let audio = response
+            .bytes()
+            .map_err(|err| GroqError::from(err).with_response(status, &headers))?;
+        let mut meta = ResponseMeta::from_response(status, &headers);
+        meta.timing.time_to_first_byte = time_to_first_byte;
+        Ok((audio.to_vec(), meta))
+    }
+
+    /// Checks that the API is reachable and accepts the API key, with a cheap authenticated request listing the models.
+    ///
+    /// Meant for startup checks: an invalid key fails with `GroqError::Unauthorized`, an unreachable host with
+    /// `GroqError::RequestFailed` or `GroqError::Timeout`, an unavailable API with `GroqError::ServerError`, and an
+    /// endpoint that is not an OpenAI-compatible API with `GroqError::NotFound` or `GroqError::JsonParseError`.
+    ///
+    /// # Returns
+    ///
+    /// The `ResponseMeta` of the check, holding its latency and the rate limit state.
+    pub fn ping(&self) -> Result<ResponseMeta, GroqError> {
+        let started = Instant::now();
+        let link = format!("{}/models", self.endpoint);
+        let (response, time_to_first_byte) =
+            self.execute(Method::GET, &link, &CallOptions::default(), |req| req)?;
+        let (_, meta) = parse_response(response, time_to_first_byte)?;
+        Ok(meta.with_total(started.elapsed(), None))
+    }
+
+    /// Lists the models currently served by the API, with their context windows and output limits.
+    ///
+    /// Merge the listing into a `ModelRegistry` with `update` to refresh the built-in capabilities.
+    ///
+    /// # Returns
+    ///
+    /// The listed models, with default capabilities besides the ones reported by the API.
+    pub fn list_models(&self) -> Result<Vec<ModelInfo>, GroqError> {
+        let link = format!("{}/models", self.endpoint);
+        let (response, time_to_first_byte) =
+            self.execute(Method::GET, &link, &CallOptions::default(), |req| req)?;
+        let (body, _) = parse_response(response, time_to_first_byte)?;
+        Ok(models::parse_listing(&body))
+    }
+
+    /// Builds a chat completion request exactly as `chat_completion` would send it, without sending it.
+    ///
+    /// Meant for asserting request construction in unit tests and for debugging parameters. No network I/O is done,
+    /// and budgets, rate limits and hooks are not involved. The `Authorization` header is redacted, and message
+    /// content is replaced by placeholders when `redact_content` is enabled.
+    ///
+    /// # Parameters
+    ///
+    /// - `request`: The `ChatCompletionRequest` to build.
+    ///
+    /// # Returns
+    ///
+    /// The `DryRun` holding the method, URL, headers and JSON body of the request.
+    pub fn dry_run(&self, request: &ChatCompletionRequest) -> Result<DryRun, GroqError> {
+        let request = &self.hooks.scrub_chat(request.clone());
+        let link = format!("{}/chat/completions", self.endpoint);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let body = chat_completion_body(request, &request.model);
+        let built = self
+            .request_builder(Method::POST, &link, &options)
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .build()?;
+        Ok(self.hooks.dry_run(
+            built.method(),
+            &self.auth_scheme.redact_url(built.url()),
+            built.headers(),
+            body.to_value(),
+        ))
+    }
+
+    /// Sends a chat completion request to the GROQ API and returns the response.
+    ///
+    /// # Parameters
+    ///
+    /// - `request` - A `ChatCompletionRequest` containing the details of the chat completion request.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if there is an issue sending the request or parsing the response.
+    pub fn chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, GroqError> {
+        let (response, _) = self.chat_completion_with_meta(request)?;
+        Ok(response)
+    }
+
+    #[cfg(feature = "stream")]
+    /// Sends a chat completion request and returns the answer as a stream of chunks, read as they are generated.
+    ///
+    /// The budget is settled, and the call recorded by the usage tracker, the usage log and the audit sink, once the
+    /// stream ends, from the usage reported by its last chunk. Response caches, fallback models and continuations only
+    /// apply to `chat_completion`.
+    ///
+    /// # Parameters
+    ///
+    /// - `request` - A `ChatCompletionRequest` containing the details of the chat completion request.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if the request fails before the stream starts; errors while streaming are yielded by it.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "groq.chat_completion_stream",
+            skip_all,
+            fields(
+                endpoint = %self.endpoint,
+                model = %request.model,
+                correlation_id = request.correlation_id.as_deref().unwrap_or_default(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                prompt_tokens = tracing::field::Empty,
+                completion_tokens = tracing::field::Empty,
+                total_tokens = tracing::field::Empty,
+            )
+        )
+    )]
+    pub fn chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionStream, GroqError> {
+        request.validate()?;
+        let request = self.hooks.scrub_chat(request);
+        let mut bookkeeping = self.stream_bookkeeping(&request)?;
+        self.wait_for_capacity(
+            &request.model,
+            estimate_request_tokens(&request),
+            request.priority,
+        );
+        let link = format!("{}/chat/completions", self.endpoint);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let mut body = chat_completion_body(&request, &request.model);
+        body.stream = true;
+        let result = self
+            .execute(Method::POST, &link, &options, |req| {
+                req.header("Content-Type", "application/json").json(&body)
+            })
+            .and_then(|(response, time_to_first_byte)| {
+                let status = response.status();
+                if !status.is_success() {
+                    let headers = response.headers().clone();
+                    let text = response.text().unwrap_or_default();
+                    return Err(GroqError::from_body(status, &headers, &text));
+                }
+                Ok((response, time_to_first_byte))
+            })
+            .map_err(|err| {
+                self.hooks
+                    .redact_error(err)
+                    .with_correlation_id(request.correlation_id.as_deref())
+            });
+        match result {
+            Ok((response, time_to_first_byte)) => {
+                bookkeeping.open(time_to_first_byte);
+                Ok(ChatCompletionStream::new(response, bookkeeping))
+            }
+            Err(err) => {
+                bookkeeping.finish(Err(&err), false);
+                Err(err)
+            }
+        }
+    }
+
+    #[cfg(feature = "stream")]
+    /// Starts the bookkeeping of a streamed chat completion, reserving its tokens against the budget.
+    fn stream_bookkeeping(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<StreamBookkeeping, GroqError> {
+        let audit = self.audit.as_ref().map(|sink| {
+            let hash = chat_request_hash(request);
+            (sink.clone(), request.audit_context.clone(), hash)
+        });
+        let mut bookkeeping = StreamBookkeeping::new(
+            request.model.clone(),
+            Instant::now(),
+            self.usage.clone(),
+            self.usage_log.clone(),
+            audit,
+            self.telemetry.clone(),
+            telemetry::StreamSpan::current(),
+        );
+        let reservation = self
+            .budget
+            .as_ref()
+            .map(|budget| budget.reserve(request, &self.usage.pricing))
+            .transpose();
+        match reservation {
+            Ok(reservation) => {
+                bookkeeping.reservation = reservation;
+                Ok(bookkeeping)
+            }
+            Err(err) => {
+                bookkeeping.finish(Err(&err), false);
+                Err(err)
+            }
+        }
+    }
+
+    /// Sends a chat completion request constrained to the JSON schema of `T`, and deserializes the answer into `T`.
+    ///
+    /// # Parameters
+    ///
+    /// - `request` - The `ChatCompletionRequest` to send, whose response format is replaced by `ResponseFormat::json_schema_for::<T>()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if there is an issue sending the request, or if the answer does not deserialize into `T`.
+    pub fn chat_completion_json<T: ToolParameter + DeserializeOwned>(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<T, GroqError> {
+        let request = request.response_format(ResponseFormat::json_schema_for::<T>());
+        self.chat_completion(request)?.parse_json()
+    }
+
+    /// Sends a chat completion request to the GROQ API and returns the response along with its HTTP metadata.
+    ///
+    /// # Parameters
+    ///
+    /// - `request` - A `ChatCompletionRequest` containing the details of the chat completion request.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `ChatCompletionResponse` and the `ResponseMeta` holding its headers, request ID and rate limit state.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError` if there is an issue sending the request or parsing the response.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "groq.chat_completion",
+            skip_all,
+            fields(
+                endpoint = %self.endpoint,
+                model = %request.model,
+                correlation_id = request.correlation_id.as_deref().unwrap_or_default(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                prompt_tokens = tracing::field::Empty,
+                completion_tokens = tracing::field::Empty,
+                total_tokens = tracing::field::Empty,
+            )
+        )
+    )]
+    pub fn chat_completion_with_meta(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<(ChatCompletionResponse, ResponseMeta), GroqError> {
+        request.validate()?;
+        let request = self.hooks.scrub_chat(request);
+        let started = Instant::now();
+        let model = request.model.clone();
+        let correlation_id = request.correlation_id.clone();
+        let audit = self
+            .audit
+            .as_ref()
+            .map(|_| (request.audit_context.clone(), chat_request_hash(&request)));
+        let result = self
+            .send_with_continuations(request)
+            .map(|(response, meta)| {
+                let meta = meta.with_total(started.elapsed(), response.usage.as_ref());
+                (response, meta)
+            })
+            .map_err(|err| {
+                self.hooks
+                    .redact_error(err)
+                    .with_correlation_id(correlation_id.as_deref())
+            });
+        if let Some(log) = &self.usage_log {
+            let record = UsageRecord::new("/chat/completions", &model, started.elapsed());
+            log.write(&match &result {
+                Ok((response, meta)) => record.success(meta, response.usage.as_ref()),
+                Err(err) => record.failure(err),
+            });
+        }
+        if let (Some(sink), Some((context, hash))) = (&self.audit, audit) {
+            sink.record(&AuditEntry::new(
+                context.as_deref(),
+                "/chat/completions",
+                &model,
+                hash,
+                &result,
+            ));
+        }
+        result
+    }
+
+    /// Sends a chat completion request, continuing the answer as long as it is cut by the token limit and the request
+    /// allows more continuations.
+    fn send_with_continuations(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<(ChatCompletionResponse, ResponseMeta), GroqError> {
+        let base = (request.max_continuations > 0).then(|| request.clone());
+        let (mut response, mut meta) = self.send_chat_completion(request)?;
+        if let Some(base) = base {
+            for attempt in 1..=base.max_continuations {
+                let Some(next) = continuation_request(&base, &response, attempt) else {
+                    break;
+                };
+                let (continuation, continuation_meta) = self.send_chat_completion(next)?;
+                merge_continuation(&mut response, continuation);
+                meta = continuation_meta;
+            }
+        }
+        Ok((response, meta))
+    }
+
+    /// Sends a chat completion request, trying the fallback models if the requested one is decommissioned.
+    fn send_chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<(ChatCompletionResponse, ResponseMeta), GroqError> {
+        let cache_lookup = self.response_cache.as_ref().and_then(|cache| {
+            cache.lookup(
+                &request,
+                &chat_completion_body(&request, &request.model).to_value(),
+            )
+        });
+        if let Some(response) = cache_lookup.as_ref().and_then(CacheLookup::response) {
+            return Ok((response, ResponseMeta::cached()));
+        }
+        let estimated_tokens = estimate_request_tokens(&request);
+        let reservation = self
+            .budget
+            .as_ref()
+            .map(|budget| budget.reserve(&request, &self.usage.pricing))
+            .transpose()?;
+        self.wait_for_capacity(&request.model, estimated_tokens, request.priority);
+
+        let link = format!("{}/chat/completions", self.endpoint);
+        let options = self.call_options(
+            request.idempotency_key.as_deref(),
+            request.correlation_id.as_deref(),
+        );
+        let mut models = chat_completion_models(&request).peekable();
+        loop {
+            let Some(model) = models.next() else {
+                unreachable!("the requested model is always tried");
+            };
+            let body = chat_completion_body(&request, model);
+            match self.send_request(body, &link, &options.for_model(&request, model)) {
+                Err(err) if is_model_decommissioned(&err) && models.peek().is_some() => {
+                    log::warn!("Model {model} is decommissioned, falling back to the next model");
+                }
+                result => {
+                    let (response, meta) = result?;
+                    let chat_completion_response: ChatCompletionResponse = deserialize(response)?;
+                    self.telemetry.record_usage(
+                        &chat_completion_response.model,
+                        chat_completion_response.usage.as_ref(),
+                    );
+                    self.usage.record(
+                        &chat_completion_response.model,
+                        chat_completion_response.usage.as_ref(),
+                    );
+                    if let Some(reservation) = reservation {
+                        reservation.settle(
+                            &chat_completion_response.model,
+                            chat_completion_response.usage.as_ref(),
+                        );
+                    }
+                    if let (Some(cache), Some(lookup)) = (&self.response_cache, cache_lookup) {
+                        cache.store(lookup, &chat_completion_response);
+                    }
+                    return Ok((chat_completion_response, meta));
+                }
+            }
+        }
+    }
+}
+
+/// Hashes the body a chat completion request is sent with, for its `AuditEntry`.
+fn chat_request_hash(request: &ChatCompletionRequest) -> String {
+    let body = chat_completion_body(request, &request.model)
+        .to_value()
+        .to_string();
+    audit::request_hash(&[body.as_bytes()])
+}
+
+/// A multipart form of either client, so that both send speech-to-text requests with the same fields.
+#[cfg(feature = "audio")]
+trait MultipartForm {
+    fn with_file(file: Vec<u8>, file_name: &'static str) -> Self;
+    fn with_text(self, name: &'static str, value: String) -> Self;
+}
+
+#[cfg(all(feature = "audio", feature = "blocking"))]
+impl MultipartForm for Form {
+    fn with_file(file: Vec<u8>, file_name: &'static str) -> Self {
+        Form::new().part("file", Part::bytes(file).file_name(file_name))
+    }
+
+    fn with_text(self, name: &'static str, value: String) -> Self {
+        self.text(name, value)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl MultipartForm for AForm {
+    fn with_file(file: Vec<u8>, file_name: &'static str) -> Self {
+        AForm::new().part("file", APart::bytes(file).file_name(file_name))
+    }
+
+    fn with_text(self, name: &'static str, value: String) -> Self {
+        self.text(name, value)
+    }
+}
+
+/// Builds the multipart form a speech-to-text request is sent with, holding the audio file and every set parameter.
+///
+/// The form is rebuilt for every attempt, since a multipart body can only be sent once.
+#[cfg(feature = "audio")]
+fn speech_form<F: MultipartForm>(request: &SpeechToTextRequest) -> F {
+    let fields = [
+        ("model", request.model.clone()),
+        (
+            "temperature",
+            request.temperature.map(|temp| temp.to_string()),
+        ),
+        ("language", request.language.clone()),
+        ("prompt", request.prompt.clone()),
+        (
+            "response_format",
+            request
+                .response_format
+                .map(|format| format.as_str().to_string()),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .fold(
+            F::with_file(request.file.clone(), "audio.wav"),
+            |form, (name, value)| form.with_text(name, value),
+        )
+}
+
+#[cfg(feature = "audio")]
+/// Hashes the audio file and the parameters of a speech-to-text request, for its `AuditEntry`.
+fn speech_request_hash(request: &SpeechToTextRequest) -> String {
+    let temperature = request.temperature.map(|temp| temp.to_string());
+    fn optional(value: Option<&str>) -> &[u8] {
+        value.unwrap_or_default().as_bytes()
+    }
+    audit::request_hash(&[
+        &request.file,
+        optional(request.model.as_deref()),
+        optional(temperature.as_deref()),
+        optional(request.language.as_deref()),
+        optional(request.prompt.as_deref()),
+        optional(request.response_format.map(TranscriptionFormat::as_str)),
+        &[u8::from(request.english_text)],
+    ])
+}
+
+/// Hashes the body a text-to-speech request is sent with, for its `AuditEntry`.
+fn tts_request_hash(request: &TextToSpeechRequest) -> String {
+    audit::request_hash(&[request.to_json().to_string().as_bytes()])
+}
+
+/// The JSON body of a chat completion request, borrowing the messages and tools of the request.
+///
+/// The body is serialized straight into the HTTP request; build a `Value` with `serde_json::to_value` only where one
+/// is needed, such as for cache keys and dry runs.
+#[derive(Serialize)]
+struct ChatCompletionBody<'a> {
+    model: &'a str,
+    messages: &'a [ChatCompletionMessage],
+    temperature: f64,
+    max_tokens: u32,
+    top_p: f64,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(
+        skip_serializing_if = "<[_]>::is_empty",
+        serialize_with = "message::function_tools::serialize"
+    )]
+    tools: &'a [ToolDefinition],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<&'a ResponseFormat>,
+}
+
+impl ChatCompletionBody<'_> {
+    /// Returns the body as a JSON value.
+    fn to_value(&self) -> Value {
+        serde_json::to_value(self).expect("a chat completion body always serializes")
+    }
+}
+
+/// Builds the JSON body of a chat completion request for the given model.
+///
+/// # Parameters
+///
+/// - `request` - The `ChatCompletionRequest` to serialize.
+/// - `model` - The model to request, which is either `request.model` or one of its fallback models.
+///
+/// # Returns
+///
+/// The request body, borrowing from `request`.
+fn chat_completion_body<'a>(
+    request: &'a ChatCompletionRequest,
+    model: &'a str,
+) -> ChatCompletionBody<'a> {
+    ChatCompletionBody {
+        model,
+        messages: &request.messages,
+        temperature: request.temperature.unwrap_or(1.0),
+        max_tokens: request.max_tokens_for(model),
+        top_p: request.top_p.unwrap_or(1.0),
+        stream: request.stream.unwrap_or(false),
+        stop: request.stop.as_deref(),
+        seed: request.seed,
+        tools: &request.tools,
+        response_format: request.response_format.as_ref(),
+    }
+}
+
+/// Returns the request continuing a response cut by the token limit, or `None` if the response finished otherwise.
+///
+/// The partial answer is sent as a trailing assistant message, to the model that served the response. A distinct
+/// idempotency key is derived for every continuation.
+fn continuation_request(
+    base: &ChatCompletionRequest,
+    response: &ChatCompletionResponse,
+    attempt: u32,
+) -> Option<ChatCompletionRequest> {
+    let choice = response.choices.first()?;
+    if choice.finish_reason != "length" {
+        return None;
+    }
+    let mut request = base.clone();
+    request.model = response.model.clone();
+    request.max_continuations = 0;
+    request.idempotency_key = base
+        .idempotency_key
+        .as_ref()
+        .map(|key| format!("{key}-continuation-{attempt}"));
+    request.messages.push(ChatCompletionMessage {
+        role: ChatCompletionRoles::Assistant,
+        content: choice.message.content.clone().into(),
+        name: None,
+        tool_calls: None,
+        tool_call_id: None,
+    });
+    Some(request)
+}
+
+/// Appends a continuation to the response it continues, summing their usage.
+fn merge_continuation(response: &mut ChatCompletionResponse, continuation: ChatCompletionResponse) {
+    let (Some(choice), Some(next)) = (
+        response.choices.first_mut(),
+        continuation.choices.into_iter().next(),
+    ) else {
+        return;
+    };
+    choice.message.content.push_str(&next.message.content);
+    choice.finish_reason = next.finish_reason;
+    match (&mut response.usage, continuation.usage) {
+        (Some(usage), Some(next)) => {
+            usage.prompt_tokens += next.prompt_tokens;
+            usage.completion_tokens += next.completion_tokens;
+            usage.total_tokens += next.total_tokens;
+            usage.prompt_time += next.prompt_time;
+            usage.completion_time += next.completion_time;
+            usage.total_time += next.total_time;
+        }
+        (usage, next) => *usage = usage.take().or(next),
+    }
+}
+
+/// Returns the models to try for a chat completion request, in order.
+fn chat_completion_models(request: &ChatCompletionRequest) -> impl Iterator<Item = &str> {
+    std::iter::once(request.model.as_str())
+        .chain(request.fallback_models.iter().map(String::as_str))
+}
+
+/// Returns true if the API rejected the request because its model was retired.
+fn is_model_decommissioned(error: &GroqError) -> bool {
+    error.code() == Some("model_decommissioned")
+}
+
+/// Options applying to every attempt of a single API call.
+///
+/// - `idempotency_key`: The key sent as the `Idempotency-Key` header, if any.
+/// - `correlation_id`: The ID sent as the `X-Correlation-ID` header, if any.
+#[derive(Debug, Clone, Default)]
+struct CallOptions {
+    idempotency_key: Option<String>,
+    correlation_id: Option<String>,
+}
+
+impl CallOptions {
+    /// Returns the options to use when sending the request with one of its fallback models.
+    ///
+    /// A request sent with another model is a different request, so it gets a distinct idempotency key.
+    fn for_model(&self, request: &ChatCompletionRequest, model: &str) -> CallOptions {
+        if model == request.model {
+            return self.clone();
+        }
+        CallOptions {
+            idempotency_key: self
+                .idempotency_key
+                .as_ref()
+                .map(|key| format!("{key}:{model}")),
+            correlation_id: self.correlation_id.clone(),
+        }
+    }
+}
+
+/// Returns the timeout of the next attempt, shortened so that it ends by the deadline of the call.
+fn attempt_timeout(
+    timeout: Option<Duration>,
+    deadline: Option<Duration>,
+    call_started: Instant,
+) -> Option<Duration> {
+    let remaining = deadline.map(|deadline| deadline.saturating_sub(call_started.elapsed()));
+    match (timeout, remaining) {
+        (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+        (timeout, remaining) => timeout.or(remaining),
+    }
+}
+
+/// Turns the error of a failed attempt into `GroqError::DeadlineExceeded` if the deadline of the call was reached,
+/// or if it would be by the time the next attempt starts.
+///
+/// # Arguments
+/// * `deadline` - The deadline of the call, if any.
+/// * `call_started` - When the first attempt started.
+/// * `attempts` - The number of attempts made so far.
+/// * `delay` - The delay before the next attempt, or `None` if the call is not retried.
+/// * `error` - The error of the failed attempt.
+fn past_deadline(
+    deadline: Option<Duration>,
+    call_started: Instant,
+    attempts: u32,
+    delay: Option<Duration>,
+    error: GroqError,
+) -> Result<GroqError, GroqError> {
+    let Some(deadline) = deadline else {
+        return Ok(error);
+    };
+    let elapsed = call_started.elapsed();
+    let exceeded = match delay {
+        Some(delay) => elapsed + delay >= deadline,
+        None => matches!(error, GroqError::Timeout { .. }) && elapsed >= deadline,
+    };
+    if !exceeded {
+        return Ok(error);
+    }
+    Err(GroqError::DeadlineExceeded {
+        elapsed,
+        attempts,
+        last_error: Box::new(error),
+    })
+}
+
+#[cfg(feature = "blocking")]
+/// Parses the response from a GROQ API request and returns the response body as a JSON value.
+///
+/// # Parameters
+///
+/// - `response` - The HTTP response from the GROQ API request.
+/// - `time_to_first_byte` - The time the request took to receive the response headers.
+///
+/// # Errors
+///
+/// Returns a `GroqError` if the response status is not successful or if there is an error parsing the response body.
+///
+/// # Returns
+///
+/// The response body as a JSON value, along with the `ResponseMeta` of the response.
+fn parse_response(
+    response: Response,
+    time_to_first_byte: Duration,
+) -> Result<(Value, ResponseMeta), GroqError> {
+    let (text, meta) = read_response(response, time_to_first_byte)?;
+    let body: Value = serde_json::from_str(&text)?;
+    Ok((body, meta))
+}
+
+#[cfg(feature = "blocking")]
+/// Reads the body of a response from a GROQ API request as text, for responses that are not JSON.
+///
+/// # Parameters
+///
+/// - `response` - The HTTP response from the GROQ API request.
+/// - `time_to_first_byte` - The time the request took to receive the response headers.
+///
+/// # Errors
+///
+/// Returns a `GroqError` if the response status is not successful or if the body cannot be read.
+///
+/// # Returns
+///
+/// The response body, along with the `ResponseMeta` of the response.
+fn read_response(
+    response: Response,
+    time_to_first_byte: Duration,
+) -> Result<(String, ResponseMeta), GroqError> {
+    let status = response.status();
+    let headers = response.headers().clone();
+    let text = response
+        .text()
+        .map_err(|err| GroqError::from(err).with_response(status, &headers))?;
+    if !status.is_success() {
+        return Err(GroqError::from_body(status, &headers, &text));
+    }
+    let mut meta = ResponseMeta::from_response(status, &headers);
+    meta.timing.time_to_first_byte = time_to_first_byte;
+
+    Ok((text, meta))
+}
+
+#[cfg(all(test, feature = "blocking", feature = "audio"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs::File;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Serves the given raw HTTP responses, one per connection, and returns the endpoint along with
+    /// a handle yielding the bodies of the received requests.
+    fn serve(responses: Vec<(u16, String)>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+                bodies.push(String::from_utf8_lossy(&request_body).into_owned());
+
+                let response = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+            bodies
+        });
+        (endpoint, handle)
+    }
+
+    #[test]
+    fn test_non_json_error_body() {
+        let html = "<html><body><h1>502 Bad Gateway</h1></body></html>".to_string();
+        let (endpoint, server) = serve(vec![(502, html)]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+
+        let err = client.chat_completion(request).unwrap_err();
+        let GroqError::ServerError { status, payload } = &err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(*status, 502);
+        assert!(payload.message.contains("502 Bad Gateway"));
+        assert_eq!(
+            payload.body["body"],
+            "<html><body><h1>502 Bad Gateway</h1></body></html>"
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_error_status_without_error_object() {
+        let (endpoint, server) = serve(vec![(400, json!({"detail": "bad request"}).to_string())]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+
+        let err = client.chat_completion(request).unwrap_err();
+        let GroqError::ApiError { status, body, .. } = &err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(*status, 400);
+        assert_eq!(body["detail"], "bad request");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_deadline_covers_all_attempts() {
+        let busy =
+            json!({"error": {"message": "Over capacity", "type": "server_error"}}).to_string();
+        let (endpoint, server) = serve(vec![(503, busy.clone()), (503, busy)]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint))
+            .retry_policy(
+                ExponentialBackoff::new(10)
+                    .base_delay(Duration::from_millis(300))
+                    .jitter(false),
+            )
+            .deadline(Duration::from_millis(500));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+
+        let err = client.chat_completion(request).unwrap_err();
+        let GroqError::DeadlineExceeded {
+            attempts,
+            last_error,
+            elapsed,
+        } = &err
+        else {
+            panic!("unexpected error: {err:?}");
+        };
+        // The second retry would start 900ms after the first attempt, past the deadline.
+        assert_eq!(*attempts, 2);
+        assert!(*elapsed < Duration::from_millis(500));
+        assert_eq!(last_error.status(), Some(503));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_observer_hooks() {
+        use std::sync::Mutex;
+
+        let busy =
+            json!({"error": {"message": "Over capacity", "type": "server_error"}}).to_string();
+        let (endpoint, server) = serve(vec![
+            (503, busy),
+            (200, chat_response_body("llama3-8b-8192")),
+        ]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let (on_request, on_response, on_retry) = (events.clone(), events.clone(), events.clone());
+        let client = GroqClient::new("gsk_secret".to_string(), Some(endpoint))
+            .retry_policy(ExponentialBackoff::new(2).base_delay(Duration::from_millis(1)))
+            .on_request(move |info| {
+                let auth = info
+                    .headers
+                    .iter()
+                    .find(|(name, _)| name == "authorization");
+                assert_eq!(auth.unwrap().1, "Bearer [REDACTED]");
+                assert!(info.body.as_deref().unwrap().contains("llama3-8b-8192"));
+                on_request
+                    .lock()
+                    .unwrap()
+                    .push(format!("request {}", info.attempt));
+            })
+            .on_response(move |info| {
+                let status = info.meta.status;
+                on_response
+                    .lock()
+                    .unwrap()
+                    .push(format!("response {status}"));
+            })
+            .on_retry(move |info| {
+                let status = info.error.status().unwrap();
+                on_retry.lock().unwrap().push(format!("retry {status}"));
+            });
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+
+        client.chat_completion(request).unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "request 1",
+                "response 503",
+                "retry 503",
+                "request 2",
+                "response 200"
+            ]
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_usage_summary() {
+        let (endpoint, server) = serve(vec![
+            (200, chat_response_body("llama3-8b-8192")),
+            (200, chat_response_body("llama3-8b-8192")),
+        ]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        for _ in 0..2 {
+            let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+            client.chat_completion(request).unwrap();
+        }
+
+        let usage = client.usage_summary().models["llama3-8b-8192"];
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.prompt_tokens, 20);
+        assert_eq!(usage.total_tokens(), 24);
+        let expected_cost = (20.0 * 0.05 + 4.0 * 0.08) / 1_000_000.0;
+        assert!((usage.estimated_cost - expected_cost).abs() < 1e-12);
+        assert_eq!(client.reset_usage().total().requests, 2);
+        assert!(client.usage_summary().models.is_empty());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_clients_are_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync + Clone + 'static>() {}
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send_sync::<GroqClient>();
+        assert_send_sync::<AsyncGroqClient>();
+        assert_send_sync::<ChatCompletionRequest>();
+        assert_send_sync::<ChatCompletionResponse>();
+
+        let (endpoint, server) = serve(vec![
+            (200, chat_response_body("llama3-8b-8192")),
+            (200, chat_response_body("llama3-8b-8192")),
+        ]);
+        let client = GroqClient::new("key", Some(endpoint));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let worker = client.clone();
+                assert!(Arc::ptr_eq(&worker.api_key, &client.api_key));
+                std::thread::spawn(move || {
+                    let request =
+                        ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+                    worker.chat_completion(request).unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(client.usage_summary().total().requests, 2);
+        server.join().unwrap();
+
+        let _futures_are_send = |client: AsyncGroqClient, request: ChatCompletionRequest| {
+            assert_send(&client.chat_completion(request.clone()));
+            #[cfg(feature = "stream")]
+            assert_send(&client.chat_completion_stream(request));
+        };
+    }
+
+    #[cfg(feature = "vcr")]
+    #[test]
+    fn test_cassette_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("groq-cassette-{}.json", std::process::id()));
+        let (endpoint, server) = serve(vec![(200, chat_response_body("llama3-8b-8192"))]);
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+
+        let cassette = Cassette::record(&path);
+        let client = GroqClient::new("key".to_string(), Some(endpoint.clone())).cassette(cassette);
+        let recorded = client.chat_completion(request.clone()).unwrap();
+        server.join().unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("Bearer"));
+
+        // The server is gone, so the response can only come from the cassette.
+        let cassette = Cassette::open(&path).unwrap();
+        assert_eq!(cassette.mode(), CassetteMode::Replay);
+        let client = GroqClient::new("key".to_string(), Some(endpoint)).cassette(cassette);
+        let replayed = client.chat_completion(request.clone()).unwrap();
+        assert_eq!(replayed.id, recorded.id);
+        assert_eq!(replayed.choices[0].message.content, "Hello!");
+        assert!(matches!(
+            client.chat_completion(request),
+            Err(GroqError::CassetteMiss { .. })
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_response_timing() {
+        let (endpoint, server) = serve(vec![(200, chat_response_body("llama3-8b-8192"))]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+        let (response, meta) = client.chat_completion_with_meta(request).unwrap();
+
+        assert!(meta.timing.time_to_first_byte > Duration::ZERO);
+        assert!(meta.timing.total >= meta.timing.time_to_first_byte);
+        assert_eq!(meta.timing.completion_tokens, Some(2));
+        assert!(meta.timing.tokens_per_second().unwrap() > 0.0);
+        let tokens_per_second = response.usage.unwrap().tokens_per_second().unwrap();
+        assert!((tokens_per_second - 100.0).abs() < 1e-9);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_ping() {
+        let unauthorized = json!({"error": {"message": "Invalid API Key", "type": "invalid_request_error", "code": "invalid_api_key"}});
+        let (endpoint, server) = serve(vec![
+            (401, unauthorized.to_string()),
+            (200, json!({"object": "list", "data": []}).to_string()),
+        ]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        assert!(matches!(client.ping(), Err(GroqError::Unauthorized(_))));
+        let meta = client.ping().unwrap();
+        assert_eq!(meta.status, 200);
+        assert!(meta.timing.total >= meta.timing.time_to_first_byte);
+        server.join().unwrap();
+
+        let client = GroqClient::new("key".to_string(), Some("http://127.0.0.1:1".to_string()));
+        assert!(matches!(
+            client.ping(),
+            Err(GroqError::RequestFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_transcription_with_meta() {
+        let body = json!({"text": "Hello", "x_groq": {"id": "req_1"}}).to_string();
+        let (endpoint, server) = serve(vec![(200, body)]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        let request = SpeechToTextRequest::new(vec![0; 16]).model("whisper-large-v3");
+        let (response, meta) = client.speech_to_text_with_meta(request).unwrap();
+
+        assert_eq!(response.text, "Hello");
+        assert_eq!(meta.header("Content-Type"), Some("application/json"));
+        assert!(meta.timing.total > Duration::ZERO);
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transcription_sends_every_field() {
+        let body = json!({"text": "Hello"}).to_string();
+        let (endpoint, server) = serve(vec![(200, body.clone()), (200, body)]);
+        let request = SpeechToTextRequest::new(b"RIFF".to_vec())
+            .model("whisper-large-v3")
+            .temperature(0.5)
+            .language("en")
+            .prompt("Rust")
+            .response_format(TranscriptionFormat::Json);
+
+        let (blocking_endpoint, sent) = (endpoint.clone(), request.clone());
+        tokio::task::spawn_blocking(move || {
+            let client = GroqClient::new("key", Some(blocking_endpoint));
+            client.speech_to_text(sent).unwrap()
+        })
+        .await
+        .unwrap();
+        let client = AsyncGroqClient::new("key", Some(endpoint)).await;
+        client.speech_to_text(request).await.unwrap();
+
+        let bodies = server.join().unwrap();
+        for body in &bodies {
+            for (name, value) in [
+                ("file", "RIFF"),
+                ("model", "whisper-large-v3"),
+                ("temperature", "0.5"),
+                ("language", "en"),
+                ("prompt", "Rust"),
+                ("response_format", "json"),
+            ] {
+                assert!(
+                    body.contains(&format!("name=\"{}\"", name)),
+                    "{} is missing",
+                    name
+                );
+                assert!(body.contains(value));
+            }
+        }
+        let fields = |body: &str| body.lines().filter(|line| line.contains("name=")).count();
+        assert_eq!(fields(&bodies[0]), fields(&bodies[1]));
+    }
+
+    #[test]
+    fn test_transcription_formats() {
+        let srt = "1\n00:00:00,000 --> 00:00:01,000\nHello\n";
+        let verbose = json!({"text": "Hello", "language": "english", "segments": [{"id": 0, "text": "Hello"}]});
+        let (endpoint, server) = serve(vec![(200, srt.to_string()), (200, verbose.to_string())]);
+        let client = GroqClient::new("key", Some(endpoint));
+
+        let request =
+            SpeechToTextRequest::new(vec![0; 16]).response_format(TranscriptionFormat::Srt);
+        assert_eq!(client.speech_to_text(request).unwrap().text, srt);
+        let request =
+            SpeechToTextRequest::new(vec![0; 16]).response_format(TranscriptionFormat::VerboseJson);
+        let response = client.speech_to_text(request).unwrap();
+        assert_eq!(response.text, "Hello");
+        assert_eq!(response.extra["segments"][0]["text"], "Hello");
+
+        let bodies = server.join().unwrap();
+        assert!(bodies[0].contains("name=\"response_format\"\r\n\r\nsrt\r\n"));
+        assert!(bodies[1].contains("\r\n\r\nverbose_json\r\n"));
+
+        let request = SpeechToTextRequest::new(vec![0; 16]);
+        let hashes: Vec<String> = [
+            None,
+            Some(TranscriptionFormat::Text),
+            Some(TranscriptionFormat::Srt),
+        ]
+        .into_iter()
+        .map(|format| {
+            let mut request = request.clone();
+            request.response_format = format;
+            speech_request_hash(&request)
+        })
+        .collect();
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[1], hashes[2]);
+    }
+
+    #[test]
+    fn test_redact_content() {
+        let malformed = json!({"choices": [{"message": {"content": "Jane's secret"}}]}).to_string();
+        let (endpoint, server) = serve(vec![(200, malformed)]);
+        let bodies = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = bodies.clone();
+        let client = GroqClient::new("key".to_string(), Some(endpoint))
+            .redact_content(true)
+            .on_request(move |info| seen.lock().unwrap().push(info.body.clone().unwrap()));
+        let request = ChatCompletionRequest::new(
+            "llama3-8b-8192",
+            vec![ChatCompletionMessage {
+                role: ChatCompletionRoles::User,
+                content: "My name is Jane".into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        );
+
+        let err = client.chat_completion(request).unwrap_err();
+        assert!(!err.to_string().contains("Jane"));
+        assert!(err.to_string().contains("[REDACTED"));
+        let body = &bodies.lock().unwrap()[0];
+        assert!(!body.contains("Jane"));
+        assert!(body.contains("llama3-8b-8192"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_audit_entries() {
+        let (endpoint, server) = serve(vec![
+            (200, chat_response_body("llama3-8b-8192")),
+            (
+                400,
+                json!({"error": {"message": "Bad request"}}).to_string(),
+            ),
+        ]);
+        let entries = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = entries.clone();
+        let client = GroqClient::new("key".to_string(), Some(endpoint))
+            .audit(move |entry: &AuditEntry| recorded.lock().unwrap().push(entry.clone()));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"])
+            .audit_context("user-42");
+        client.chat_completion(request.clone()).unwrap();
+        client.chat_completion(request).unwrap_err();
+        server.join().unwrap();
+
+        let entries = entries.lock().unwrap();
+        assert_eq!(entries[0].context.as_deref(), Some("user-42"));
+        assert_eq!(entries[0].outcome, AuditOutcome::Success);
+        assert_eq!(entries[0].request_id, None);
+        assert!(matches!(entries[1].outcome, AuditOutcome::Failure { .. }));
+        assert_eq!(entries[1].status, Some(400));
+        assert_eq!(entries[0].request_hash, entries[1].request_hash);
+    }
+
+    #[test]
+    fn test_correlation_id() {
+        let (endpoint, server) = serve(vec![(
+            400,
+            json!({"error": {"message": "Bad request"}}).to_string(),
+        )]);
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = seen.clone();
+        let responses = seen.clone();
+        let client = GroqClient::new("key".to_string(), Some(endpoint))
+            .on_request(move |info| {
+                let header = info
+                    .headers
+                    .iter()
+                    .find(|(name, _)| name == "x-correlation-id")
+                    .map(|(_, value)| value.clone());
+                requests.lock().unwrap().push(header);
+            })
+            .on_response(move |info| responses.lock().unwrap().push(info.correlation_id.clone()));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"])
+            .correlation_id("action-7");
+
+        let err = client.chat_completion(request).unwrap_err();
+        server.join().unwrap();
+        assert_eq!(err.correlation_id(), Some("action-7"));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![Some("action-7".to_string()), Some("action-7".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let client = GroqClient::new("key".to_string(), None).redact_content(true);
+        let request = ChatCompletionRequest::new(
+            "llama3-8b-8192",
+            vec![ChatCompletionMessage {
+                role: ChatCompletionRoles::User,
+                content: "My name is Jane".into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        )
+        .max_tokens(64)
+        .correlation_id("action-7");
+
+        let dry_run = client.dry_run(&request).unwrap();
+        assert_eq!(dry_run.method, "POST");
+        assert_eq!(
+            dry_run.url,
+            "https://api.groq.com/openai/v1/chat/completions"
+        );
+        assert_eq!(dry_run.header("Authorization"), Some("Bearer [REDACTED]"));
+        assert_eq!(dry_run.header("X-Correlation-ID"), Some("action-7"));
+        assert_eq!(dry_run.body["model"], "llama3-8b-8192");
+        assert_eq!(dry_run.body["max_tokens"], 64);
+        assert!(!dry_run.body.to_string().contains("Jane"));
+
+        let tool = ToolDefinition::new("get_weather", "Returns the weather", json!({}));
+        let dry_run = client.dry_run(&request.tools(vec![tool])).unwrap();
+        assert_eq!(dry_run.body["tools"][0]["type"], "function");
+        assert_eq!(dry_run.body["tools"][0]["function"]["name"], "get_weather");
+    }
+
+    #[test]
+    fn test_openai_compatible_server() {
+        let body = json!({"id": "chatcmpl-9", "model": "llama3.2",
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}, "finish_reason": null}],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4}});
+        let (endpoint, server) = serve(vec![(200, body.to_string())]);
+        let client = GroqClient::openai_compatible(endpoint, None);
+        let request = ChatCompletionRequest::new("llama3.2", messages![user: "Hello"]);
+        assert_eq!(
+            client.dry_run(&request).unwrap().header("Authorization"),
+            None
+        );
+
+        let response = client.chat_completion(request.clone()).unwrap();
+        assert_eq!(response.to_string(), "Hi");
+        assert_eq!(response.usage.unwrap().total_tokens, 4);
+        server.join().unwrap();
+
+        let gateway = GroqClient::new("key".to_string(), None).auth_scheme(AuthScheme::Header {
+            name: "api-key".to_string(),
+            prefix: None,
+        });
+        let dry_run = gateway.dry_run(&request).unwrap();
+        assert_eq!(dry_run.header("api-key"), Some("[REDACTED]"));
+        assert_eq!(dry_run.header("Authorization"), None);
+
+        let gateway = gateway.auth_scheme(AuthScheme::Header {
+            name: "api-key".to_string(),
+            prefix: Some("Token".to_string()),
+        });
+        let dry_run = gateway.dry_run(&request).unwrap();
+        assert_eq!(dry_run.header("api-key"), Some("Token [REDACTED]"));
     }
 
-    /// Sends a request to the Groq API with the provided JSON body and returns the parsed response.
-    ///
-    /// # Parameters
-    ///
-    /// - `body`: The JSON body to send in the request.
-    /// - `link`: The URL link to send the request to.
-    ///
-    /// # Returns
-    ///
-    /// The parsed JSON response from the Groq API.
-    async fn send_request(&self, body: Value, link: &str) -> Result<Value, GroqError> {
-        let res = self
-            .client
-            .post(link)
-            .header("Content-Type", "application/json")
-            .header("Authorization", &format!("Bearer {}", self.api_key))
-            .json(&body)
-            .send()
-            .await?;
+    #[test]
+    fn test_auth_scheme_applies_to_every_request() {
+        let chat = json!({"id": "chatcmpl-9", "model": "llama3-8b-8192",
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}, "finish_reason": "stop"}]});
+        let (endpoint, server) = serve(vec![
+            (200, chat.to_string()),
+            (200, json!({"text": "Hi"}).to_string()),
+        ]);
+        let urls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = urls.clone();
+        let scheme = AuthScheme::Query {
+            param: "api_key".to_string(),
+        };
+        let client = GroqClient::new("secret".to_string(), Some(endpoint))
+            .auth_scheme(scheme.clone())
+            .on_request(move |info| {
+                assert!(info.headers.iter().all(|(name, _)| name != "authorization"));
+                assert!(info.to_curl().contains("?api_key='\"$GROQ_API_KEY\"''"));
+                seen.lock().unwrap().push(info.url.clone());
+            });
 
-        self.parse_response(res).await
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+        client.chat_completion(request.clone()).unwrap();
+        client
+            .speech_to_text(SpeechToTextRequest::new(vec![0; 4]))
+            .unwrap();
+        server.join().unwrap();
+        let urls = urls.lock().unwrap();
+        assert!(urls[0].ends_with("/chat/completions?api_key=%5BREDACTED%5D"));
+        assert!(urls[1].ends_with("/audio/transcriptions?api_key=%5BREDACTED%5D"));
+
+        let dry_run = client.dry_run(&request).unwrap();
+        assert!(!dry_run.url.contains("secret"));
+        let unreachable =
+            GroqClient::new("secret".to_string(), Some("http://127.0.0.1:1".to_string()))
+                .auth_scheme(scheme);
+        let error = unreachable.chat_completion(request).unwrap_err();
+        assert!(!error.to_string().contains("secret"), "{}", error);
+        assert!(!format!("{:?}", error).contains("secret"));
     }
 
-    /// Sends a speech-to-text request to the Groq API and returns the parsed response.
-    ///
-    /// # Parameters
-    ///
-    /// - `request`: The `SpeechToTextRequest` containing the audio file, temperature, language, and other options.
-    ///
-    /// # Returns
-    ///
-    /// The parsed `SpeechToTextResponse` from the Groq API.
-    pub async fn speech_to_text(
-        &self,
-        request: SpeechToTextRequest,
-    ) -> Result<SpeechToTextResponse, GroqError> {
-        let file = request.file;
-        let temperature = request.temperature;
-        let language = request.language;
-        let english_text = request.english_text;
-        let model = request.model;
+    #[test]
+    fn test_invalid_request_is_not_sent() {
+        let client = GroqClient::new("key".to_string(), Some("http://127.0.0.1:1".to_string()));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", Vec::new());
+        let Err(GroqError::InvalidRequest { reason }) = client.chat_completion(request) else {
+            panic!("a request without messages should fail before it is sent");
+        };
+        assert_eq!(reason, "the request has no messages");
 
-        let mut form = AForm::new().part("file", APart::bytes(file).file_name("audio.wav"));
-        if let Some(temp) = temperature {
-            form = form.text("temperature", temp.to_string());
-        }
-        if let Some(lang) = language {
-            form = form.text("language", lang);
-        }
+        let request = SpeechToTextRequest::new(vec![0; 16]).language("english");
+        let Err(GroqError::InvalidRequest { reason }) = client.speech_to_text(request) else {
+            panic!("a transcription in an unsupported language should fail before it is sent");
+        };
+        assert_eq!(
+            reason,
+            "`english` is not a supported ISO-639-1 language code"
+        );
 
-        let link_addition = if english_text {
-            "/audio/translations"
-        } else {
-            "/audio/transcriptions"
+        let request = TextToSpeechRequest::new("playai-tts", " ", "Fritz-PlayAI");
+        let Err(GroqError::InvalidRequest { reason }) = client.text_to_speech(request) else {
+            panic!("a speech request without text should fail before it is sent");
         };
-        if let Some(mdl) = model {
-            form = form.text("model", mdl);
+        assert_eq!(reason, "the input is empty");
+
+        #[cfg(feature = "stream")]
+        {
+            let request = ChatCompletionRequest::new(" ", messages![user: "Hi"]);
+            assert!(matches!(
+                client.chat_completion_stream(request),
+                Err(GroqError::InvalidRequest { .. })
+            ));
         }
+    }
 
-        let link = format!("{}{}", self.endpoint, link_addition);
-        let response = self
-            .client
-            .post(&link)
-            .header("Authorization", &format!("Bearer {}", self.api_key))
-            .multipart(form)
-            .send()
-            .await?;
+    #[test]
+    fn test_text_to_speech_is_audited_and_tracked() {
+        let (endpoint, server) = serve(vec![
+            (200, "RIFF audio".to_string()),
+            (400, json!({"error": {"message": "Bad voice"}}).to_string()),
+        ]);
+        let entries = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = entries.clone();
+        let client = GroqClient::new("key".to_string(), Some(endpoint))
+            .audit(move |entry: &AuditEntry| recorded.lock().unwrap().push(entry.clone()));
+        let request = TextToSpeechRequest::new("playai-tts", "Hello", "Fritz-PlayAI")
+            .audit_context("user-42");
+        assert_eq!(
+            client.text_to_speech(request.clone()).unwrap(),
+            b"RIFF audio"
+        );
+        client.text_to_speech(request).unwrap_err();
+        server.join().unwrap();
 
-        let speech_to_text_response: SpeechToTextResponse = response.json().await?;
-        Ok(speech_to_text_response)
+        let entries = entries.lock().unwrap();
+        assert_eq!(entries[0].endpoint, "/audio/speech");
+        assert_eq!(entries[0].model, "playai-tts");
+        assert_eq!(entries[0].context.as_deref(), Some("user-42"));
+        assert_eq!(entries[0].outcome, AuditOutcome::Success);
+        assert_eq!(entries[1].status, Some(400));
+        assert_eq!(entries[0].request_hash, entries[1].request_hash);
+        assert_eq!(client.usage_summary().models["playai-tts"].requests, 1);
     }
 
-    /// Sends a chat completion request to the Groq API and returns the parsed response.
-    ///
-    /// # Parameters
-    ///
-    /// - `request`: The `ChatCompletionRequest` containing the model, messages, temperature, max tokens, top-p, and other options.
-    ///
-    /// # Returns
-    ///
-    /// The parsed `ChatCompletionResponse` from the Groq API.
-    pub async fn chat_completion(
-        &self,
-        request: ChatCompletionRequest,
-    ) -> Result<ChatCompletionResponse, GroqError> {
-        let messages = request
-            .messages
-            .iter()
-            .map(|m| {
-                let mut msg_json = json!({
-                    "role": m.role,
-                    "content": m.content,
-                });
-                if let Some(name) = &m.name {
-                    msg_json["name"] = json!(name);
-                }
-                msg_json
-            })
-            .collect::<Vec<Value>>();
-
-        let mut body = json!({
-            "model": request.model,
-            "messages": messages,
-            "temperature": request.temperature.unwrap_or(1.0),
-            "max_tokens": request.max_tokens.unwrap_or(1024),
-            "top_p": request.top_p.unwrap_or(1.0),
-            "stream": request.stream.unwrap_or(false),
+    #[test]
+    fn test_chat_completion_body() {
+        let request = ChatCompletionRequest::new("llama3-70b-8192", messages![user: "Hi"])
+            .stop(vec!["\n".to_string()])
+            .seed(7)
+            .tools(vec![ToolDefinition::new(
+                "now",
+                "The time",
+                json!({"type": "object"}),
+            )]);
+        let body = chat_completion_body(&request, "llama-3.3-70b-versatile");
+        let expected = json!({
+            "model": "llama-3.3-70b-versatile",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "temperature": 1.0,
+            "max_tokens": 1024,
+            "top_p": 1.0,
+            "stream": false,
+            "stop": ["\n"],
+            "seed": 7,
+            "tools": [{"type": "function", "function": {
+                "name": "now", "description": "The time", "parameters": {"type": "object"}
+            }}],
         });
+        assert_eq!(body.to_value(), expected);
+        assert_eq!(
+            serde_json::from_slice::<Value>(&serde_json::to_vec(&body).unwrap()).unwrap(),
+            expected
+        );
+    }
 
-        if let Some(stop) = &request.stop {
-            body["stop"] = json!(stop);
-        }
-        if let Some(seed) = &request.seed {
-            body["seed"] = json!(seed);
-        }
+    #[test]
+    fn test_auto_max_tokens() {
+        let client = GroqClient::new("key".to_string(), None);
+        let request = ChatCompletionRequest::new(
+            Model::Llama3_8b8192,
+            vec![ChatCompletionMessage {
+                role: ChatCompletionRoles::User,
+                content: "a".repeat(400).into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        )
+        .auto_max_tokens(88);
 
-        let response = self
-            .send_request(body, &format!("{}/chat/completions", self.endpoint))
-            .await?;
-        let chat_completion_response: ChatCompletionResponse = serde_json::from_value(response)?;
-        Ok(chat_completion_response)
+        let max_tokens = |request: &ChatCompletionRequest| {
+            client.dry_run(request).unwrap().body["max_tokens"].clone()
+        };
+        assert_eq!(max_tokens(&request), 8000);
+        let mut request = request;
+        request.model = models::LLAMA_3_3_70B_VERSATILE.to_string();
+        assert_eq!(max_tokens(&request), 32768);
+        request.model = "unknown-model".to_string();
+        assert_eq!(max_tokens(&request), 1024);
     }
 
-    /// Parses the response from a Groq API request and returns the response body as a JSON value.
-    ///
-    /// # Parameters
-    ///
-    /// - `response`: The HTTP response from the Groq API request.
-    ///
-    /// # Returns
-    ///
-    /// The parsed JSON value from the response body, or a `GroqError` if the response was not successful.
-    async fn parse_response(&self, response: AResponse) -> Result<Value, GroqError> {
-        let status = response.status();
-        let body: Value = response.json().await?;
+    #[test]
+    fn test_response_cache() {
+        let (endpoint, server) = serve(vec![
+            (200, chat_response_body("llama3-8b-8192")),
+            (200, chat_response_body("llama3-8b-8192")),
+        ]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint))
+            .response_cache(ResponseCache::memory(16, Duration::from_secs(60)));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
 
-        if !status.is_success() {
-            if let Some(error) = body.get("error") {
-                return Err(GroqError::ApiError {
-                    message: error["message"]
-                        .as_str()
-                        .unwrap_or("Unknown error")
-                        .to_string(),
-                    type_: error["type"]
-                        .as_str()
-                        .unwrap_or("unknown_error")
-                        .to_string(),
-                });
-            }
-        }
+        let deterministic = request.clone().temperature(0.0);
+        let (_, meta) = client
+            .chat_completion_with_meta(deterministic.clone())
+            .unwrap();
+        assert!(!meta.cached);
+        let (response, meta) = client.chat_completion_with_meta(deterministic).unwrap();
+        assert!(meta.cached);
+        assert_eq!(response.choices[0].message.content, "Hello!");
 
-        Ok(body)
+        let (_, meta) = client.chat_completion_with_meta(request).unwrap();
+        assert!(!meta.cached);
+        assert_eq!(server.join().unwrap().len(), 2);
     }
-}
 
-/// An client for interacting with the Groq API.
-///
-/// # Parameters
-///
-/// - `api_key`: The API key for authenticating with the Groq API.
-/// - `endpoint`: The URL of the Groq API endpoint. If not provided, it defaults to <https://api.groq.com/openai/v1>.
-///
-/// # Returns
-///
-/// An instance of `GroqClient` configured with the provided API key and endpoint.
-///
-/// # Example
-///
-///```
-/// use groq_client::GroqClient;
-///
-/// let client = GroqClient::new("my_api_key".to_string(), None);
-///```
-pub struct GroqClient {
-    api_key: String,
-    client: Client,
-    endpoint: String,
-}
+    #[test]
+    fn test_continue_on_length() {
+        let mut truncated: Value =
+            serde_json::from_str(&chat_response_body("llama3-8b-8192")).unwrap();
+        truncated["choices"][0]["message"]["content"] = json!("Once upon");
+        truncated["choices"][0]["finish_reason"] = json!("length");
+        let mut rest: Value = serde_json::from_str(&chat_response_body("llama3-8b-8192")).unwrap();
+        rest["choices"][0]["message"]["content"] = json!(" a time.");
+        let (endpoint, server) = serve(vec![(200, truncated.to_string()), (200, rest.to_string())]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Tell a story"])
+            .continue_on_length(3);
 
-impl GroqClient {
-    /// Constructs a new `GroqClient` instance with the provided API key and optional endpoint.
-    ///
-    /// # Parameters
-    ///
-    /// - `api_key`: The API key for authenticating with the Groq API.
-    /// - `endpoint`: The URL of the Groq API endpoint. If not provided, it defaults to <https://api.groq.com/openai/v1>.
-    ///
-    /// # Returns
-    ///
-    /// A new `GroqClient` instance configured with the provided API key and endpoint.
-    pub fn new(api_key: String, endpoint: Option<String>) -> Self {
-        let ep = endpoint.unwrap_or_else(|| String::from("https://api.groq.com/openai/v1"));
-        Self {
-            api_key,
-            client: Client::new(),
-            endpoint: ep,
-        }
+        let response = client.chat_completion(request).unwrap();
+        assert_eq!(response.choices[0].message.content, "Once upon a time.");
+        assert_eq!(response.choices[0].finish_reason, "stop");
+        assert_eq!(response.usage.unwrap().total_tokens, 24);
+        let bodies = server.join().unwrap();
+        let continuation: Value = serde_json::from_str(&bodies[1]).unwrap();
+        assert_eq!(continuation["messages"][1]["role"], "assistant");
+        assert_eq!(continuation["messages"][1]["content"], "Once upon");
     }
 
-    /// Sends a request to the Groq API with the provided JSON body and returns the parsed response.
-    ///
-    /// # Parameters
-    ///
-    /// - `body`: The JSON body to send in the request.
-    /// - `link`: The URL link to send the request to.
-    ///
-    /// # Returns
-    ///
-    /// The parsed response from the Groq API as a `Value`.
-    ///
-    /// # Errors
-    ///
-    /// Returns a `GroqError` if there is an issue sending the request or parsing the response.
-    fn send_request(&self, body: Value, link: &str) -> Result<Value, GroqError> {
-        let res = self
-            .client
-            .post(link)
-            .header("Content-Type", "application/json")
-            .header("Authorization", &format!("Bearer {}", self.api_key))
-            .json(&body)
-            .send()?;
+    #[test]
+    fn test_scrub_content() {
+        let (endpoint, server) = serve(vec![(200, chat_response_body("llama3-8b-8192"))]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint)).scrub_content(mask_pii);
+        let request = ChatCompletionRequest::new(
+            "llama3-8b-8192",
+            vec![ChatCompletionMessage {
+                role: ChatCompletionRoles::User,
+                content: "Reach me at jane@example.com".into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        );
 
-        parse_response(res)
+        assert_eq!(
+            client.dry_run(&request).unwrap().body["messages"][0]["content"],
+            "Reach me at [EMAIL]"
+        );
+        client.chat_completion(request).unwrap();
+        let bodies = server.join().unwrap();
+        assert!(bodies[0].contains("Reach me at [EMAIL]"));
     }
 
-    /// Sends a speech-to-text request to the Groq API and returns the parsed response.
-    ///
-    /// # Parameters
-    ///
-    /// - `request`: A `SpeechToTextRequest` containing the necessary parameters for the speech-to-text request.
-    ///
-    /// # Returns
-    ///
-    /// The parsed `SpeechToTextResponse` from the Groq API.
-    ///
-    /// # Errors
-    ///
-    /// Returns a `GroqError` if there is an issue sending the request or parsing the response.
-    pub fn speech_to_text(
-        &self,
-        request: SpeechToTextRequest,
-    ) -> Result<SpeechToTextResponse, GroqError> {
-        // Extract values from request
-        let file = request.file;
-        let temperature = request.temperature;
-        let language = request.language;
-        let english_text = request.english_text;
-        let model = request.model;
-        let prompt = request.prompt;
-        let mut form = Form::new().part("file", Part::bytes(file).file_name("audio.wav"));
-
-        if let Some(temp) = temperature {
-            form = form.text("temperature", temp.to_string());
-        }
+    #[test]
+    fn test_list_models() {
+        let listing = json!({"object": "list", "data": [
+            {"id": "llama-3.1-8b-instant", "object": "model", "owned_by": "Meta", "active": true, "context_window": 131072},
+            {"id": "whisper-large-v3", "object": "model", "owned_by": "OpenAI", "active": true, "context_window": 448},
+        ]});
+        let (endpoint, server) = serve(vec![(200, listing.to_string())]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
 
-        if let Some(lang) = language {
-            form = form.text("language", lang);
-        }
+        let models = client.list_models().unwrap();
+        server.join().unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[1].modality, Modality::Audio);
+        let registry = ModelRegistry::default().update(models);
+        assert_eq!(registry.filter(|model| model.active).len(), 2);
+        assert!(registry.get("llama-3.1-8b-instant").unwrap().supports_tools);
+    }
 
-        let link_addition = if english_text {
-            "/audio/translations"
-        } else {
-            "/audio/transcriptions"
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_streamed_completion() {
+        let chunk = |content: &str| {
+            json!({"id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 0, "model": "llama3-8b-8192",
+                "choices": [{"index": 0, "delta": {"content": content}, "finish_reason": null}]})
         };
+        let body = format!(
+            "data: {}\n\ndata: {}\n\ndata: [DONE]\n\n",
+            chunk("Hel"),
+            chunk("lo!")
+        );
+        let (endpoint, server) = serve(vec![(200, body)]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
 
-        if let Some(mdl) = model {
-            form = form.text("model", mdl);
-        }
-        if let Some(prompt) = prompt {
-            form = form.text("prompt", prompt.to_string());
-        }
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+        let stream = client.chat_completion_stream(request).unwrap();
+        let content: Vec<String> = stream
+            .map(|chunk| chunk.unwrap().content().to_string())
+            .collect();
+        assert_eq!(content, vec!["Hel", "lo!"]);
+        let bodies = server.join().unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&bodies[0]).unwrap()["stream"],
+            true
+        );
+    }
 
-        let link = format!("{}{}", self.endpoint, link_addition);
-        let response = self
-            .client
-            .post(link)
-            .header("Authorization", &format!("Bearer {}", self.api_key))
-            .multipart(form)
-            .send()?;
+    #[test]
+    fn test_streamed_completion_bookkeeping() {
+        let transcript = include_str!("../fixtures/chat_stream.sse").to_string();
+        let (endpoint, server) = serve(vec![
+            (200, transcript.clone()),
+            (200, transcript),
+            (
+                400,
+                json!({"error": {"message": "Bad request"}}).to_string(),
+            ),
+        ]);
+        let path =
+            std::env::temp_dir().join(format!("groq-stream-usage-{}.jsonl", std::process::id()));
+        let entries = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = entries.clone();
+        let budget = Budget::new(BudgetWindow::Hour).max_tokens(100_000);
+        let client = GroqClient::new("key".to_string(), Some(endpoint))
+            .budget(budget.clone())
+            .usage_log(UsageLog::file(&path, UsageLogFormat::JsonLines).unwrap())
+            .audit(move |entry: &AuditEntry| recorded.lock().unwrap().push(entry.clone()));
+        let request = ChatCompletionRequest::new(
+            "llama-3.3-70b-versatile",
+            messages![user: "What is the capital of France?"],
+        )
+        .audit_context("user-42");
+
+        let stream = client.chat_completion_stream(request.clone()).unwrap();
+        assert_eq!(stream.count(), 9);
+        let usage = client.usage_summary().models["llama-3.3-70b-versatile"];
+        assert_eq!((usage.requests, usage.completion_tokens), (1, 8));
+        assert_eq!(budget.spent().0, 53);
+
+        // A stream dropped before its end keeps the estimate of its tokens.
+        let mut dropped = client.chat_completion_stream(request.clone()).unwrap();
+        dropped.next().unwrap().unwrap();
+        drop(dropped);
+        let estimate = budget.spent().0 - 53;
+        assert!(estimate > 0);
 
-        let speech_to_text_response: SpeechToTextResponse = response.json()?;
-        Ok(speech_to_text_response)
+        assert!(client.chat_completion_stream(request).is_err());
+        assert_eq!(budget.spent().0, 53 + estimate);
+        server.join().unwrap();
+
+        let entries = entries.lock().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].endpoint, "/chat/completions");
+        assert_eq!(entries[0].context.as_deref(), Some("user-42"));
+        assert_eq!(entries[0].outcome, AuditOutcome::Success);
+        assert_eq!(entries[1].outcome, AuditOutcome::Success);
+        assert_eq!(entries[2].status, Some(400));
+        assert_eq!(
+            client.usage_summary().models["llama-3.3-70b-versatile"].requests,
+            2
+        );
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let records: Vec<Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["prompt_tokens"], 45);
+        assert_eq!(records[0]["completion_tokens"], 8);
+        assert_eq!(records[1]["completion_tokens"], Value::Null);
+        assert_eq!(records[2]["status"], 400);
     }
 
-    /// Sends a chat completion request to the GROQ API and returns the response.
-    ///
-    /// # Parameters
-    ///
-    /// - `request` - A `ChatCompletionRequest` containing the details of the chat completion request.
-    ///
-    /// # Errors
-    ///
-    /// Returns a `GroqError` if there is an issue sending the request or parsing the response.
-    pub fn chat_completion(
-        &self,
-        request: ChatCompletionRequest,
-    ) -> Result<ChatCompletionResponse, GroqError> {
-        let messages = request
-            .messages
-            .iter()
-            .map(|m| {
-                let mut msg_json = json!({
-                    "role": m.role,
-                    "content": m.content,
-                });
-                if let Some(name) = &m.name {
-                    msg_json["name"] = json!(name);
-                }
-                msg_json
-            })
-            .collect::<Vec<_>>();
-
-        let mut body = json!({
-            "model": request.model,
-            "messages": messages,
-            "temperature": request.temperature.unwrap_or(1.0),
-            "max_tokens": request.max_tokens.unwrap_or(1024),
-            "top_p": request.top_p.unwrap_or(1.0),
-            "stream": request.stream.unwrap_or(false),
-        });
+    #[cfg(feature = "sse")]
+    #[tokio::test]
+    async fn test_stream_relays_server_sent_events() {
+        use futures_util::StreamExt;
 
-        if let Some(stop) = &request.stop {
-            body["stop"] = json!(stop);
-        }
-        if let Some(seed) = &request.seed {
-            body["seed"] = json!(seed);
+        let chunk = json!({"id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 0, "model": "llama3-8b-8192",
+            "choices": [{"index": 0, "delta": {"content": "Hi"}, "finish_reason": null}]});
+        let error = json!({"error": {"message": "overloaded", "type": "server_error"}});
+        let (endpoint, server) = serve(vec![
+            (200, format!("data: {}\n\ndata: [DONE]\n\n", chunk)),
+            (200, format!("data: {}\n\ndata: {}\n\n", chunk, error)),
+        ]);
+        let client = AsyncGroqClient::new("key".to_string(), Some(endpoint)).await;
+
+        let mut relayed = Vec::new();
+        for _ in 0..2 {
+            let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+            let stream = client.chat_completion_stream(request).await.unwrap();
+            let events: Vec<String> = stream
+                .into_sse()
+                .map(|event| event.unwrap())
+                .collect()
+                .await;
+            relayed.push(events.concat());
         }
+        assert_eq!(relayed[0], format!("data: {}\n\ndata: [DONE]\n\n", chunk));
+        assert_eq!(
+            relayed[1],
+            format!("data: {}\n\nevent: error\ndata: {}\n\n", chunk, error)
+        );
+        server.join().unwrap();
+    }
 
-        let response = self.send_request(body, &format!("{}/chat/completions", self.endpoint))?;
-        let chat_completion_response: ChatCompletionResponse = serde_json::from_value(response)?;
-        Ok(chat_completion_response)
+    #[tokio::test]
+    async fn test_text_to_speech_returns_audio() {
+        let (endpoint, server) = serve(vec![(200, "RIFF audio".to_string())]);
+        let client = AsyncGroqClient::new("key".to_string(), Some(endpoint)).await;
+
+        let request =
+            TextToSpeechRequest::new("playai-tts", "Hello", "Fritz-PlayAI").response_format("wav");
+        let audio = client.text_to_speech(request).await.unwrap();
+        assert_eq!(audio, b"RIFF audio");
+        let bodies = server.join().unwrap();
+        let body: Value = serde_json::from_str(&bodies[0]).unwrap();
+        assert_eq!(
+            body,
+            json!({"model": "playai-tts", "input": "Hello", "voice": "Fritz-PlayAI", "response_format": "wav"})
+        );
     }
-}
 
-/// Parses the response from a GROQ API request and returns the response body as a JSON value.
-///
-/// # Parameters
-///
-/// - `response` - The HTTP response from the GROQ API request.
-///
-/// # Errors
-///
-/// Returns a `GroqError` if the response status is not successful or if there is an error parsing the response body.
-///
-/// # Returns
-///
-/// The response body as a JSON value.
-fn parse_response(response: Response) -> Result<Value, GroqError> {
-    let status = response.status();
-    let body: Value = response.json()?;
+    #[test]
+    fn test_structured_output() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct City {
+            name: String,
+            population: u64,
+        }
 
-    if !status.is_success() {
-        if let Some(error) = body.get("error") {
-            return Err(GroqError::ApiError {
-                message: error["message"]
-                    .as_str()
-                    .unwrap_or("Unknown error")
-                    .to_string(),
-                type_: error["type"]
-                    .as_str()
-                    .unwrap_or("unknown_error")
-                    .to_string(),
-            });
+        impl ToolParameter for City {
+            fn schema() -> Value {
+                json!({
+                    "type": "object",
+                    "properties": {"name": String::schema(), "population": u64::schema()},
+                    "required": ["name", "population"],
+                })
+            }
         }
-    }
 
-    Ok(body)
-}
+        let mut body: Value = serde_json::from_str(&chat_response_body("llama3-8b-8192")).unwrap();
+        body["choices"][0]["message"]["content"] =
+            json!(r#"{"name": "Paris", "population": 2100000}"#);
+        let (endpoint, server) = serve(vec![(200, body.to_string())]);
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        let request = ChatCompletionRequest::new(
+            "llama3-8b-8192",
+            vec![ChatCompletionMessage {
+                role: ChatCompletionRoles::User,
+                content: "What is the capital of France?".into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-    use std::io::Read;
-    use tokio;
+        let city: City = client.chat_completion_json(request).unwrap();
+        assert_eq!(
+            city,
+            City {
+                name: "Paris".to_string(),
+                population: 2100000
+            }
+        );
+        let bodies = server.join().unwrap();
+        let sent: Value = serde_json::from_str(&bodies[0]).unwrap();
+        assert_eq!(sent["response_format"]["type"], "json_schema");
+        assert_eq!(sent["response_format"]["json_schema"]["name"], "City");
+        assert_eq!(
+            sent["response_format"]["json_schema"]["schema"],
+            City::schema()
+        );
+    }
+
+    fn chat_response_body(model: &str) -> String {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1718000000,
+            "model": model,
+            "system_fingerprint": "fp_1",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hello!"},
+                "logprobs": null,
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 10, "prompt_time": 0.01,
+                "completion_tokens": 2, "completion_time": 0.02,
+                "total_tokens": 12, "total_time": 0.03
+            },
+            "x_groq": {"id": "req_1"}
+        })
+        .to_string()
+    }
 
     #[test]
     fn test_chat_completion() {
@@ -426,8 +4052,10 @@ mod tests {
         let client = GroqClient::new(api_key.to_string(), None);
         let messages = vec![ChatCompletionMessage {
             role: ChatCompletionRoles::User,
-            content: "Hello".to_string(),
+            content: "Hello".into(),
             name: None,
+            tool_calls: None,
+            tool_call_id: None,
         }];
         let request = ChatCompletionRequest::new("llama3-70b-8192", messages);
         let response = client.chat_completion(request).unwrap();
@@ -462,15 +4090,19 @@ mod tests {
 
         let messages1 = vec![ChatCompletionMessage {
             role: ChatCompletionRoles::User,
-            content: "Hello".to_string(),
+            content: "Hello".into(),
             name: None,
+            tool_calls: None,
+            tool_call_id: None,
         }];
         let request1 = ChatCompletionRequest::new("llama3-70b-8192", messages1);
 
         let messages2 = vec![ChatCompletionMessage {
             role: ChatCompletionRoles::User,
-            content: "How are you?".to_string(),
+            content: "How are you?".into(),
             name: None,
+            tool_calls: None,
+            tool_call_id: None,
         }];
         let request2 = ChatCompletionRequest::new("llama3-70b-8192", messages2);
 
@@ -529,4 +4161,49 @@ mod tests {
         assert!(!response1.text.is_empty());
         assert!(!response2.text.is_empty());
     }
+
+    #[test]
+    fn test_timeout_error() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (_stream, _) = listener.accept().unwrap();
+            std::thread::sleep(Duration::from_millis(300));
+        });
+
+        let client =
+            GroqClient::new("key".to_string(), Some(endpoint)).timeout(Duration::from_millis(50));
+        let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hello"]);
+        match client.chat_completion(request) {
+            Err(GroqError::Timeout { phase, elapsed, .. }) => {
+                assert_eq!(phase, TimeoutPhase::Read);
+                assert!(elapsed >= Duration::from_millis(50));
+            }
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_fallback_on_decommissioned_model() {
+        let decommissioned = json!({"error": {
+            "message": "The model `llama3-70b-8192` has been decommissioned",
+            "type": "invalid_request_error",
+            "code": "model_decommissioned"
+        }});
+        let (endpoint, server) = serve(vec![
+            (400, decommissioned.to_string()),
+            (200, chat_response_body("llama-3.3-70b-versatile")),
+        ]);
+
+        let client = GroqClient::new("key".to_string(), Some(endpoint));
+        let request = ChatCompletionRequest::new("llama3-70b-8192", messages![user: "Hello"])
+            .fallback_models(["llama-3.3-70b-versatile"]);
+        let response = client.chat_completion(request).unwrap();
+        assert_eq!(response.model, "llama-3.3-70b-versatile");
+
+        let bodies = server.join().unwrap();
+        assert!(bodies[0].contains("\"model\":\"llama3-70b-8192\""));
+        assert!(bodies[1].contains("\"model\":\"llama-3.3-70b-versatile\""));
+    }
 }
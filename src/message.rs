@@ -1,47 +1,816 @@
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use crate::builder::{ChatCompletionRequestBuilder, Missing};
+use crate::tokens::count_tokens;
+use crate::{BudgetWindow, ModelRegistry, RequestPriority, ToolParameter};
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 #[derive(Error, Debug)]
 /// Represents errors that can occur when interacting with the GROQ API.
 ///
-/// - `RequestFailed`: Indicates a failure in the underlying HTTP request.
+/// - `RequestFailed`: Indicates a failure in the underlying HTTP request, with the context known at the time of failure.
 /// - `JsonParseError`: Indicates a failure in parsing the JSON response from the API.
-/// - `ApiError`: Indicates an error returned by the API, with a message and error type.
+/// - `ReadFailed`: Reading the body of a streamed response failed, with the context of the response.
+/// - `ApiError`: Indicates an error returned by the API, with a message, error type, error code, HTTP status, request ID, correlation ID and the full response body.
+/// - `Unauthorized`: The API rejected the API key (401).
+/// - `Forbidden`: The API key is not allowed to perform the request (403).
+/// - `NotFound`: The requested resource or model does not exist (404).
+/// - `UnprocessableEntity`: The request was well-formed but contained invalid parameters (422).
+/// - `RateLimited`: The rate limit was exceeded (429), with the wait time requested by the API if any.
+/// - `ServerError`: The API failed to process the request (5xx).
+/// - `CircuitOpen`: The request was not sent because the circuit breaker of the endpoint is open.
+/// - `Timeout`: The request timed out, with the time spent and the phase it timed out in.
+/// - `DeserializationError`: The response was valid JSON but did not match the expected type, with a truncated copy of it.
+/// - `DeadlineExceeded`: The deadline of the call was reached, with the time spent, the number of attempts and the last error.
+/// - `BudgetExceeded`: The request was not sent because it would exceed the configured `Budget`, with what was already spent.
+/// - `CassetteMiss`: The request was not sent because the replayed `Cassette` has no matching interaction left.
+/// - `AgentLimitExceeded`: An `Agent` reached its maximum number of iterations or its timeout without a final answer.
+/// - `UnexpectedAnswer`: The model answered successfully, but not in the format the caller expected, with the answer.
+/// - `InvalidRequest`: The request was not sent because the API would reject it, such as a request without messages.
 pub enum GroqError {
-    #[error("API request failed: {0}")]
-    RequestFailed(#[from] reqwest::Error),
+    #[error("API request failed: {source}")]
+    RequestFailed {
+        source: reqwest::Error,
+        context: RequestContext,
+    },
     #[error("Failed to parse JSON: {0}")]
     JsonParseError(#[from] serde_json::Error),
-    #[error("API error: {message}")]
-    ApiError { message: String, type_: String },
+    #[error("Failed to read the response: {source}")]
+    ReadFailed {
+        source: std::io::Error,
+        context: RequestContext,
+    },
+    #[error("API error ({status}): {message}")]
+    ApiError {
+        message: String,
+        type_: String,
+        code: Option<String>,
+        status: u16,
+        request_id: Option<String>,
+        correlation_id: Option<String>,
+        body: Box<Value>,
+    },
+    #[error("Unauthorized: {}", .0.message)]
+    Unauthorized(ApiErrorPayload),
+    #[error("Forbidden: {}", .0.message)]
+    Forbidden(ApiErrorPayload),
+    #[error("Not found: {}", .0.message)]
+    NotFound(ApiErrorPayload),
+    #[error("Unprocessable entity: {}", .0.message)]
+    UnprocessableEntity(ApiErrorPayload),
+    #[error("Rate limited: {}", .payload.message)]
+    RateLimited {
+        payload: ApiErrorPayload,
+        retry_after: Option<Duration>,
+    },
+    #[error("Server error ({status}): {}", .payload.message)]
+    ServerError {
+        status: u16,
+        payload: ApiErrorPayload,
+    },
+    #[error("Circuit open for {endpoint}, retry in {retry_in:?}")]
+    CircuitOpen {
+        endpoint: String,
+        retry_in: Duration,
+    },
+    #[error("Request timed out while {phase} after {elapsed:?}")]
+    Timeout {
+        elapsed: Duration,
+        phase: TimeoutPhase,
+        context: RequestContext,
+    },
+    #[error("Failed to deserialize response: {source}; payload: {payload}")]
+    DeserializationError {
+        source: serde_json::Error,
+        payload: String,
+    },
+    #[error("Deadline exceeded after {attempts} attempts in {elapsed:?}: {last_error}")]
+    DeadlineExceeded {
+        elapsed: Duration,
+        attempts: u32,
+        last_error: Box<GroqError>,
+    },
+    #[error("Budget exceeded: {spent_tokens} tokens and an estimated cost of {spent_cost:.4} spent in the last {window}")]
+    BudgetExceeded {
+        window: BudgetWindow,
+        spent_tokens: u64,
+        spent_cost: f64,
+    },
+    #[error("No recorded interaction left for {method} {url}")]
+    CassetteMiss { method: String, url: String },
+    #[error("Agent stopped without a final answer after {iterations} iterations in {elapsed:?}")]
+    AgentLimitExceeded { iterations: u32, elapsed: Duration },
+    #[error("Unexpected answer, expected {expected}: {answer}")]
+    UnexpectedAnswer { expected: String, answer: String },
+    #[error("Invalid request: {reason}")]
+    InvalidRequest { reason: String },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+/// Represents the phase of a request in which a timeout occurred.
+///
+/// - `Connect`: The connection to the API could not be established in time.
+/// - `Read`: The connection was established but the response did not arrive in time.
+pub enum TimeoutPhase {
+    Connect,
+    Read,
+}
+
+impl std::fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeoutPhase::Connect => write!(f, "connecting"),
+            TimeoutPhase::Read => write!(f, "waiting for the response"),
+        }
+    }
+}
+
+impl GroqError {
+    /// Returns the HTTP status code associated with the error, if any.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            GroqError::RequestFailed { context, .. } | GroqError::ReadFailed { context, .. } => {
+                context.status
+            }
+            GroqError::Unauthorized(_) => Some(401),
+            GroqError::Forbidden(_) => Some(403),
+            GroqError::NotFound(_) => Some(404),
+            GroqError::UnprocessableEntity(_) => Some(422),
+            GroqError::RateLimited { .. } => Some(429),
+            GroqError::ServerError { status, .. } => Some(*status),
+            GroqError::ApiError { status, .. } => Some(*status),
+            GroqError::JsonParseError(_)
+            | GroqError::CircuitOpen { .. }
+            | GroqError::Timeout { .. }
+            | GroqError::DeserializationError { .. }
+            | GroqError::DeadlineExceeded { .. }
+            | GroqError::BudgetExceeded { .. }
+            | GroqError::CassetteMiss { .. }
+            | GroqError::AgentLimitExceeded { .. }
+            | GroqError::UnexpectedAnswer { .. }
+            | GroqError::InvalidRequest { .. } => None,
+        }
+    }
+
+    /// Returns true if the request exceeded a rate limit.
+    pub fn is_rate_limit(&self) -> bool {
+        matches!(self, GroqError::RateLimited { .. })
+    }
+
+    /// Returns true if the API key was rejected or is not allowed to perform the request.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(self, GroqError::Unauthorized(_) | GroqError::Forbidden(_))
+    }
+
+    /// Returns true if sending the same request again may succeed.
+    ///
+    /// This is the case for rate limits, server errors, connection failures and timeouts.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            GroqError::RequestFailed { source, .. } => source.is_connect() || source.is_timeout(),
+            GroqError::RateLimited { .. }
+            | GroqError::ServerError { .. }
+            | GroqError::Timeout { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the `x-request-id` of the failed response, if any.
+    ///
+    /// Include it when reporting issues to Groq support.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            GroqError::RequestFailed { context, .. } | GroqError::ReadFailed { context, .. } => {
+                context.request_id.as_deref()
+            }
+            GroqError::ApiError { request_id, .. } => request_id.as_deref(),
+            GroqError::Unauthorized(payload)
+            | GroqError::Forbidden(payload)
+            | GroqError::NotFound(payload)
+            | GroqError::UnprocessableEntity(payload)
+            | GroqError::RateLimited { payload, .. }
+            | GroqError::ServerError { payload, .. } => payload.request_id.as_deref(),
+            GroqError::JsonParseError(_)
+            | GroqError::CircuitOpen { .. }
+            | GroqError::Timeout { .. }
+            | GroqError::DeserializationError { .. }
+            | GroqError::BudgetExceeded { .. }
+            | GroqError::CassetteMiss { .. }
+            | GroqError::AgentLimitExceeded { .. }
+            | GroqError::UnexpectedAnswer { .. }
+            | GroqError::InvalidRequest { .. } => None,
+            GroqError::DeadlineExceeded { last_error, .. } => last_error.request_id(),
+        }
+    }
+
+    /// Returns the correlation ID of the request that failed, if one was set with `correlation_id`.
+    pub fn correlation_id(&self) -> Option<&str> {
+        match self {
+            GroqError::ApiError { correlation_id, .. } => correlation_id.as_deref(),
+            GroqError::RequestFailed { context, .. }
+            | GroqError::ReadFailed { context, .. }
+            | GroqError::Timeout { context, .. } => context.correlation_id.as_deref(),
+            GroqError::Unauthorized(payload)
+            | GroqError::Forbidden(payload)
+            | GroqError::NotFound(payload)
+            | GroqError::UnprocessableEntity(payload)
+            | GroqError::RateLimited { payload, .. }
+            | GroqError::ServerError { payload, .. } => payload.correlation_id.as_deref(),
+            GroqError::DeadlineExceeded { last_error, .. } => last_error.correlation_id(),
+            _ => None,
+        }
+    }
+
+    /// Attaches the correlation ID of the call to the error, if the error describes a request.
+    ///
+    /// # Arguments
+    /// * `id` - The correlation ID of the call, if any.
+    pub(crate) fn with_correlation_id(mut self, id: Option<&str>) -> Self {
+        if id.is_some() {
+            self.set_correlation_id(id);
+        }
+        self
+    }
+
+    fn set_correlation_id(&mut self, id: Option<&str>) {
+        let id = id.map(|id| id.to_string());
+        match self {
+            GroqError::ApiError { correlation_id, .. } => *correlation_id = id,
+            GroqError::RequestFailed { context, .. }
+            | GroqError::ReadFailed { context, .. }
+            | GroqError::Timeout { context, .. } => context.correlation_id = id,
+            GroqError::Unauthorized(payload)
+            | GroqError::Forbidden(payload)
+            | GroqError::NotFound(payload)
+            | GroqError::UnprocessableEntity(payload)
+            | GroqError::RateLimited { payload, .. }
+            | GroqError::ServerError { payload, .. } => payload.correlation_id = id,
+            GroqError::DeadlineExceeded { last_error, .. } => {
+                last_error.set_correlation_id(id.as_deref())
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the error code reported by the API, such as `json_validate_failed`, if any.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            GroqError::ApiError { code, .. } => code.as_deref(),
+            GroqError::Unauthorized(payload)
+            | GroqError::Forbidden(payload)
+            | GroqError::NotFound(payload)
+            | GroqError::UnprocessableEntity(payload)
+            | GroqError::RateLimited { payload, .. }
+            | GroqError::ServerError { payload, .. } => payload.code.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Adds the status and request ID of the response being read to a transport error.
+    ///
+    /// # Arguments
+    /// * `status` - The HTTP status of the response.
+    /// * `headers` - The headers of the response.
+    pub(crate) fn with_response(mut self, status: StatusCode, headers: &HeaderMap) -> Self {
+        if let GroqError::RequestFailed { context, .. } | GroqError::ReadFailed { context, .. } =
+            &mut self
+        {
+            context.status = Some(status.as_u16());
+            context.request_id = request_id(headers);
+        }
+        self
+    }
+
+    /// Returns the wait time requested by the API before retrying, if any.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            GroqError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Builds the error matching the HTTP status of a failed response from its raw body.
+    ///
+    /// Bodies that are not JSON, such as the HTML page of a proxy, are kept as a truncated string and quoted in the message.
+    ///
+    /// # Arguments
+    /// * `status` - The HTTP status of the response.
+    /// * `headers` - The headers of the response.
+    /// * `body` - The raw response body.
+    pub(crate) fn from_body(status: StatusCode, headers: &HeaderMap, body: &str) -> Self {
+        if let Ok(body) = serde_json::from_str(body) {
+            return Self::from_response(status, headers, body);
+        }
+        let snippet = snippet(body.trim(), 512);
+        let message = if snippet.is_empty() {
+            format!("HTTP {status}")
+        } else {
+            format!("HTTP {status}: {snippet}")
+        };
+        let body = json!({ "error": { "message": message }, "body": snippet });
+        Self::from_response(status, headers, body)
+    }
+
+    /// Builds the error matching the HTTP status of a failed response.
+    ///
+    /// # Arguments
+    /// * `status` - The HTTP status of the response.
+    /// * `headers` - The headers of the response, used to read the request ID and the requested retry delay.
+    /// * `body` - The response body, which usually holds an `error` object.
+    pub(crate) fn from_response(status: StatusCode, headers: &HeaderMap, body: Value) -> Self {
+        let error = &body["error"];
+        let payload = ApiErrorPayload {
+            message: error["message"]
+                .as_str()
+                .map_or_else(|| format!("HTTP {status}"), |message| message.to_string()),
+            type_: error["type"]
+                .as_str()
+                .unwrap_or("unknown_error")
+                .to_string(),
+            code: error["code"].as_str().map(|code| code.to_string()),
+            request_id: request_id(headers),
+            correlation_id: None,
+            body: Box::new(body),
+        };
+
+        match status {
+            StatusCode::UNAUTHORIZED => GroqError::Unauthorized(payload),
+            StatusCode::FORBIDDEN => GroqError::Forbidden(payload),
+            StatusCode::NOT_FOUND => GroqError::NotFound(payload),
+            StatusCode::UNPROCESSABLE_ENTITY => GroqError::UnprocessableEntity(payload),
+            StatusCode::TOO_MANY_REQUESTS => GroqError::RateLimited {
+                retry_after: crate::retry::retry_after_header(headers)
+                    .or_else(|| crate::retry::retry_after_message(&payload.message)),
+                payload,
+            },
+            status if status.is_server_error() => GroqError::ServerError {
+                status: status.as_u16(),
+                payload,
+            },
+            _ => GroqError::ApiError {
+                message: payload.message,
+                type_: payload.type_,
+                code: payload.code,
+                status: status.as_u16(),
+                request_id: payload.request_id,
+                correlation_id: payload.correlation_id,
+                body: payload.body,
+            },
+        }
+    }
+}
+
+impl GroqError {
+    /// Converts an error raised while sending a request, telling timeouts apart from other transport failures.
+    ///
+    /// # Arguments
+    /// * `source` - The error raised by the HTTP client.
+    /// * `elapsed` - The time spent on the request before it failed.
+    pub(crate) fn from_transport(source: reqwest::Error, elapsed: Duration) -> Self {
+        if !source.is_timeout() {
+            return GroqError::from(source);
+        }
+        let phase = if source.is_connect() {
+            TimeoutPhase::Connect
+        } else {
+            TimeoutPhase::Read
+        };
+        let context = RequestContext {
+            url: source.url().map(|url| url.to_string()),
+            status: None,
+            request_id: None,
+            correlation_id: None,
+        };
+        GroqError::Timeout {
+            elapsed,
+            phase,
+            context,
+        }
+    }
+}
+
+impl From<reqwest::Error> for GroqError {
+    fn from(source: reqwest::Error) -> Self {
+        let context = RequestContext {
+            url: source.url().map(|url| url.to_string()),
+            status: source.status().map(|status| status.as_u16()),
+            request_id: None,
+            correlation_id: None,
+        };
+        GroqError::RequestFailed { source, context }
+    }
+}
+
+/// Deserializes a response body, keeping a truncated copy of it in the error if it doesn't match the expected type.
+pub(crate) fn deserialize<T: DeserializeOwned>(body: Value) -> Result<T, GroqError> {
+    T::deserialize(&body).map_err(|source| GroqError::DeserializationError {
+        source,
+        payload: snippet(&body.to_string(), 1024),
+    })
+}
+
+/// Truncates `text` to at most `max_chars` characters, marking the cut with an ellipsis.
+pub(crate) fn snippet(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Reads the `x-request-id` header of a response.
+pub(crate) fn request_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-request-id")?
+        .to_str()
+        .ok()
+        .map(|id| id.to_string())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Represents what was known about a request when its transport failed.
+///
+/// - `url`: The URL of the request.
+/// - `status`: The HTTP status, if a response was received before the failure.
+/// - `request_id`: The `x-request-id` of the response, if a response was received before the failure.
+/// - `correlation_id`: The correlation ID of the request, if one was set.
+pub struct RequestContext {
+    pub url: Option<String>,
+    pub status: Option<u16>,
+    pub request_id: Option<String>,
+    pub correlation_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Represents the error object returned by the API alongside a failed response.
+///
+/// - `message`: A human-readable description of the error.
+/// - `type_`: The type of the error, such as `invalid_request_error`.
+/// - `code`: The error code, such as `json_validate_failed` or `model_decommissioned`, if any.
+/// - `request_id`: The `x-request-id` of the response, if any.
+/// - `correlation_id`: The correlation ID of the request, if one was set.
+/// - `body`: The full response body, including structured details such as `failed_generation`.
+pub struct ApiErrorPayload {
+    pub message: String,
+    pub type_: String,
+    pub code: Option<String>,
+    pub request_id: Option<String>,
+    pub correlation_id: Option<String>,
+    pub body: Box<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 /// Represents the different roles that can be used in a chat completion message.
 ///
 /// - `System`: Indicates a message from the system.
 /// - `User`: Indicates a message from the user.
 /// - `Assistant`: Indicates a message from the assistant.
+/// - `Tool`: Indicates the result of a tool call, answering the assistant message that requested it.
 pub enum ChatCompletionRoles {
     System,
     User,
     Assistant,
+    Tool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 /// Represents a message in a chat completion response.
 ///
 /// - `role`: The role of the message, such as `System`, `User`, or `Assistant`.
-/// - `content`: The content of the message.
+/// - `content`: The content of the message, shared by the clones of the message.
 /// - `name`: An optional name associated with the message.
+/// - `tool_calls`: The tools an assistant message asked to call, if any.
+/// - `tool_call_id`: The ID of the tool call a `Tool` message answers.
 pub struct ChatCompletionMessage {
     pub role: ChatCompletionRoles,
-    pub content: String,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub content: MessageContent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Represents the text of a `ChatCompletionMessage`, stored once and shared by its clones.
+///
+/// Requests are cloned for retries, fallback models, caches and conversation histories, so a large prompt is only
+/// copied when it is turned into a `MessageContent`: a `String` or a `&str` is copied once into the shared buffer, and
+/// only an `Arc<str>` converts without copying. The content reads as a `&str`:
+///
+/// ```
+/// use groq_api_rust::MessageContent;
+/// use std::sync::Arc;
+///
+/// let corpus: Arc<str> = Arc::from("A very long document...");
+/// let content = MessageContent::from(corpus.clone());
+/// assert!(content.starts_with("A very long"));
+/// assert!(Arc::ptr_eq(content.as_arc(), &corpus));
+/// ```
+pub struct MessageContent(Arc<str>);
+
+impl MessageContent {
+    /// Returns the content as a `&str`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the shared content, to reuse it in other messages without copying it.
+    pub fn as_arc(&self) -> &Arc<str> {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for MessageContent {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for MessageContent {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::borrow::Borrow<str> for MessageContent {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for MessageContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Arc<str>> for MessageContent {
+    fn from(content: Arc<str>) -> Self {
+        MessageContent(content)
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(content: String) -> Self {
+        MessageContent(content.into())
+    }
+}
+
+impl From<&String> for MessageContent {
+    fn from(content: &String) -> Self {
+        MessageContent(content.as_str().into())
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(content: &str) -> Self {
+        MessageContent(content.into())
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl From<Cow<'_, str>> for MessageContent {
+    fn from(content: Cow<'_, str>) -> Self {
+        MessageContent(content.into())
+    }
+}
+
+impl From<MessageContent> for String {
+    fn from(content: MessageContent) -> Self {
+        content.0.to_string()
+    }
+}
+
+impl PartialEq<str> for MessageContent {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for MessageContent {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for MessageContent {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl Serialize for MessageContent {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageContent {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(String::deserialize(deserializer)?.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Represents a tool the model may call, described by a JSON schema of its parameters.
+///
+/// - `name`: The name of the tool, as used in tool calls.
+/// - `description`: What the tool does, helping the model decide when to call it.
+/// - `parameters`: The JSON schema of the arguments of the tool.
+pub struct ToolDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Value,
+}
+
+/// Serializes tools in the `{"type": "function", "function": {...}}` form of the `tools` field of the request body.
+pub(crate) mod function_tools {
+    use super::ToolDefinition;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    struct FunctionTool {
+        function: ToolDefinition,
+    }
+
+    #[derive(Serialize)]
+    struct FunctionToolRef<'a> {
+        #[serde(rename = "type")]
+        type_: &'static str,
+        function: &'a ToolDefinition,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        tools: &[ToolDefinition],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(tools.iter().map(|function| FunctionToolRef {
+            type_: "function",
+            function,
+        }))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<ToolDefinition>, D::Error> {
+        let tools = Vec::<FunctionTool>::deserialize(deserializer)?;
+        Ok(tools.into_iter().map(|tool| tool.function).collect())
+    }
+}
+
+impl ToolDefinition {
+    /// Creates a new `ToolDefinition`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the tool.
+    /// * `description` - What the tool does.
+    /// * `parameters` - The JSON schema of the arguments of the tool.
+    pub fn new(name: impl Into<String>, description: impl Into<String>, parameters: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The format the model must answer a chat completion request in.
+///
+/// - `Text`: Free-form text, the default.
+/// - `JsonObject`: Any valid JSON object.
+/// - `JsonSchema`: A JSON value matching `schema`, identified by `name`, and followed exactly when `strict` is set.
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema {
+        name: String,
+        schema: Value,
+        strict: bool,
+    },
+}
+
+impl ResponseFormat {
+    /// Creates a strict `JsonSchema` format.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the schema.
+    /// * `schema` - The JSON schema the answer must match.
+    pub fn json_schema(name: impl Into<String>, schema: Value) -> Self {
+        ResponseFormat::JsonSchema {
+            name: name.into(),
+            schema,
+            strict: true,
+        }
+    }
+
+    /// Creates a strict `JsonSchema` format from the schema of a Rust type, named after the type.
+    ///
+    /// The schema comes from the `ToolParameter` implementation of the type, so it always matches the type the answer
+    /// is deserialized into.
+    pub fn json_schema_for<T: ToolParameter>() -> Self {
+        let name = std::any::type_name::<T>()
+            .split('<')
+            .next()
+            .and_then(|path| path.rsplit("::").next())
+            .unwrap_or("response");
+        Self::json_schema(name, T::schema())
+    }
+
+    /// Returns the `response_format` field of the request body.
+    pub(crate) fn to_json(&self) -> Value {
+        match self {
+            ResponseFormat::Text => json!({"type": "text"}),
+            ResponseFormat::JsonObject => json!({"type": "json_object"}),
+            ResponseFormat::JsonSchema {
+                name,
+                schema,
+                strict,
+            } => json!({
+                "type": "json_schema",
+                "json_schema": {"name": name, "schema": schema, "strict": strict},
+            }),
+        }
+    }
+}
+
+/// Serializes the format as the `response_format` field of the request body.
+impl Serialize for ResponseFormat {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+/// Deserializes the `response_format` field of a request body.
+impl<'de> Deserialize<'de> for ResponseFormat {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct JsonSchema {
+            name: String,
+            #[serde(default)]
+            schema: Value,
+            #[serde(default)]
+            strict: bool,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        enum Wire {
+            Text,
+            JsonObject,
+            JsonSchema { json_schema: JsonSchema },
+        }
+
+        Ok(match Wire::deserialize(deserializer)? {
+            Wire::Text => ResponseFormat::Text,
+            Wire::JsonObject => ResponseFormat::JsonObject,
+            Wire::JsonSchema { json_schema } => ResponseFormat::JsonSchema {
+                name: json_schema.name,
+                schema: json_schema.schema,
+                strict: json_schema.strict,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Represents a tool call requested by the model.
+///
+/// - `id`: The ID of the call, repeated by the `Tool` message holding its result.
+/// - `type_`: The type of the tool, always `function`.
+/// - `function`: The name and arguments of the call.
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Represents the function called by a `ToolCall`.
+///
+/// - `name`: The name of the tool.
+/// - `arguments`: The arguments of the call, as a JSON document generated by the model.
+pub struct FunctionCall {
+    pub name: String,
+    pub arguments: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 /// Represents the response from a chat completion API request.
 ///
 /// - `choices`: A vector of `Choice` objects, each representing a possible response.
@@ -49,45 +818,103 @@ pub struct ChatCompletionMessage {
 /// - `id`: The unique identifier for the response.
 /// - `model`: The name of the model used to generate the response.
 /// - `object`: The type of the response object.
-/// - `system_fingerprint`: A unique identifier for the system that generated the response.
-/// - `usage`: Usage statistics for the request, including token counts and processing times.
-/// - `x_groq`: Additional metadata about the response, including the GROQ API ID.
+/// - `system_fingerprint`: A unique identifier for the system that generated the response, if reported.
+/// - `usage`: Usage statistics for the request, including token counts and processing times, if reported.
+/// - `x_groq`: Additional metadata about the response, including the GROQ API ID, if reported.
+/// - `extra`: Fields of the response not modelled by this type, keyed by name.
+///
+/// The optional fields are always present on Groq responses, but may be missing from other OpenAI-compatible backends,
+/// which may also omit `created` and `object` (read as `0` and an empty string).
 pub struct ChatCompletionResponse {
     pub choices: Vec<Choice>,
+    #[serde(default)]
     pub created: u64,
     pub id: String,
     pub model: String,
+    #[serde(default)]
     pub object: String,
-    pub system_fingerprint: String,
-    pub usage: Usage,
-    pub x_groq: XGroq,
+    pub system_fingerprint: Option<String>,
+    pub usage: Option<Usage>,
+    pub x_groq: Option<XGroq>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl ChatCompletionResponse {
+    /// Deserializes the content of the first choice, as answered to a `JsonObject` or `JsonSchema` response format.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError::DeserializationError` if there is no choice, or if its content is not valid JSON of
+    /// type `T`.
+    pub fn parse_json<T: DeserializeOwned>(&self) -> Result<T, GroqError> {
+        let content = self
+            .choices
+            .first()
+            .map_or("", |choice| choice.message.content.as_str());
+        serde_json::from_str(content).map_err(|source| GroqError::DeserializationError {
+            source,
+            payload: snippet(content, 1024),
+        })
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Displays the content of the first choice, or nothing if there is none.
+impl std::fmt::Display for ChatCompletionResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.choices.first() {
+            Some(choice) => f.write_str(&choice.message.content),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 /// Represents a single choice in a chat completion response.
 ///
-/// - `finish_reason`: The reason the generation finished, such as "stop" or "length".
+/// - `finish_reason`: The reason the generation finished, such as "stop" or "length", empty if not reported.
 /// - `index`: The index of the choice within the list of choices.
 /// - `logprobs`: Optional log probabilities for the tokens in the generated text.
 /// - `message`: The message associated with this choice, containing the role, content, and optional name.
+/// - `extra`: Fields of the response not modelled by this type, keyed by name.
 pub struct Choice {
+    #[serde(default, deserialize_with = "null_as_empty")]
     pub finish_reason: String,
     pub index: u64,
     pub logprobs: Option<Value>,
     pub message: Message,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 /// Represents a message in a chat completion response.
 ///
-/// - `content`: The content of the message.
+/// - `content`: The content of the message, empty when the model only requests tool calls.
 /// - `role`: The role of the message, such as `System`, `User`, or `Assistant`.
+/// - `tool_calls`: The tools the model asked to call, if any.
+/// - `extra`: Fields of the response not modelled by this type, keyed by name.
 pub struct Message {
+    #[serde(default, deserialize_with = "null_as_empty")]
     pub content: String,
     pub role: ChatCompletionRoles,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Deserializes a string that the API may send as `null`.
+fn null_as_empty<'de, D: serde::Deserializer<'de>, T: Deserialize<'de> + Default>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 /// Represents usage statistics for a chat completion request, including token counts and processing times.
 ///
 /// - `completion_time`: The time (in seconds) it took to generate the completion.
@@ -96,21 +923,155 @@ pub struct Message {
 /// - `prompt_tokens`: The number of tokens in the prompt.
 /// - `total_time`: The total time (in seconds) for the entire request.
 /// - `total_tokens`: The total number of tokens used in the request.
+/// - `extra`: Fields of the response not modelled by this type, keyed by name.
+///
+/// The times are reported by Groq only, and are `0.0` on responses of other OpenAI-compatible servers.
 pub struct Usage {
+    #[serde(default)]
     pub completion_time: f64,
     pub completion_tokens: u64,
+    #[serde(default)]
     pub prompt_time: f64,
     pub prompt_tokens: u64,
+    #[serde(default)]
     pub total_time: f64,
     pub total_tokens: u64,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Displays a summary such as `12 tokens (10 prompt + 2 completion) in 0.030s`.
+impl std::fmt::Display for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} tokens ({} prompt + {} completion) in {:.3}s",
+            self.total_tokens, self.prompt_tokens, self.completion_tokens, self.total_time
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 /// Represents a GROQ-related data structure.
 ///
 /// - `id`: The unique identifier for this GROQ-related data.
+/// - `extra`: Fields of the response not modelled by this type, keyed by name.
 pub struct XGroq {
     pub id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the format of a transcription or translation response.
+///
+/// - `Json`: A JSON object holding the transcript.
+/// - `VerboseJson`: A JSON object holding the transcript along with its language, duration, segments and words.
+/// - `Text`: The transcript as plain text.
+/// - `Srt`: The transcript as SubRip subtitles.
+/// - `Vtt`: The transcript as WebVTT subtitles.
+pub enum TranscriptionFormat {
+    #[default]
+    Json,
+    VerboseJson,
+    Text,
+    Srt,
+    Vtt,
+}
+
+impl TranscriptionFormat {
+    /// Returns the value of the `response_format` field sent with the request, such as `verbose_json`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TranscriptionFormat::Json => "json",
+            TranscriptionFormat::VerboseJson => "verbose_json",
+            TranscriptionFormat::Text => "text",
+            TranscriptionFormat::Srt => "srt",
+            TranscriptionFormat::Vtt => "vtt",
+        }
+    }
+
+    #[cfg(feature = "audio")]
+    /// Parses a response body of this format, keeping the whole body as the transcript of the text formats.
+    pub(crate) fn parse(self, body: &str) -> Result<SpeechToTextResponse, GroqError> {
+        match self {
+            TranscriptionFormat::Json | TranscriptionFormat::VerboseJson => {
+                deserialize(serde_json::from_str(body)?)
+            }
+            TranscriptionFormat::Text | TranscriptionFormat::Srt | TranscriptionFormat::Vtt => {
+                Ok(SpeechToTextResponse {
+                    text: body.to_string(),
+                    extra: HashMap::new(),
+                })
+            }
+        }
+    }
+}
+
+/// The ISO-639-1 codes of the languages the Whisper models transcribe, sorted for binary search.
+const LANGUAGE_CODES: [&str; 98] = [
+    "af", "am", "ar", "as", "az", "ba", "be", "bg", "bn", "bo", "br", "bs", "ca", "cs", "cy", "da",
+    "de", "el", "en", "es", "et", "eu", "fa", "fi", "fo", "fr", "gl", "gu", "ha", "he", "hi", "hr",
+    "ht", "hu", "hy", "id", "is", "it", "ja", "jv", "ka", "kk", "km", "kn", "ko", "la", "lb", "ln",
+    "lo", "lt", "lv", "mg", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "ne", "nl", "nn", "no",
+    "oc", "pa", "pl", "ps", "pt", "ro", "ru", "sa", "sd", "si", "sk", "sl", "sn", "so", "sq", "sr",
+    "su", "sv", "sw", "ta", "te", "tg", "th", "tk", "tl", "tr", "tt", "uk", "ur", "uz", "vi", "yi",
+    "yo", "zh",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Represents the ISO-639-1 code of a language the Whisper models transcribe, such as `en`.
+///
+/// `SpeechToTextRequest::language` takes a `Language` or a code as a string; the clients check string codes before
+/// uploading the audio file.
+///
+/// ```
+/// use groq_api_rust::Language;
+///
+/// let language: Language = "fr".parse().unwrap();
+/// assert_eq!(language.code(), "fr");
+/// assert!("french".parse::<Language>().is_err());
+/// ```
+pub struct Language(&'static str);
+
+impl Language {
+    /// Returns the language with the given ISO-639-1 code, or `None` if the code is not supported.
+    ///
+    /// # Arguments
+    /// * `code` - The lowercase ISO-639-1 code, such as `en`.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let index = LANGUAGE_CODES.binary_search(&code).ok()?;
+        Some(Language(LANGUAGE_CODES[index]))
+    }
+
+    /// Returns the ISO-639-1 code of the language.
+    pub fn code(self) -> &'static str {
+        self.0
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = GroqError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Language::from_code(code).ok_or_else(|| GroqError::InvalidRequest {
+            reason: format!("`{}` is not a supported ISO-639-1 language code", code),
+        })
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl From<Language> for String {
+    fn from(language: Language) -> Self {
+        language.0.to_string()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -119,10 +1080,14 @@ pub struct XGroq {
 /// - `file`: The audio file to be transcribed.
 /// - `model`: The speech recognition model to use.
 /// - `temperature`: The temperature parameter to control the randomness of the transcription.
-/// - `language`: The language of the audio file.
+/// - `language`: The ISO-639-1 code of the language of the audio file, checked by `validate`.
 /// - `english_text`: If true, the API will use the translation endpoint instead of the transcription endpoint.
 /// - `prompt`: An optional prompt to provide context for the transcription.
-/// - `response_format`: The desired format of the transcription response, either "text" or "json".
+/// - `response_format`: The format of the transcription response, `TranscriptionFormat::Json` by default.
+/// - `idempotency_key`: An optional key sent as the `Idempotency-Key` header, kept identical across retries.
+/// - `priority`: How urgently the request must be sent when the client has a `RateLimiter`.
+/// - `audit_context`: The context recorded with the call by the client's `AuditSink`, such as the user making it.
+/// - `correlation_id`: An optional ID sent as the `X-Correlation-ID` header and attached to errors, spans and hook events.
 pub struct SpeechToTextRequest {
     pub file: Vec<u8>,
     pub model: Option<String>,
@@ -131,7 +1096,11 @@ pub struct SpeechToTextRequest {
     /// If true, the API will use following path: `/audio/translations` instead of `/audio/transcriptions`
     pub english_text: bool,
     pub prompt: Option<String>,
-    pub response_format: Option<String>,
+    pub response_format: Option<TranscriptionFormat>,
+    pub idempotency_key: Option<String>,
+    pub priority: RequestPriority,
+    pub audit_context: Option<String>,
+    pub correlation_id: Option<String>,
 }
 
 /// Constructs a new `SpeechToTextRequest` with the given audio file.
@@ -151,6 +1120,10 @@ impl SpeechToTextRequest {
             english_text: false,
             prompt: None,
             response_format: None,
+            idempotency_key: None,
+            priority: RequestPriority::Interactive,
+            audit_context: None,
+            correlation_id: None,
         }
     }
 
@@ -169,15 +1142,30 @@ impl SpeechToTextRequest {
     /// Sets the language of the audio file.
     ///
     /// # Arguments
-    /// * `language` - The language of the audio file.
+    /// * `language` - The ISO-639-1 code of the language of the audio file, such as `en`, or a `Language`.
     ///
     /// # Returns
     /// The modified `SpeechToTextRequest` instance with the updated language.
-    pub fn language(mut self, language: &str) -> Self {
-        self.language = Some(language.to_string());
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
         self
     }
 
+    /// Checks that the language of the request, if any, is a supported ISO-639-1 code.
+    ///
+    /// The clients check requests before uploading the audio file, so a wrong code fails without a round trip to the
+    /// API.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError::InvalidRequest` naming the unsupported code.
+    pub fn validate(&self) -> Result<(), GroqError> {
+        match &self.language {
+            Some(language) => language.parse::<Language>().map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
     /// Sets whether the API should use the translation endpoint instead of the transcription endpoint.
     ///
     /// # Arguments
@@ -193,12 +1181,12 @@ impl SpeechToTextRequest {
     /// Sets the speech recognition model to use.
     ///
     /// # Arguments
-    /// * `model` - The speech recognition model to use.
+    /// * `model` - The speech recognition model to use, such as `Model::WhisperLargeV3`.
     ///
     /// # Returns
     /// The modified `SpeechToTextRequest` instance with the updated model.
-    pub fn model(mut self, model: &str) -> Self {
-        self.model = Some(model.to_string());
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
         self
     }
 
@@ -209,31 +1197,251 @@ impl SpeechToTextRequest {
     ///
     /// # Returns
     /// The modified `SpeechToTextRequest` instance with the updated prompt.
-    pub fn prompt(mut self, prompt: &str) -> Self {
-        self.prompt = Some(prompt.to_string());
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into());
         self
     }
 
-    /// Sets the desired format of the transcription response.
+    /// Sets the format of the transcription response.
+    ///
+    /// The transcript of every format is returned as the `text` of the `SpeechToTextResponse`; the segments and words
+    /// of `TranscriptionFormat::VerboseJson` are kept in its `extra` fields.
     ///
     /// # Arguments
-    /// * `response_format` - The desired format of the transcription response, either "text" or "json".
+    /// * `response_format` - The format of the transcription response.
     ///
     /// # Returns
     /// The modified `SpeechToTextRequest` instance with the updated response format.
-    pub fn response_format(mut self, response_format: &str) -> Self {
-        // Currently only "text" and "json" are supported.
-        self.response_format = Some(response_format.to_string());
+    pub fn response_format(mut self, response_format: TranscriptionFormat) -> Self {
+        self.response_format = Some(response_format);
+        self
+    }
+
+    /// Sets the idempotency key sent as the `Idempotency-Key` header.
+    ///
+    /// # Arguments
+    /// * `key` - A unique key identifying this request, reused for every retry of it.
+    ///
+    /// # Returns
+    /// The modified `SpeechToTextRequest` instance with the updated idempotency key.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
         self
     }
+
+    /// Sets how urgently the request must be sent when the client has a `RateLimiter`.
+    ///
+    /// # Arguments
+    /// * `priority` - The priority of the request.
+    ///
+    /// # Returns
+    /// The modified `SpeechToTextRequest` instance with the updated priority.
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the context recorded with this call by the client's `AuditSink`.
+    ///
+    /// # Arguments
+    /// * `context` - A caller-defined description of who makes the call and why, such as a user ID.
+    ///
+    /// # Returns
+    /// The modified `SpeechToTextRequest` instance with the updated audit context.
+    pub fn audit_context(mut self, context: impl Into<String>) -> Self {
+        self.audit_context = Some(context.into());
+        self
+    }
+
+    /// Sets the correlation ID sent as the `X-Correlation-ID` header.
+    ///
+    /// The ID is kept across retries and attached to errors, tracing spans and hook events, so that one user action
+    /// can be followed through the logs.
+    ///
+    /// # Arguments
+    /// * `id` - The correlation ID.
+    ///
+    /// # Returns
+    /// The modified `SpeechToTextRequest` instance with the updated correlation ID.
+    pub fn correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Represents a request to the text-to-speech API.
+///
+/// - `model`: The speech synthesis model to use, such as `playai-tts`.
+/// - `input`: The text to speak.
+/// - `voice`: The voice to speak with, such as `Fritz-PlayAI`.
+/// - `response_format`: The audio format of the response, such as `wav`.
+/// - `speed`: The speed of the speech, 1.0 being normal.
+/// - `idempotency_key`: An optional key sent as the `Idempotency-Key` header, kept identical across retries.
+/// - `priority`: How urgently the request must be sent when the client has a `RateLimiter`.
+/// - `audit_context`: The context recorded with the call by the client's `AuditSink`, such as the user making it.
+/// - `correlation_id`: An optional ID sent as the `X-Correlation-ID` header and attached to errors, spans and hook events.
+pub struct TextToSpeechRequest {
+    pub model: String,
+    pub input: String,
+    pub voice: String,
+    pub response_format: Option<String>,
+    pub speed: Option<f64>,
+    pub idempotency_key: Option<String>,
+    pub priority: RequestPriority,
+    pub audit_context: Option<String>,
+    pub correlation_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl TextToSpeechRequest {
+    /// Constructs a new `TextToSpeechRequest` answered in the default audio format of the API.
+    ///
+    /// # Arguments
+    /// * `model` - The speech synthesis model to use.
+    /// * `input` - The text to speak.
+    /// * `voice` - The voice to speak with.
+    pub fn new(
+        model: impl Into<String>,
+        input: impl Into<String>,
+        voice: impl Into<String>,
+    ) -> Self {
+        TextToSpeechRequest {
+            model: model.into(),
+            input: input.into(),
+            voice: voice.into(),
+            response_format: None,
+            speed: None,
+            idempotency_key: None,
+            priority: RequestPriority::default(),
+            audit_context: None,
+            correlation_id: None,
+        }
+    }
+
+    /// Checks that the request has a model, a voice and text to speak, which the API requires.
+    ///
+    /// The clients check requests before sending them, so invalid requests fail without a round trip to the API.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError::InvalidRequest` naming the missing part.
+    pub fn validate(&self) -> Result<(), GroqError> {
+        let reason = if self.model.trim().is_empty() {
+            "the model is empty"
+        } else if self.voice.trim().is_empty() {
+            "the voice is empty"
+        } else if self.input.trim().is_empty() {
+            "the input is empty"
+        } else {
+            return Ok(());
+        };
+        Err(GroqError::InvalidRequest {
+            reason: reason.to_string(),
+        })
+    }
+
+    /// Sets the audio format of the response.
+    ///
+    /// # Arguments
+    /// * `response_format` - The format, such as `wav`, `mp3` or `flac`.
+    ///
+    /// # Returns
+    /// The modified `TextToSpeechRequest` instance with the updated response format.
+    pub fn response_format(mut self, response_format: impl Into<String>) -> Self {
+        self.response_format = Some(response_format.into());
+        self
+    }
+
+    /// Sets the speed of the speech.
+    ///
+    /// # Arguments
+    /// * `speed` - The speed, 1.0 being normal.
+    ///
+    /// # Returns
+    /// The modified `TextToSpeechRequest` instance with the updated speed.
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Sets the idempotency key sent as the `Idempotency-Key` header.
+    ///
+    /// # Arguments
+    /// * `key` - A unique key identifying this request, reused for every retry of it.
+    ///
+    /// # Returns
+    /// The modified `TextToSpeechRequest` instance with the updated idempotency key.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Sets how urgently the request must be sent when the client has a `RateLimiter`.
+    ///
+    /// # Arguments
+    /// * `priority` - The priority of the request.
+    ///
+    /// # Returns
+    /// The modified `TextToSpeechRequest` instance with the updated priority.
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the context recorded with this call by the client's `AuditSink`.
+    ///
+    /// # Arguments
+    /// * `context` - A caller-defined description of who makes the call and why, such as a user ID.
+    ///
+    /// # Returns
+    /// The modified `TextToSpeechRequest` instance with the updated audit context.
+    pub fn audit_context(mut self, context: impl Into<String>) -> Self {
+        self.audit_context = Some(context.into());
+        self
+    }
+
+    /// Sets the correlation ID sent as the `X-Correlation-ID` header.
+    ///
+    /// # Arguments
+    /// * `id` - The correlation ID.
+    ///
+    /// # Returns
+    /// The modified `TextToSpeechRequest` instance with the updated correlation ID.
+    pub fn correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+
+    /// Returns the JSON body of the request.
+    pub(crate) fn to_json(&self) -> Value {
+        let mut body = json!({"model": self.model, "input": self.input, "voice": self.voice});
+        if let Some(format) = &self.response_format {
+            body["response_format"] = json!(format);
+        }
+        if let Some(speed) = self.speed {
+            body["speed"] = json!(speed);
+        }
+        body
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 /// Represents the response from a speech-to-text transcription request.
 ///
-/// The `text` field contains the transcribed text from the audio input.
+/// The `text` field contains the transcribed text from the audio input, and `extra` holds the fields of the response
+/// not modelled by this type, such as `x_groq`.
 pub struct SpeechToTextResponse {
     pub text: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Displays the transcribed text.
+impl std::fmt::Display for SpeechToTextResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
 }
 
 /// Represents a request to the OpenAI chat completion API.
@@ -246,16 +1454,57 @@ pub struct SpeechToTextResponse {
 /// - `stream`: Whether to stream the response or return it all at once.
 /// - `stop`: A list of strings to stop the generation when encountered.
 /// - `seed`: The seed value to use for the random number generator.
-#[derive(Debug, Clone)]
+/// - `idempotency_key`: An optional key sent as the `Idempotency-Key` header, kept identical across retries.
+/// - `fallback_models`: Models to try in order when the API reports that `model` is decommissioned.
+/// - `priority`: How urgently the request must be sent when the client has a `RateLimiter`.
+/// - `audit_context`: The context recorded with the call by the client's `AuditSink`, such as the user making it.
+/// - `correlation_id`: An optional ID sent as the `X-Correlation-ID` header and attached to errors, spans and hook events.
+/// - `tools`: The tools the model may call.
+/// - `response_format`: The format the model must answer in, as free-form text if not set.
+/// - `auto_max_tokens`: When set, the margin kept when `max_tokens` is computed from the context window of the model.
+/// - `max_continuations`: How many times an answer cut by the token limit is continued with a new request.
+///
+/// Requests serialize to and deserialize from the JSON body of the API. The fields that only configure the client,
+/// from `idempotency_key` to `correlation_id`, `auto_max_tokens` and `max_continuations`, are not part of it and are
+/// left at their defaults by deserialization.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChatCompletionRequest {
     pub model: String,
     pub messages: Vec<ChatCompletionMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub stop: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub seed: Option<u64>,
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
+    #[serde(skip)]
+    pub fallback_models: Vec<String>,
+    #[serde(skip)]
+    pub priority: RequestPriority,
+    #[serde(skip)]
+    pub audit_context: Option<String>,
+    #[serde(skip)]
+    pub correlation_id: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "function_tools"
+    )]
+    pub tools: Vec<ToolDefinition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    #[serde(skip)]
+    pub auto_max_tokens: Option<u32>,
+    #[serde(skip)]
+    pub max_continuations: u32,
 }
 
 /// Represents a request to the OpenAI chat completion API.
@@ -270,16 +1519,50 @@ pub struct ChatCompletionRequest {
 /// - `stream`: Whether to stream the response or return it all at once.
 /// - `stop`: A list of strings to stop the generation when encountered.
 /// - `seed`: The seed value to use for the random number generator.
+/// - `idempotency_key`: An optional key sent as the `Idempotency-Key` header, kept identical across retries.
+/// - `fallback_models`: Models to try in order when the API reports that `model` is decommissioned.
+/// - `priority`: How urgently the request must be sent when the client has a `RateLimiter`.
+/// - `audit_context`: The context recorded with the call by the client's `AuditSink`, such as the user making it.
+/// - `correlation_id`: An optional ID sent as the `X-Correlation-ID` header and attached to errors, spans and hook events.
+/// - `tools`: The tools the model may call.
+/// - `response_format`: The format the model must answer in, as free-form text if not set.
+/// - `auto_max_tokens`: When set, the margin kept when `max_tokens` is computed from the context window of the model.
+/// - `max_continuations`: How many times an answer cut by the token limit is continued with a new request.
 impl ChatCompletionRequest {
+    /// Starts a `ChatCompletionRequestBuilder`, which only builds a request once a model and a message are set.
+    pub fn builder() -> ChatCompletionRequestBuilder<Missing, Missing> {
+        ChatCompletionRequestBuilder::new()
+    }
+
+    /// Checks that the request has a model and at least one message, which the API requires.
+    ///
+    /// The clients check requests before sending them, so invalid requests fail without a round trip to the API.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `GroqError::InvalidRequest` naming the missing part.
+    pub fn validate(&self) -> Result<(), GroqError> {
+        let reason = if self.model.trim().is_empty() {
+            "the model is empty"
+        } else if self.messages.is_empty() {
+            "the request has no messages"
+        } else {
+            return Ok(());
+        };
+        Err(GroqError::InvalidRequest {
+            reason: reason.to_string(),
+        })
+    }
+
     /// Creates a new `ChatCompletionRequest` instance with the given model and messages.
     ///
     /// # Arguments
     ///
-    /// * `model` - The language model to use for the chat completion.
+    /// * `model` - The language model to use for the chat completion, such as `Model::Llama33_70bVersatile`.
     /// * `messages` - The messages to provide as context for the chat completion.
-    pub fn new(model: &str, messages: Vec<ChatCompletionMessage>) -> Self {
+    pub fn new(model: impl Into<String>, messages: Vec<ChatCompletionMessage>) -> Self {
         ChatCompletionRequest {
-            model: model.to_string(),
+            model: model.into(),
             messages,
             temperature: Some(1.0),
             max_tokens: Some(1024),
@@ -287,6 +1570,15 @@ impl ChatCompletionRequest {
             stream: Some(false),
             stop: None,
             seed: None,
+            idempotency_key: None,
+            fallback_models: Vec::new(),
+            priority: RequestPriority::Interactive,
+            audit_context: None,
+            correlation_id: None,
+            tools: Vec::new(),
+            response_format: None,
+            auto_max_tokens: None,
+            max_continuations: 0,
         }
     }
 
@@ -313,6 +1605,50 @@ impl ChatCompletionRequest {
         self
     }
 
+    /// Computes `max_tokens` from the context window of the model instead of using a fixed value.
+    ///
+    /// The limit sent is the context window minus the estimated prompt tokens and the margin, capped at the output
+    /// limit of the model, as known by the default `ModelRegistry`. It is computed again for every fallback model, and
+    /// replaces `max_tokens`, which is only used for models missing from the registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `margin` - The number of tokens kept free to absorb errors of the prompt estimate.
+    pub fn auto_max_tokens(mut self, margin: u32) -> Self {
+        self.auto_max_tokens = Some(margin);
+        self
+    }
+
+    /// Continues answers cut by the token limit, up to the given number of extra requests.
+    ///
+    /// When the first choice of a response finishes with `"length"`, the request is sent again with the partial
+    /// answer as a trailing assistant message for the model to continue, until it finishes for another reason or
+    /// `max` continuations were sent. The continuations are merged into one response: their content is concatenated,
+    /// their usage summed, and the finish reason is the one of the last continuation.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum number of continuation requests.
+    pub fn continue_on_length(mut self, max: u32) -> Self {
+        self.max_continuations = max;
+        self
+    }
+
+    /// Returns the `max_tokens` sent when the request is served by the given model.
+    pub(crate) fn max_tokens_for(&self, model: &str) -> u32 {
+        let fixed = self.max_tokens.unwrap_or(1024);
+        let Some(margin) = self.auto_max_tokens else {
+            return fixed;
+        };
+        let Some(info) = ModelRegistry::builtin().get(model) else {
+            return fixed;
+        };
+        let prompt_tokens = count_tokens(self);
+        let available =
+            u64::from(info.context_window).saturating_sub(prompt_tokens + u64::from(margin));
+        available.clamp(1, u64::from(info.output_limit())) as u32
+    }
+
     /// Sets the top-p parameter for the chat completion request.
     ///
     /// The top-p parameter controls the nucleus sampling, which is a technique for sampling from the most likely tokens.
@@ -345,6 +1681,26 @@ impl ChatCompletionRequest {
         self
     }
 
+    /// Sets the tools the model may call.
+    ///
+    /// # Arguments
+    ///
+    /// * `tools` - The definitions of the tools.
+    pub fn tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Sets the format the model must answer in.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The response format.
+    pub fn response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+
     /// Sets the seed value to use for the random number generator.
     ///
     /// # Arguments
@@ -354,4 +1710,305 @@ impl ChatCompletionRequest {
         self.seed = Some(seed);
         self
     }
+
+    /// Sets the idempotency key sent as the `Idempotency-Key` header.
+    ///
+    /// Gateways that honor the header will not process the same request twice, even when it is retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A unique key identifying this request.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Sets the models to fall back to when the API reports that the requested model is decommissioned.
+    ///
+    /// The models are tried in order; the `model` field of the response tells which one served the request.
+    ///
+    /// # Arguments
+    ///
+    /// * `models` - The fallback models, in order of preference.
+    pub fn fallback_models(mut self, models: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.fallback_models = models
+            .into_iter()
+            .map(|model| model.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Sets how urgently the request must be sent when the client has a `RateLimiter`.
+    ///
+    /// Background requests only use capacity that no interactive request is waiting for.
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - The priority of the request.
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the context recorded with this call by the client's `AuditSink`.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - A caller-defined description of who makes the call and why, such as a user ID.
+    pub fn audit_context(mut self, context: impl Into<String>) -> Self {
+        self.audit_context = Some(context.into());
+        self
+    }
+
+    /// Sets the correlation ID sent as the `X-Correlation-ID` header.
+    ///
+    /// The ID is kept across retries and fallback models, and attached to errors, tracing spans and hook events, so
+    /// that one user action can be followed through the logs.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The correlation ID.
+    pub fn correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_error_from_response_status() {
+        let error =
+            json!({"error": {"message": "Invalid API Key", "type": "invalid_request_error"}});
+        let headers = HeaderMap::new();
+        assert!(matches!(
+            GroqError::from_response(StatusCode::UNAUTHORIZED, &headers, error.clone()),
+            GroqError::Unauthorized(ApiErrorPayload { ref message, .. }) if message == "Invalid API Key"
+        ));
+        assert!(matches!(
+            GroqError::from_response(StatusCode::SERVICE_UNAVAILABLE, &headers, error.clone()),
+            GroqError::ServerError { status: 503, .. }
+        ));
+        assert!(matches!(
+            GroqError::from_response(StatusCode::BAD_REQUEST, &headers, error.clone()),
+            GroqError::ApiError { status: 400, .. }
+        ));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", "req_01abc".parse().unwrap());
+        let error = GroqError::from_response(StatusCode::BAD_REQUEST, &headers, error);
+        assert_eq!(error.request_id(), Some("req_01abc"));
+        assert_eq!(error.status(), Some(400));
+
+        let error = json!({"error": {"message": "Please try again in 1.5s.", "type": "tokens"}});
+        assert!(matches!(
+            GroqError::from_response(StatusCode::TOO_MANY_REQUESTS, &headers, error),
+            GroqError::RateLimited { retry_after: Some(d), .. } if d == Duration::from_millis(1500)
+        ));
+    }
+
+    #[test]
+    fn test_unknown_response_fields_are_kept() {
+        let body = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1718000000,
+            "model": "llama3-8b-8192",
+            "system_fingerprint": "fp_1",
+            "service_tier": "on_demand",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hi", "reasoning": "..."},
+                "logprobs": null,
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "queue_time": 0.01,
+                "prompt_tokens": 10, "prompt_time": 0.01,
+                "completion_tokens": 2, "completion_time": 0.02,
+                "total_tokens": 12, "total_time": 0.03
+            },
+            "x_groq": {"id": "req_1", "region": "us-east"}
+        });
+        let response: ChatCompletionResponse = deserialize(body.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&response).unwrap(), body);
+        assert_eq!(response.choices[0].message.content, "Hi");
+        assert_eq!(response.to_string(), "Hi");
+        assert_eq!(
+            response.usage.as_ref().unwrap().to_string(),
+            "12 tokens (10 prompt + 2 completion) in 0.030s"
+        );
+        assert_eq!(response.extra["service_tier"], "on_demand");
+        assert_eq!(response.choices[0].message.extra["reasoning"], "...");
+        assert_eq!(response.usage.unwrap().extra["queue_time"], 0.01);
+    }
+
+    #[test]
+    fn test_optional_response_fields() {
+        let body = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1718000000,
+            "model": "llama3",
+            "choices": []
+        });
+        let response: ChatCompletionResponse = deserialize(body).unwrap();
+        assert_eq!(response.to_string(), "");
+        assert!(response.system_fingerprint.is_none());
+        assert!(response.usage.is_none());
+        assert!(response.x_groq.is_none());
+    }
+
+    #[test]
+    fn test_request_round_trips_wire_format() {
+        let body = json!({
+            "model": "llama-3.3-70b-versatile",
+            "messages": [
+                {"role": "system", "content": "Be brief"},
+                {"role": "assistant", "content": null, "tool_calls": [
+                    {"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{}"}}
+                ]},
+                {"role": "tool", "content": "Sunny", "tool_call_id": "call_1"}
+            ],
+            "temperature": 0.2,
+            "stop": ["\n\n"],
+            "tools": [{"type": "function", "function": {"name": "get_weather", "description": "Returns the weather", "parameters": {"type": "object"}}}],
+            "response_format": {"type": "json_schema", "json_schema": {"name": "weather", "schema": {}, "strict": true}}
+        });
+        let request: ChatCompletionRequest = serde_json::from_value(body.clone()).unwrap();
+        assert_eq!(request.messages[1].content, "");
+        assert_eq!(request.tools[0].name, "get_weather");
+        assert_eq!(
+            request.response_format,
+            Some(ResponseFormat::json_schema("weather", json!({})))
+        );
+        assert_eq!(request.max_tokens, None);
+        assert_eq!(request.priority, RequestPriority::Interactive);
+
+        let mut expected = body;
+        expected["messages"][1]["content"] = json!("");
+        assert_eq!(serde_json::to_value(&request).unwrap(), expected);
+
+        let request = ChatCompletionRequest::new("llama3-8b-8192", crate::messages![user: "Hi"])
+            .idempotency_key("key-1");
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({"model": "llama3-8b-8192", "messages": [{"role": "user", "content": "Hi"}],
+                "temperature": 1.0, "max_tokens": 1024, "top_p": 1.0, "stream": false})
+        );
+    }
+
+    #[test]
+    fn test_messages_and_requests_compare() {
+        let history = crate::messages![user: "Hi", assistant: "Hello!", user: "Hi"];
+        let unique: std::collections::HashSet<&ChatCompletionMessage> = history.iter().collect();
+        assert_eq!(unique.len(), 2);
+        assert_eq!(history[0], history[2]);
+
+        let request = ChatCompletionRequest::new("llama3-8b-8192", history.clone());
+        assert_eq!(request, request.clone());
+        assert_ne!(request, request.clone().temperature(0.0));
+        assert_ne!(
+            request,
+            ChatCompletionRequest::new("llama3-8b-8192", history[..2].to_vec())
+        );
+    }
+
+    #[test]
+    fn test_message_content_is_shared_by_clones() {
+        let corpus: Arc<str> = "word ".repeat(100_000).into();
+        let request = ChatCompletionRequest::builder()
+            .model("llama3-8b-8192")
+            .user(corpus.clone())
+            .build();
+        let retry = request.clone();
+        assert!(Arc::ptr_eq(retry.messages[0].content.as_arc(), &corpus));
+
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(body["messages"][0]["content"], json!(&*corpus));
+        let parsed: ChatCompletionMessage =
+            serde_json::from_value(json!({"role": "assistant", "content": null})).unwrap();
+        assert_eq!(parsed.content, "");
+    }
+
+    #[test]
+    fn test_setters_accept_any_string() {
+        let id = String::from("action-7");
+        let request = ChatCompletionRequest::new(crate::Model::Llama3_8b8192, Vec::new())
+            .correlation_id(&id)
+            .audit_context(id.clone())
+            .idempotency_key(std::borrow::Cow::Borrowed("key-1"))
+            .fallback_models([crate::Model::Llama33_70bVersatile]);
+        assert_eq!(request.correlation_id.as_deref(), Some("action-7"));
+        assert_eq!(request.audit_context, Some(id));
+        assert_eq!(request.idempotency_key.as_deref(), Some("key-1"));
+        assert_eq!(request.fallback_models, vec!["llama-3.3-70b-versatile"]);
+
+        let transcription = SpeechToTextRequest::new(Vec::new())
+            .language(String::from("fr"))
+            .prompt("Bonjour");
+        assert_eq!(transcription.language.as_deref(), Some("fr"));
+    }
+
+    #[test]
+    fn test_language_codes() {
+        assert_eq!(Language::from_code("en").map(Language::code), Some("en"));
+        assert_eq!(Language::from_code("EN"), None);
+        assert_eq!(Language::from_code("xx"), None);
+        assert!(LANGUAGE_CODES.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let request =
+            SpeechToTextRequest::new(Vec::new()).language(Language::from_code("de").unwrap());
+        assert_eq!(request.language.as_deref(), Some("de"));
+        assert!(request.validate().is_ok());
+        assert!(SpeechToTextRequest::new(Vec::new()).validate().is_ok());
+        assert!(matches!(
+            SpeechToTextRequest::new(Vec::new())
+                .language("german")
+                .validate(),
+            Err(GroqError::InvalidRequest { .. })
+        ));
+    }
+
+    #[test]
+    fn test_deserialization_error_keeps_payload() {
+        let body = json!({"id": "meta-1", "usage": {"total_tokens": "twelve"}});
+        let Err(GroqError::DeserializationError { payload, .. }) =
+            deserialize::<SpeechToTextResponse>(body)
+        else {
+            panic!("expected a deserialization error");
+        };
+        assert!(payload.contains("\"total_tokens\":\"twelve\""));
+    }
+
+    #[test]
+    fn test_error_keeps_code_and_body() {
+        let body = r#"{"error": {"message": "Failed to generate JSON", "type": "invalid_request_error", "code": "json_validate_failed", "failed_generation": "{\"a\":"}}"#;
+        let error = GroqError::from_body(StatusCode::BAD_REQUEST, &HeaderMap::new(), body);
+        assert_eq!(error.code(), Some("json_validate_failed"));
+        match error {
+            GroqError::ApiError { body, .. } => {
+                assert_eq!(body["error"]["failed_generation"], "{\"a\":")
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_error_classification() {
+        let headers = HeaderMap::new();
+        let rate_limited = GroqError::from_body(StatusCode::TOO_MANY_REQUESTS, &headers, "");
+        assert!(rate_limited.is_rate_limit() && rate_limited.is_retryable());
+        assert_eq!(rate_limited.status(), Some(429));
+
+        let bad_gateway = GroqError::from_body(StatusCode::BAD_GATEWAY, &headers, "<html>");
+        assert!(bad_gateway.is_retryable() && !bad_gateway.is_auth_error());
+        assert_eq!(bad_gateway.status(), Some(502));
+
+        let forbidden = GroqError::from_body(StatusCode::FORBIDDEN, &headers, "{}");
+        assert!(forbidden.is_auth_error() && !forbidden.is_retryable());
+    }
 }
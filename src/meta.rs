@@ -0,0 +1,154 @@
+use crate::message::request_id;
+use crate::retry::parse_duration;
+use crate::Usage;
+use reqwest::{header::HeaderMap, StatusCode};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+/// Represents HTTP metadata of a successful response.
+///
+/// - `status`: The HTTP status of the response.
+/// - `request_id`: The `x-request-id` of the response, if any.
+/// - `rate_limit`: The rate limit state reported through the `x-ratelimit-*` headers.
+/// - `timing`: The wall-clock timings measured by the client.
+/// - `headers`: All the headers of the response, including the ones without a dedicated field.
+/// - `cached`: Whether the response was returned by the client's `ResponseCache` without sending a request.
+pub struct ResponseMeta {
+    pub status: u16,
+    pub request_id: Option<String>,
+    pub rate_limit: RateLimitInfo,
+    pub timing: ResponseTiming,
+    pub headers: HeaderMap,
+    pub cached: bool,
+}
+
+impl ResponseMeta {
+    /// Builds the metadata from the status and headers of a response.
+    pub(crate) fn from_response(status: StatusCode, headers: &HeaderMap) -> Self {
+        Self {
+            status: status.as_u16(),
+            request_id: request_id(headers),
+            rate_limit: RateLimitInfo::from_headers(headers),
+            timing: ResponseTiming::default(),
+            headers: headers.clone(),
+            cached: false,
+        }
+    }
+
+    /// Returns the value of a response header, if it is present and valid text.
+    ///
+    /// # Arguments
+    /// * `name` - The case-insensitive name of the header.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    /// Builds the metadata of a response returned by a `ResponseCache`.
+    pub(crate) fn cached() -> Self {
+        Self {
+            status: 200,
+            cached: true,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the total duration of the call and the number of generated tokens.
+    pub(crate) fn with_total(mut self, total: Duration, usage: Option<&Usage>) -> Self {
+        self.timing.total = total;
+        self.timing.completion_tokens = usage.map(|usage| usage.completion_tokens);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+/// Represents the wall-clock timings of a call, as measured by the client.
+///
+/// - `time_to_first_byte`: The time the last attempt took to receive the response headers.
+/// - `total`: The duration of the whole call, including waiting for capacity and retries.
+/// - `completion_tokens`: The number of generated tokens, if reported.
+pub struct ResponseTiming {
+    pub time_to_first_byte: Duration,
+    pub total: Duration,
+    pub completion_tokens: Option<u64>,
+}
+
+impl ResponseTiming {
+    /// Returns the number of generated tokens per second of the whole call, as experienced by the caller.
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        let secs = self.total.as_secs_f64();
+        self.completion_tokens
+            .filter(|_| secs > 0.0)
+            .map(|tokens| tokens as f64 / secs)
+    }
+}
+
+impl Usage {
+    /// Returns the number of completion tokens generated per second, as reported by the API.
+    ///
+    /// Unlike `ResponseTiming::tokens_per_second`, this excludes network and queueing time.
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        (self.completion_time > 0.0).then(|| self.completion_tokens as f64 / self.completion_time)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+/// Represents the rate limit state reported by the Groq API.
+///
+/// Request limits are per day and token limits are per minute. Every field is `None` when the corresponding header is missing.
+///
+/// - `limit_requests`: The maximum number of requests allowed (`x-ratelimit-limit-requests`).
+/// - `limit_tokens`: The maximum number of tokens allowed (`x-ratelimit-limit-tokens`).
+/// - `remaining_requests`: The number of requests left (`x-ratelimit-remaining-requests`).
+/// - `remaining_tokens`: The number of tokens left (`x-ratelimit-remaining-tokens`).
+/// - `reset_requests`: The time until the request limit resets (`x-ratelimit-reset-requests`).
+/// - `reset_tokens`: The time until the token limit resets (`x-ratelimit-reset-tokens`).
+pub struct RateLimitInfo {
+    pub limit_requests: Option<u64>,
+    pub limit_tokens: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    pub reset_requests: Option<Duration>,
+    pub reset_tokens: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Reads the `x-ratelimit-*` headers of a response.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let count = |name: &str| header(name).and_then(|value| value.trim().parse().ok());
+        let reset = |name: &str| header(name).and_then(|value| parse_duration(value.trim()));
+
+        Self {
+            limit_requests: count("x-ratelimit-limit-requests"),
+            limit_tokens: count("x-ratelimit-limit-tokens"),
+            remaining_requests: count("x-ratelimit-remaining-requests"),
+            remaining_tokens: count("x-ratelimit-remaining-tokens"),
+            reset_requests: reset("x-ratelimit-reset-requests"),
+            reset_tokens: reset("x-ratelimit-reset-tokens"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit-requests", "14400".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-tokens", "5930".parse().unwrap());
+        headers.insert("x-ratelimit-reset-requests", "2m59.56s".parse().unwrap());
+        headers.insert("x-ratelimit-reset-tokens", "7.66s".parse().unwrap());
+
+        let info = RateLimitInfo::from_headers(&headers);
+        assert_eq!(info.limit_requests, Some(14400));
+        assert_eq!(info.remaining_tokens, Some(5930));
+        assert_eq!(info.remaining_requests, None);
+        assert_eq!(info.reset_requests, Some(Duration::from_secs_f64(179.56)));
+        assert_eq!(info.reset_tokens, Some(Duration::from_secs_f64(7.66)));
+    }
+}
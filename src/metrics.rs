@@ -0,0 +1,140 @@
+use crate::Usage;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+/// Prometheus metrics of the requests sent by a client, available with the `metrics` feature.
+///
+/// The metrics are registered in their own `Registry`, which can be scraped through `encode` or merged into an
+/// application registry with `with_registry`. Labels are limited to the endpoint path, the status, the error type
+/// and the model, so their cardinality stays bounded. Cloning a `GroqMetrics` shares its state.
+///
+/// - `groq_requests_total`: Attempts sent, by `endpoint` and `status` (empty when no response was received).
+/// - `groq_failures_total`: Failed attempts, by `endpoint` and `error`.
+/// - `groq_tokens_total`: Tokens used by chat completions, by `model` and `type` (`prompt` or `completion`).
+/// - `groq_request_duration_seconds`: Time until the response headers were received, by `endpoint`.
+pub struct GroqMetrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    failures: IntCounterVec,
+    tokens: IntCounterVec,
+    latency: HistogramVec,
+}
+
+impl GroqMetrics {
+    /// Creates the metrics in a new `Registry`.
+    pub fn new() -> Self {
+        Self::with_registry(Registry::new()).expect("metrics are registered in an empty registry")
+    }
+
+    /// Creates the metrics and registers them in the given `Registry`.
+    ///
+    /// # Arguments
+    /// * `registry` - The registry to register the metrics in.
+    ///
+    /// # Errors
+    /// Returns an error if metrics with the same names are already registered.
+    pub fn with_registry(registry: Registry) -> Result<Self, prometheus::Error> {
+        let requests = IntCounterVec::new(
+            Opts::new("groq_requests_total", "Requests sent to the Groq API"),
+            &["endpoint", "status"],
+        )?;
+        let failures = IntCounterVec::new(
+            Opts::new("groq_failures_total", "Failed requests to the Groq API"),
+            &["endpoint", "error"],
+        )?;
+        let tokens = IntCounterVec::new(
+            Opts::new("groq_tokens_total", "Tokens used by chat completions"),
+            &["model", "type"],
+        )?;
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "groq_request_duration_seconds",
+                "Time until the response headers were received",
+            ),
+            &["endpoint"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(failures.clone()))?;
+        registry.register(Box::new(tokens.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        Ok(Self {
+            registry,
+            requests,
+            failures,
+            tokens,
+            latency,
+        })
+    }
+
+    /// Returns the registry holding the metrics.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Encodes the metrics in the Prometheus text format, ready to be served to a scraper.
+    pub fn encode(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn record_attempt(&self, url: &str, status: Option<u16>, latency: Duration) {
+        let endpoint = endpoint_label(url);
+        let status = status.map(|status| status.to_string()).unwrap_or_default();
+        self.requests.with_label_values(&[endpoint, &status]).inc();
+        self.latency
+            .with_label_values(&[endpoint])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub(crate) fn record_error(&self, url: &str, error: &str) {
+        self.failures
+            .with_label_values(&[endpoint_label(url), error])
+            .inc();
+    }
+
+    pub(crate) fn record_usage(&self, model: &str, usage: &Usage) {
+        self.tokens
+            .with_label_values(&[model, "prompt"])
+            .inc_by(usage.prompt_tokens);
+        self.tokens
+            .with_label_values(&[model, "completion"])
+            .inc_by(usage.completion_tokens);
+    }
+}
+
+impl Default for GroqMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the path of the endpoint a URL points to, such as `/chat/completions`.
+fn endpoint_label(url: &str) -> &str {
+    let path = url.split_once("://").map_or(url, |(_, rest)| {
+        rest.find('/').map_or("", |start| &rest[start..])
+    });
+    path.strip_prefix("/openai/v1").unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_are_encoded() {
+        let metrics = GroqMetrics::new();
+        let url = "https://api.groq.com/openai/v1/chat/completions";
+        metrics.record_attempt(url, Some(200), Duration::from_millis(120));
+        metrics.record_error(url, "rate_limited");
+
+        let text = metrics.encode();
+        assert!(
+            text.contains(r#"groq_requests_total{endpoint="/chat/completions",status="200"} 1"#)
+        );
+        assert!(text.contains(
+            r#"groq_failures_total{endpoint="/chat/completions",error="rate_limited"} 1"#
+        ));
+    }
+}
@@ -0,0 +1,327 @@
+#[cfg(feature = "stream")]
+use crate::{AsyncChatCompletionStream, ChatCompletionChunk, StreamApi};
+use crate::{
+    AudioApi, ChatApi, ChatCompletionRequest, ChatCompletionResponse, GroqError,
+    SpeechToTextRequest, SpeechToTextResponse,
+};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+type Reply = Arc<dyn Fn(&ChatCompletionRequest) -> Result<String, GroqError> + Send + Sync>;
+type Predicate = Arc<dyn Fn(&ChatCompletionRequest) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+/// An offline client implementing `ChatApi`, `StreamApi` and `AudioApi` with canned or rule-based responses.
+///
+/// Rules are checked in the order they were added and the first matching one answers the request; requests no rule
+/// matches get the default reply. Every request is recorded, so tests can assert on what was sent.
+/// Cloning a `MockGroqClient` shares its recorded requests.
+///
+/// Streamed answers are split into one chunk per word, with the usage in the last chunk; use `delay` to simulate
+/// latency.
+pub struct MockGroqClient {
+    rules: Vec<(Predicate, Reply)>,
+    default_reply: Reply,
+    transcription: String,
+    delay: Duration,
+    requests: Arc<Mutex<Vec<ChatCompletionRequest>>>,
+}
+
+impl Default for MockGroqClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockGroqClient {
+    /// Creates a new `MockGroqClient` replying "This is a mock response." to every chat completion.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            default_reply: Arc::new(|_| Ok("This is a mock response.".to_string())),
+            transcription: "This is a mock transcription.".to_string(),
+            delay: Duration::ZERO,
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Sets the reply to chat completions no rule matches.
+    ///
+    /// # Arguments
+    /// * `content` - The content of the assistant message.
+    pub fn reply(mut self, content: impl Into<String>) -> Self {
+        let content: String = content.into();
+        self.default_reply = Arc::new(move |_| Ok(content.clone()));
+        self
+    }
+
+    /// Adds a rule replying with the given content to chat completions whose last message contains `pattern`.
+    ///
+    /// # Arguments
+    /// * `pattern` - The text to look for in the last message.
+    /// * `content` - The content of the assistant message.
+    pub fn reply_when_contains(
+        self,
+        pattern: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        let pattern: String = pattern.into();
+        let content: String = content.into();
+        self.rule(
+            move |request| {
+                request
+                    .messages
+                    .last()
+                    .is_some_and(|message| message.content.contains(&pattern))
+            },
+            move |_| Ok(content.clone()),
+        )
+    }
+
+    /// Adds a rule answering the chat completions matching `predicate`.
+    ///
+    /// # Arguments
+    /// * `predicate` - Returns true for the requests this rule answers.
+    /// * `reply` - Returns the content of the assistant message, or the error to fail the request with.
+    pub fn rule(
+        mut self,
+        predicate: impl Fn(&ChatCompletionRequest) -> bool + Send + Sync + 'static,
+        reply: impl Fn(&ChatCompletionRequest) -> Result<String, GroqError> + Send + Sync + 'static,
+    ) -> Self {
+        self.rules.push((Arc::new(predicate), Arc::new(reply)));
+        self
+    }
+
+    /// Sets the text returned by speech-to-text requests.
+    ///
+    /// # Arguments
+    /// * `text` - The transcribed text.
+    pub fn transcription(mut self, text: impl Into<String>) -> Self {
+        self.transcription = text.into();
+        self
+    }
+
+    /// Sets how long every request takes before its response is returned.
+    ///
+    /// # Arguments
+    /// * `delay` - The simulated latency.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Returns the chat completion requests received so far, in order.
+    pub fn requests(&self) -> Vec<ChatCompletionRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    fn answer(&self, request: &ChatCompletionRequest) -> Result<String, GroqError> {
+        let reply = self
+            .rules
+            .iter()
+            .find(|(predicate, _)| predicate(request))
+            .map_or(&self.default_reply, |(_, reply)| reply);
+        reply(request)
+    }
+}
+
+impl std::fmt::Debug for MockGroqClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockGroqClient")
+            .field("rules", &self.rules.len())
+            .field("transcription", &self.transcription)
+            .field("delay", &self.delay)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ChatApi for MockGroqClient {
+    async fn chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, GroqError> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(request.clone());
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+
+        let content = self.answer(&request)?;
+        let prompt_chars: usize = request
+            .messages
+            .iter()
+            .map(|message| message.content.len())
+            .sum();
+        let prompt_tokens = (prompt_chars as u64).div_ceil(4);
+        let completion_tokens = (content.len() as u64).div_ceil(4);
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        crate::message::deserialize(json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion",
+            "created": created,
+            "model": request.model,
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": content},
+                "logprobs": null,
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": prompt_tokens,
+                "completion_tokens": completion_tokens,
+                "total_tokens": prompt_tokens + completion_tokens,
+                "prompt_time": 0.0,
+                "completion_time": self.delay.as_secs_f64(),
+                "total_time": self.delay.as_secs_f64()
+            }
+        }))
+    }
+}
+
+#[cfg(feature = "stream")]
+#[async_trait]
+impl StreamApi for MockGroqClient {
+    async fn chat_completion_stream(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<AsyncChatCompletionStream, GroqError> {
+        let response = ChatApi::chat_completion(self, request).await?;
+        let chunk = |delta: serde_json::Value,
+                     finish_reason: Option<&str>,
+                     usage: Option<&crate::Usage>| {
+            crate::message::deserialize::<ChatCompletionChunk>(json!({
+                "id": response.id,
+                "object": "chat.completion.chunk",
+                "created": response.created,
+                "model": response.model,
+                "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}],
+                "x_groq": {"id": "req_mock", "usage": usage}
+            }))
+        };
+        let content = &response.choices[0].message.content;
+        let mut chunks = vec![chunk(
+            json!({"role": "assistant", "content": ""}),
+            None,
+            None,
+        )?];
+        for word in content.split_inclusive(' ') {
+            chunks.push(chunk(json!({"content": word}), None, None)?);
+        }
+        chunks.push(chunk(json!({}), Some("stop"), response.usage.as_ref())?);
+        Ok(AsyncChatCompletionStream::from_chunks(chunks))
+    }
+}
+
+#[async_trait]
+impl AudioApi for MockGroqClient {
+    async fn speech_to_text(
+        &self,
+        _request: SpeechToTextRequest,
+    ) -> Result<SpeechToTextResponse, GroqError> {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        crate::message::deserialize(json!({ "text": self.transcription }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChatCompletionMessage, ChatCompletionRoles};
+
+    fn request(content: &str) -> ChatCompletionRequest {
+        ChatCompletionRequest::new(
+            "llama3-8b-8192",
+            vec![ChatCompletionMessage {
+                role: ChatCompletionRoles::User,
+                content: content.into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_mock_rules_and_recording() {
+        let mock = MockGroqClient::new()
+            .reply("I don't know.")
+            .reply_when_contains("weather", "It is sunny.")
+            .rule(
+                |request| request.model == "unknown-model",
+                |request| {
+                    Err(GroqError::NotFound(crate::ApiErrorPayload {
+                        message: format!("The model `{}` does not exist", request.model),
+                        type_: "invalid_request_error".to_string(),
+                        code: Some("model_not_found".to_string()),
+                        request_id: None,
+                        correlation_id: None,
+                        body: Box::new(json!({})),
+                    }))
+                },
+            );
+        let chat: &dyn ChatApi = &mock;
+
+        let response = chat
+            .chat_completion(request("What is the weather like?"))
+            .await
+            .unwrap();
+        assert_eq!(response.choices[0].message.content, "It is sunny.");
+        assert_eq!(response.usage.as_ref().unwrap().completion_tokens, 3);
+        let response = chat.chat_completion(request("Hello")).await.unwrap();
+        assert_eq!(response.choices[0].message.content, "I don't know.");
+
+        let mut unknown = request("Hello");
+        unknown.model = "unknown-model".to_string();
+        assert!(matches!(
+            chat.chat_completion(unknown).await,
+            Err(GroqError::NotFound(_))
+        ));
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[cfg(all(feature = "audio", feature = "stream"))]
+    #[tokio::test]
+    async fn test_providers_behind_groq_api() {
+        let groq = crate::AsyncGroqClient::new("key", Some("http://127.0.0.1:1".to_string())).await;
+        let providers: Vec<Arc<dyn crate::GroqApi>> = vec![
+            Arc::new(MockGroqClient::new().reply("The capital of France is Paris.")),
+            Arc::new(groq),
+        ];
+
+        let mut stream = providers[0]
+            .chat_completion_stream(request("Capital of France?"))
+            .await
+            .unwrap();
+        let mut chunks = 0;
+        let mut content = String::new();
+        let mut usage = None;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            chunks += 1;
+            content.push_str(chunk.content());
+            usage = usage.or(chunk.usage());
+        }
+        assert_eq!(content, "The capital of France is Paris.");
+        assert_eq!(chunks, 8);
+        assert_eq!(usage.unwrap().completion_tokens, 8);
+
+        let chat: &dyn ChatApi = providers[0].as_ref();
+        assert!(chat.chat_completion(request("Hello")).await.is_ok());
+        assert!(providers[1]
+            .chat_completion_stream(request("Hello"))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! Names of the models served by Groq.
+//!
+//! The constants can be passed anywhere a model name is expected, and `Model` lists the same models as an enum
+//! converting into their names, so that typos in model names are caught at compile time:
+//!
+//! ```
+//! use groq_api_rust::{messages, models, ChatCompletionRequest, Model};
+//!
+//! let request = ChatCompletionRequest::new(Model::Llama31_8bInstant, messages![user: "Hello"]);
+//! assert_eq!(request.model, models::LLAMA_3_1_8B_INSTANT);
+//! ```
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Llama 3.3 70B, for general-purpose chat.
+pub const LLAMA_3_3_70B_VERSATILE: &str = "llama-3.3-70b-versatile";
+/// Llama 3.1 8B, for fast and cheap chat.
+pub const LLAMA_3_1_8B_INSTANT: &str = "llama-3.1-8b-instant";
+/// Llama 3 70B with an 8K context window.
+pub const LLAMA3_70B_8192: &str = "llama3-70b-8192";
+/// Llama 3 8B with an 8K context window.
+pub const LLAMA3_8B_8192: &str = "llama3-8b-8192";
+/// Llama 3 70B fine-tuned for tool use.
+pub const LLAMA3_GROQ_70B_TOOL_USE: &str = "llama3-groq-70b-8192-tool-use-preview";
+/// Llama 3 8B fine-tuned for tool use.
+pub const LLAMA3_GROQ_8B_TOOL_USE: &str = "llama3-groq-8b-8192-tool-use-preview";
+/// Llama Guard 3, classifying the safety of prompts and answers.
+pub const LLAMA_GUARD_3_8B: &str = "llama-guard-3-8b";
+/// Gemma 2 9B, instruction-tuned.
+pub const GEMMA2_9B_IT: &str = "gemma2-9b-it";
+/// Mixtral 8x7B with a 32K context window.
+pub const MIXTRAL_8X7B_32768: &str = "mixtral-8x7b-32768";
+/// Whisper large v3, for transcription and translation.
+pub const WHISPER_LARGE_V3: &str = "whisper-large-v3";
+/// Whisper large v3 turbo, a faster transcription model.
+pub const WHISPER_LARGE_V3_TURBO: &str = "whisper-large-v3-turbo";
+/// Distil-Whisper large v3, transcribing English only.
+pub const DISTIL_WHISPER_LARGE_V3_EN: &str = "distil-whisper-large-v3-en";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+/// Represents a model served by Groq, converting into its name.
+///
+/// - `Llama33_70bVersatile`: `llama-3.3-70b-versatile`.
+/// - `Llama31_8bInstant`: `llama-3.1-8b-instant`.
+/// - `Llama3_70b8192`: `llama3-70b-8192`.
+/// - `Llama3_8b8192`: `llama3-8b-8192`.
+/// - `Llama3Groq70bToolUse`: `llama3-groq-70b-8192-tool-use-preview`.
+/// - `Llama3Groq8bToolUse`: `llama3-groq-8b-8192-tool-use-preview`.
+/// - `LlamaGuard3_8b`: `llama-guard-3-8b`.
+/// - `Gemma2_9bIt`: `gemma2-9b-it`.
+/// - `Mixtral8x7b32768`: `mixtral-8x7b-32768`.
+/// - `WhisperLargeV3`: `whisper-large-v3`.
+/// - `WhisperLargeV3Turbo`: `whisper-large-v3-turbo`.
+/// - `DistilWhisperLargeV3En`: `distil-whisper-large-v3-en`.
+pub enum Model {
+    Llama33_70bVersatile,
+    Llama31_8bInstant,
+    Llama3_70b8192,
+    Llama3_8b8192,
+    Llama3Groq70bToolUse,
+    Llama3Groq8bToolUse,
+    LlamaGuard3_8b,
+    Gemma2_9bIt,
+    Mixtral8x7b32768,
+    WhisperLargeV3,
+    WhisperLargeV3Turbo,
+    DistilWhisperLargeV3En,
+}
+
+impl Model {
+    /// Every known model.
+    pub const ALL: [Model; 12] = [
+        Model::Llama33_70bVersatile,
+        Model::Llama31_8bInstant,
+        Model::Llama3_70b8192,
+        Model::Llama3_8b8192,
+        Model::Llama3Groq70bToolUse,
+        Model::Llama3Groq8bToolUse,
+        Model::LlamaGuard3_8b,
+        Model::Gemma2_9bIt,
+        Model::Mixtral8x7b32768,
+        Model::WhisperLargeV3,
+        Model::WhisperLargeV3Turbo,
+        Model::DistilWhisperLargeV3En,
+    ];
+
+    /// Returns the name of the model, as sent to the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Model::Llama33_70bVersatile => LLAMA_3_3_70B_VERSATILE,
+            Model::Llama31_8bInstant => LLAMA_3_1_8B_INSTANT,
+            Model::Llama3_70b8192 => LLAMA3_70B_8192,
+            Model::Llama3_8b8192 => LLAMA3_8B_8192,
+            Model::Llama3Groq70bToolUse => LLAMA3_GROQ_70B_TOOL_USE,
+            Model::Llama3Groq8bToolUse => LLAMA3_GROQ_8B_TOOL_USE,
+            Model::LlamaGuard3_8b => LLAMA_GUARD_3_8B,
+            Model::Gemma2_9bIt => GEMMA2_9B_IT,
+            Model::Mixtral8x7b32768 => MIXTRAL_8X7B_32768,
+            Model::WhisperLargeV3 => WHISPER_LARGE_V3,
+            Model::WhisperLargeV3Turbo => WHISPER_LARGE_V3_TURBO,
+            Model::DistilWhisperLargeV3En => DISTIL_WHISPER_LARGE_V3_EN,
+        }
+    }
+
+    /// Returns the capabilities of the model, from the built-in `ModelRegistry`.
+    pub fn info(&self) -> ModelInfo {
+        ModelRegistry::builtin()
+            .get(self.as_str())
+            .cloned()
+            .expect("every `Model` has a built-in `ModelInfo`")
+    }
+}
+
+impl std::fmt::Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for Model {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<Model> for String {
+    fn from(model: Model) -> Self {
+        model.as_str().to_string()
+    }
+}
+
+/// Parses the name of a known model, returning it back as the error otherwise.
+impl std::str::FromStr for Model {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Model::ALL
+            .into_iter()
+            .find(|model| model.as_str() == name)
+            .ok_or_else(|| name.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+/// Represents the kind of input a model takes.
+///
+/// - `Text`: Chat messages.
+/// - `Audio`: Audio files, for transcription and translation.
+pub enum Modality {
+    Text,
+    Audio,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+/// Represents the capabilities of a model.
+///
+/// - `id`: The name of the model, as sent to the API.
+/// - `context_window`: The maximum number of tokens of the prompt and completion together.
+/// - `max_output_tokens`: The maximum number of tokens of a completion, if limited below the context window.
+/// - `supports_tools`: Whether the model can call tools.
+/// - `supports_vision`: Whether the model accepts images.
+/// - `supports_json_mode`: Whether the model supports the `JsonObject` and `JsonSchema` response formats.
+/// - `modality`: The kind of input the model takes.
+/// - `active`: Whether the model is currently served, as reported by the `/models` endpoint.
+pub struct ModelInfo {
+    pub id: String,
+    pub context_window: u32,
+    pub max_output_tokens: Option<u32>,
+    pub supports_tools: bool,
+    pub supports_vision: bool,
+    pub supports_json_mode: bool,
+    pub modality: Modality,
+    pub active: bool,
+}
+
+impl ModelInfo {
+    /// Creates a new `ModelInfo` for an active text model without tools, vision or JSON mode.
+    ///
+    /// # Arguments
+    /// * `id` - The name of the model.
+    /// * `context_window` - The maximum number of tokens of the prompt and completion together.
+    pub fn new(id: impl Into<String>, context_window: u32) -> Self {
+        Self {
+            id: id.into(),
+            context_window,
+            max_output_tokens: None,
+            supports_tools: false,
+            supports_vision: false,
+            supports_json_mode: false,
+            modality: Modality::Text,
+            active: true,
+        }
+    }
+
+    /// Returns the maximum number of tokens the model can generate.
+    pub fn output_limit(&self) -> u32 {
+        self.max_output_tokens.unwrap_or(self.context_window)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A table of model capabilities, queryable at runtime.
+///
+/// `ModelRegistry::default()` holds the capabilities Groq documented for the models of `Model`. The live `/models`
+/// endpoint, listed with `list_models` on the clients, reports the context windows, output limits and availability of
+/// the current models but not their other capabilities: `update` merges such a listing into the registry, keeping the
+/// known capabilities.
+pub struct ModelRegistry {
+    models: HashMap<String, ModelInfo>,
+}
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        let models = [
+            (Model::Llama33_70bVersatile, 131_072, Some(32_768), true),
+            (Model::Llama31_8bInstant, 131_072, None, true),
+            (Model::Llama3_70b8192, 8192, None, true),
+            (Model::Llama3_8b8192, 8192, None, true),
+            (Model::Llama3Groq70bToolUse, 8192, None, true),
+            (Model::Llama3Groq8bToolUse, 8192, None, true),
+            (Model::LlamaGuard3_8b, 8192, None, false),
+            (Model::Gemma2_9bIt, 8192, None, true),
+            (Model::Mixtral8x7b32768, 32_768, None, true),
+        ];
+        let mut registry = Self::empty();
+        for (model, context_window, max_output_tokens, capable) in models {
+            registry = registry.model(ModelInfo {
+                max_output_tokens,
+                supports_tools: capable,
+                supports_json_mode: capable,
+                ..ModelInfo::new(model.as_str(), context_window)
+            });
+        }
+        for model in [
+            Model::WhisperLargeV3,
+            Model::WhisperLargeV3Turbo,
+            Model::DistilWhisperLargeV3En,
+        ] {
+            registry = registry.model(ModelInfo {
+                modality: Modality::Audio,
+                ..ModelInfo::new(model.as_str(), 448)
+            });
+        }
+        registry
+    }
+}
+
+impl ModelRegistry {
+    /// Returns the built-in registry, built once and shared by every caller.
+    pub(crate) fn builtin() -> &'static ModelRegistry {
+        static BUILTIN: OnceLock<ModelRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(ModelRegistry::default)
+    }
+
+    /// Creates an empty `ModelRegistry`.
+    pub fn empty() -> Self {
+        Self {
+            models: HashMap::new(),
+        }
+    }
+
+    /// Sets the capabilities of a model, replacing the existing ones.
+    ///
+    /// # Arguments
+    /// * `info` - The capabilities of the model.
+    pub fn model(mut self, info: ModelInfo) -> Self {
+        self.models.insert(info.id.clone(), info);
+        self
+    }
+
+    /// Returns the capabilities of a model, if known.
+    pub fn get(&self, model: impl AsRef<str>) -> Option<&ModelInfo> {
+        self.models.get(model.as_ref())
+    }
+
+    /// Returns the known models matching the predicate, sorted by name.
+    ///
+    /// # Arguments
+    /// * `predicate` - A function returning true for the models to keep, such as `|model| model.supports_tools`.
+    pub fn filter(&self, predicate: impl Fn(&ModelInfo) -> bool) -> Vec<&ModelInfo> {
+        let mut models: Vec<&ModelInfo> = self.models.values().filter(|m| predicate(m)).collect();
+        models.sort_by(|a, b| a.id.cmp(&b.id));
+        models
+    }
+
+    /// Merges a listing of the `/models` endpoint into the registry.
+    ///
+    /// Listed models update the context window, output limit and availability of the known ones, and are added with
+    /// default capabilities otherwise. Known models missing from the listing are marked as inactive.
+    ///
+    /// # Arguments
+    /// * `listing` - The models returned by `list_models`.
+    pub fn update(mut self, listing: Vec<ModelInfo>) -> Self {
+        for info in self.models.values_mut() {
+            info.active = false;
+        }
+        for listed in listing {
+            match self.models.get_mut(&listed.id) {
+                Some(info) => {
+                    info.context_window = listed.context_window;
+                    info.max_output_tokens = listed.max_output_tokens.or(info.max_output_tokens);
+                    info.active = listed.active;
+                }
+                None => {
+                    self.models.insert(listed.id.clone(), listed);
+                }
+            }
+        }
+        self
+    }
+}
+
+/// Parses the models of a `/models` response, skipping entries without a context window.
+pub(crate) fn parse_listing(body: &Value) -> Vec<ModelInfo> {
+    let entries = body["data"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let id = entry["id"].as_str()?;
+            let context_window = u32::try_from(entry["context_window"].as_u64()?).ok()?;
+            let audio = id.contains("whisper");
+            Some(ModelInfo {
+                max_output_tokens: entry["max_completion_tokens"]
+                    .as_u64()
+                    .and_then(|tokens| u32::try_from(tokens).ok()),
+                modality: if audio {
+                    Modality::Audio
+                } else {
+                    Modality::Text
+                },
+                active: entry["active"].as_bool().unwrap_or(true),
+                ..ModelInfo::new(id, context_window)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_model_registry() {
+        for model in Model::ALL {
+            assert_eq!(model.as_str().parse(), Ok(model));
+            assert_eq!(model.info().id, model.as_str());
+        }
+        let registry = ModelRegistry::default();
+        let audio = registry.filter(|model| model.modality == Modality::Audio);
+        assert_eq!(audio.len(), 3);
+        assert!(!registry.get(LLAMA_GUARD_3_8B).unwrap().supports_tools);
+
+        let listing = parse_listing(&json!({"object": "list", "data": [
+            {"id": "llama-3.3-70b-versatile", "context_window": 128000, "max_completion_tokens": 32768, "active": true},
+            {"id": "new-model", "context_window": 4096},
+            {"id": "broken-model"},
+        ]}));
+        assert_eq!(listing.len(), 2);
+        let registry = registry.update(listing);
+        let llama = registry.get(LLAMA_3_3_70B_VERSATILE).unwrap();
+        assert_eq!(llama.context_window, 128000);
+        assert!(llama.supports_tools && llama.active);
+        assert_eq!(registry.get("new-model").unwrap().output_limit(), 4096);
+        assert!(!registry.get(LLAMA3_8B_8192).unwrap().active);
+    }
+}
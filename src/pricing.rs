@@ -0,0 +1,114 @@
+use crate::Usage;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Represents the price of a model, in US dollars per million tokens.
+///
+/// - `input`: The price of one million prompt tokens.
+/// - `output`: The price of one million completion tokens.
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl ModelPrice {
+    /// Creates a new `ModelPrice`.
+    ///
+    /// # Arguments
+    /// * `input` - The price of one million prompt tokens.
+    /// * `output` - The price of one million completion tokens.
+    pub fn new(input: f64, output: f64) -> Self {
+        Self { input, output }
+    }
+
+    /// Returns the cost of the given number of prompt and completion tokens.
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        (prompt_tokens as f64 * self.input + completion_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A table of model prices used to estimate the cost of requests.
+///
+/// `PricingTable::default()` holds the on-demand prices Groq published for its chat models; they change over time,
+/// so override them with `price` when needed. Models missing from the table have no estimated cost.
+pub struct PricingTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        let prices = [
+            ("gemma-7b-it", 0.07, 0.07),
+            ("gemma2-9b-it", 0.20, 0.20),
+            ("llama-3.1-8b-instant", 0.05, 0.08),
+            ("llama-3.1-70b-versatile", 0.59, 0.79),
+            ("llama-3.3-70b-versatile", 0.59, 0.79),
+            ("llama3-8b-8192", 0.05, 0.08),
+            ("llama3-70b-8192", 0.59, 0.79),
+            ("mixtral-8x7b-32768", 0.24, 0.24),
+        ];
+        Self {
+            prices: prices
+                .into_iter()
+                .map(|(model, input, output)| (model.to_string(), ModelPrice::new(input, output)))
+                .collect(),
+        }
+    }
+}
+
+impl PricingTable {
+    /// Creates an empty `PricingTable`.
+    pub fn empty() -> Self {
+        Self {
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Sets the price of a model, replacing the existing one.
+    ///
+    /// # Arguments
+    /// * `model` - The model the price applies to.
+    /// * `price` - The price of the model.
+    pub fn price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.prices.insert(model.into(), price);
+        self
+    }
+
+    /// Returns the price of a model, if known.
+    pub fn get(&self, model: impl AsRef<str>) -> Option<ModelPrice> {
+        self.prices.get(model.as_ref()).copied()
+    }
+
+    /// Returns the estimated cost of the given usage of a model, if its price is known.
+    pub fn cost(&self, model: impl AsRef<str>, usage: &Usage) -> Option<f64> {
+        self.get(model)
+            .map(|price| price.cost(usage.prompt_tokens, usage.completion_tokens))
+    }
+}
+
+impl Usage {
+    /// Returns the estimated cost of this usage in US dollars, using the default `PricingTable`.
+    ///
+    /// # Arguments
+    /// * `model` - The model that reported this usage.
+    pub fn estimated_cost(&self, model: impl AsRef<str>) -> Option<f64> {
+        PricingTable::default().cost(model, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_table() {
+        let table = PricingTable::default().price("my-model", ModelPrice::new(1.0, 2.0));
+        assert_eq!(table.get("my-model").unwrap().cost(500_000, 250_000), 1.0);
+        assert_eq!(
+            table.get("llama3-70b-8192"),
+            Some(ModelPrice::new(0.59, 0.79))
+        );
+        assert_eq!(PricingTable::empty().get("llama3-70b-8192"), None);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::tokens::estimate_text_tokens;
+#[cfg(feature = "blocking")]
+use crate::ChatCompletionStream;
+use crate::{AsyncChatCompletionStream, ChatCompletionChunk, ChatCompletionRequest, GroqError};
+
+/// A snapshot of the progress of a streamed chat completion, for progress bars.
+///
+/// - `tokens`: The number of tokens generated so far, estimated from the streamed text until the last chunk reports
+///   the usage of the completion.
+/// - `max_tokens`: The `max_tokens` of the request, the most tokens the completion may take.
+/// - `finished`: Whether the model has finished generating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GenerationProgress {
+    pub tokens: u64,
+    pub max_tokens: u32,
+    pub finished: bool,
+}
+
+impl GenerationProgress {
+    /// Returns the estimated fraction of the completion generated so far, between 0 and 1.
+    ///
+    /// The fraction is taken against `max_tokens`, so an answer stopping early jumps to 1 when it finishes.
+    pub fn fraction(&self) -> f64 {
+        if self.finished {
+            return 1.0;
+        }
+        (self.tokens as f64 / f64::from(self.max_tokens.max(1))).min(1.0)
+    }
+
+    /// Counts the tokens of a chunk, replacing the estimate by the usage reported in the last chunk.
+    fn record(&mut self, chunk: &ChatCompletionChunk) {
+        for choice in &chunk.choices {
+            let arguments = choice.delta.tool_calls.iter().flatten().map(|call| {
+                let function = call.function.as_ref();
+                let name = function.and_then(|function| function.name.as_deref());
+                let arguments = function.and_then(|function| function.arguments.as_deref());
+                estimate_text_tokens(name.unwrap_or_default())
+                    + estimate_text_tokens(arguments.unwrap_or_default())
+            });
+            self.tokens +=
+                estimate_text_tokens(choice.delta.content.as_deref().unwrap_or_default())
+                    + arguments.sum::<u64>();
+            self.finished |= choice.finish_reason.is_some();
+        }
+        if let Some(usage) = chunk.usage() {
+            self.tokens = usage.completion_tokens;
+        }
+    }
+}
+
+/// A streamed chat completion returning the progress of the generation along with every chunk.
+///
+/// Built by `with_progress` on `ChatCompletionStream` or `AsyncChatCompletionStream`.
+pub struct ProgressStream<S> {
+    stream: S,
+    progress: GenerationProgress,
+}
+
+impl<S> ProgressStream<S> {
+    fn new(stream: S, request: &ChatCompletionRequest) -> Self {
+        Self {
+            stream,
+            progress: GenerationProgress {
+                tokens: 0,
+                max_tokens: request.max_tokens_for(&request.model),
+                finished: false,
+            },
+        }
+    }
+
+    /// Returns the progress after the chunks returned so far.
+    pub fn progress(&self) -> GenerationProgress {
+        self.progress
+    }
+
+    /// Returns the wrapped stream, to read the rest of it without progress.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl ChatCompletionStream {
+    /// Wraps the stream to return the progress of the generation along with every chunk.
+    ///
+    /// # Arguments
+    /// * `request` - The request the stream answers, whose `max_tokens` the progress is estimated against.
+    pub fn with_progress(self, request: &ChatCompletionRequest) -> ProgressStream<Self> {
+        ProgressStream::new(self, request)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Iterator for ProgressStream<ChatCompletionStream> {
+    type Item = Result<(ChatCompletionChunk, GenerationProgress), GroqError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.stream.next()?;
+        Some(chunk.map(|chunk| {
+            self.progress.record(&chunk);
+            (chunk, self.progress)
+        }))
+    }
+}
+
+impl AsyncChatCompletionStream {
+    /// Wraps the stream to return the progress of the generation along with every chunk.
+    ///
+    /// # Arguments
+    /// * `request` - The request the stream answers, whose `max_tokens` the progress is estimated against.
+    pub fn with_progress(self, request: &ChatCompletionRequest) -> ProgressStream<Self> {
+        ProgressStream::new(self, request)
+    }
+}
+
+impl ProgressStream<AsyncChatCompletionStream> {
+    /// Returns the next chunk and the progress after it, or `None` once the stream has ended.
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(
+        &mut self,
+    ) -> Option<Result<(ChatCompletionChunk, GenerationProgress), GroqError>> {
+        let chunk = self.stream.next().await?;
+        Some(chunk.map(|chunk| {
+            self.progress.record(&chunk);
+            (chunk, self.progress)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages;
+
+    #[tokio::test]
+    async fn test_progress_counts_streamed_tokens() {
+        let transcript = include_str!("../fixtures/chat_stream.sse");
+        let chunks = transcript
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .filter(|data| *data != "[DONE]")
+            .map(|data| serde_json::from_str::<ChatCompletionChunk>(data).unwrap());
+        let request = ChatCompletionRequest::new(
+            "llama-3.3-70b-versatile",
+            messages![user: "What is the capital of France?"],
+        )
+        .max_tokens(16);
+        let mut stream = AsyncChatCompletionStream::from_chunks(chunks).with_progress(&request);
+
+        let mut progress = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            progress.push(chunk.unwrap().1);
+        }
+        assert_eq!(progress.len(), 9);
+        assert_eq!((progress[0].tokens, progress[0].max_tokens), (0, 16));
+        assert_eq!(progress[2].tokens, 3);
+        assert_eq!(progress[2].fraction(), 3.0 / 16.0);
+        assert!(!progress[7].finished);
+        assert_eq!(progress[8].tokens, 8);
+        assert_eq!(progress[8].fraction(), 1.0);
+        assert_eq!(stream.progress(), progress[8]);
+    }
+}
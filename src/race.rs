@@ -0,0 +1,132 @@
+use crate::join::{first_ok, join_all};
+use crate::{ChatApi, ChatCompletionRequest, ChatCompletionResponse, GroqError};
+
+/// Sends the same request to several models concurrently and returns the first successful response.
+///
+/// The requests still pending once a response arrives are cancelled. The `model` field of the response tells which
+/// model won. Fallback models of the request are not used, as the race already covers failures.
+///
+/// # Arguments
+/// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+/// * `request` - The request, sent to its own model and to every other model.
+/// * `models` - The other models to race.
+///
+/// # Errors
+///
+/// Returns the error of the last request to fail if every request fails.
+pub async fn race(
+    client: &dyn ChatApi,
+    request: ChatCompletionRequest,
+    models: &[&str],
+) -> Result<ChatCompletionResponse, GroqError> {
+    first_ok(
+        contenders(request, models)
+            .into_iter()
+            .map(|request| client.chat_completion(request)),
+    )
+    .await
+}
+
+/// Sends the same request to several models concurrently and returns all their results, for comparison.
+///
+/// # Arguments
+/// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+/// * `request` - The request, sent to its own model and to every other model.
+/// * `models` - The other models to compare.
+///
+/// # Returns
+///
+/// The model and the result of every request, starting with the model of `request`.
+pub async fn compare(
+    client: &dyn ChatApi,
+    request: ChatCompletionRequest,
+    models: &[&str],
+) -> Vec<(String, Result<ChatCompletionResponse, GroqError>)> {
+    join_all(
+        contenders(request, models)
+            .into_iter()
+            .map(|request| async move {
+                let model = request.model.clone();
+                (model, client.chat_completion(request).await)
+            }),
+    )
+    .await
+}
+
+/// Returns a copy of the request for its own model and every other model, without fallback models.
+fn contenders(mut request: ChatCompletionRequest, models: &[&str]) -> Vec<ChatCompletionRequest> {
+    request.fallback_models.clear();
+    let own = request.model.clone();
+    std::iter::once(own)
+        .chain(models.iter().map(|model| model.to_string()))
+        .map(|model| {
+            let mut request = request.clone();
+            request.model = model;
+            request
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages;
+    use crate::test_support::{reply, server_error};
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Answers after a delay depending on the model, and fails for the `broken` model.
+    struct Models;
+
+    #[async_trait]
+    impl ChatApi for Models {
+        async fn chat_completion(
+            &self,
+            request: ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, GroqError> {
+            let delay = match request.model.as_str() {
+                "fast" => 10,
+                "broken" => 0,
+                _ => 200,
+            };
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            match request.model.as_str() {
+                "broken" => Err(server_error()),
+                model => reply(model, "Hi"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_race_returns_first_success() {
+        let request = ChatCompletionRequest::new("slow", messages![user: "Hello"]);
+        let winner = race(&Models, request.clone(), &["broken", "fast"])
+            .await
+            .unwrap();
+        assert_eq!(winner.model, "fast");
+        assert!(
+            race(&Models, request.clone().fallback_models(["fast"]), &[])
+                .await
+                .is_ok_and(|response| response.model == "slow")
+        );
+
+        let results = compare(&Models, request, &["broken", "fast"]).await;
+        let models: Vec<&str> = results.iter().map(|(model, _)| model.as_str()).collect();
+        assert_eq!(models, vec!["slow", "broken", "fast"]);
+        assert!(results[1].1.is_err() && results[2].1.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_race_fails_when_every_model_fails() {
+        let request = ChatCompletionRequest::new("broken", messages![user: "Hello"]);
+        assert!(matches!(
+            race(&Models, request.clone(), &["broken"]).await,
+            Err(GroqError::ServerError { status: 500, .. })
+        ));
+
+        let results = compare(&Models, request.fallback_models(["fast"]), &[]).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "broken");
+        assert!(results[0].1.is_err());
+    }
+}
@@ -0,0 +1,262 @@
+use crate::{count_tokens, ChatCompletionRequest};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Represents the request and token budgets allowed per minute for a model.
+///
+/// - `requests_per_minute`: The maximum number of requests per minute, or `None` for no limit.
+/// - `tokens_per_minute`: The maximum number of tokens per minute, or `None` for no limit.
+pub struct RateLimit {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl RateLimit {
+    /// Creates a new `RateLimit` with the given budgets.
+    ///
+    /// # Arguments
+    /// * `requests_per_minute` - The maximum number of requests per minute, or `None` for no limit.
+    /// * `tokens_per_minute` - The maximum number of tokens per minute, or `None` for no limit.
+    pub fn new(requests_per_minute: Option<u32>, tokens_per_minute: Option<u32>) -> Self {
+        Self {
+            requests_per_minute,
+            tokens_per_minute,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A client-side token bucket limiter keeping requests within per-model budgets.
+///
+/// Clients configured with a `RateLimiter` wait until the model has enough capacity before sending a request,
+/// so that bursts are smoothed out locally instead of being rejected with `429 Too Many Requests`.
+/// Cloning a `RateLimiter` shares its state, so one limiter can be used by several clients.
+///
+/// A chat completion is charged the estimated prompt size plus its `max_tokens`, which is the most the request can consume.
+/// Requests marked with `RequestPriority::Background` only use spare capacity, so batch jobs sharing a client never
+/// delay interactive traffic.
+pub struct RateLimiter {
+    default_limit: RateLimit,
+    model_limits: Arc<HashMap<String, RateLimit>>,
+    buckets: Arc<Mutex<HashMap<String, ModelBuckets>>>,
+}
+
+impl RateLimiter {
+    /// Creates a new `RateLimiter` applying the given budgets to every model.
+    ///
+    /// # Arguments
+    /// * `requests_per_minute` - The maximum number of requests per minute, or `None` for no limit.
+    /// * `tokens_per_minute` - The maximum number of tokens per minute, or `None` for no limit.
+    pub fn new(requests_per_minute: Option<u32>, tokens_per_minute: Option<u32>) -> Self {
+        Self {
+            default_limit: RateLimit::new(requests_per_minute, tokens_per_minute),
+            model_limits: Arc::default(),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sets the budgets of a specific model, overriding the default ones.
+    ///
+    /// # Arguments
+    /// * `model` - The model the budgets apply to.
+    /// * `limit` - The budgets of the model.
+    pub fn model_limit(mut self, model: impl Into<String>, limit: RateLimit) -> Self {
+        Arc::make_mut(&mut self.model_limits).insert(model.into(), limit);
+        self
+    }
+
+    /// Reserves capacity for one request of the given number of tokens.
+    ///
+    /// Interactive requests take their capacity immediately, possibly going into debt; the granted duration is how long
+    /// the caller must wait before sending the request so that the budgets are respected. Background requests only take
+    /// capacity that is actually available, so they never delay interactive requests; when denied, the caller should try
+    /// again after the returned duration.
+    pub(crate) fn reserve(
+        &self,
+        model: &str,
+        tokens: u64,
+        priority: RequestPriority,
+    ) -> Reservation {
+        let limit = self
+            .model_limits
+            .get(model)
+            .copied()
+            .unwrap_or(self.default_limit);
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        let buckets = buckets
+            .entry(model.to_string())
+            .or_insert_with(|| ModelBuckets::new(limit, now));
+        let tokens = tokens as f64;
+
+        if priority == RequestPriority::Background {
+            let shortfall = |bucket: &mut Option<Bucket>, amount: f64| {
+                bucket
+                    .as_mut()
+                    .map_or(Duration::ZERO, |bucket| bucket.shortfall(amount, now))
+            };
+            let wait =
+                shortfall(&mut buckets.requests, 1.0).max(shortfall(&mut buckets.tokens, tokens));
+            if !wait.is_zero() {
+                return Reservation::Denied(wait);
+            }
+        }
+
+        let requests = buckets
+            .requests
+            .as_mut()
+            .map_or(Duration::ZERO, |bucket| bucket.take(1.0, now));
+        let tokens = buckets
+            .tokens
+            .as_mut()
+            .map_or(Duration::ZERO, |bucket| bucket.take(tokens, now));
+        Reservation::Granted(requests.max(tokens))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// Represents how urgently a request must be sent when a `RateLimiter` is configured.
+///
+/// - `Interactive`: Latency-sensitive traffic, served first. This is the default.
+/// - `Background`: Batch traffic, only sent with capacity that no interactive request is waiting for.
+pub enum RequestPriority {
+    #[default]
+    Interactive,
+    Background,
+}
+
+/// The outcome of `RateLimiter::reserve`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reservation {
+    /// The capacity was taken; the request may be sent after the given duration.
+    Granted(Duration),
+    /// No capacity was taken; the reservation should be attempted again after the given duration.
+    Denied(Duration),
+}
+
+/// Estimates the number of tokens a chat completion request may consume.
+///
+/// The prompt is estimated with `count_tokens`, to which the `max_tokens` sent for the model is added.
+pub(crate) fn estimate_request_tokens(request: &ChatCompletionRequest) -> u64 {
+    count_tokens(request) + u64::from(request.max_tokens_for(&request.model))
+}
+
+#[derive(Debug)]
+struct ModelBuckets {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+}
+
+impl ModelBuckets {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            requests: limit.requests_per_minute.map(|rpm| Bucket::new(rpm, now)),
+            tokens: limit.tokens_per_minute.map(|tpm| Bucket::new(tpm, now)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: u32, now: Instant) -> Self {
+        let capacity = f64::from(per_minute.max(1));
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_sec: capacity / 60.0,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Returns how long to wait until `amount` (capped at the capacity) is available, without taking it.
+    fn shortfall(&mut self, amount: f64, now: Instant) -> Duration {
+        self.refill(now);
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.refill_per_sec)
+        }
+    }
+
+    /// Takes `amount` from the bucket and returns how long to wait until the resulting debt is repaid.
+    fn take(&mut self, amount: f64, now: Instant) -> Duration {
+        self.refill(now);
+        self.available -= amount;
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / self.refill_per_sec)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn granted(reservation: Reservation) -> Duration {
+        match reservation {
+            Reservation::Granted(wait) => wait,
+            Reservation::Denied(_) => panic!("reservation was denied"),
+        }
+    }
+
+    #[test]
+    fn test_requests_wait_once_budget_is_spent() {
+        let limiter = RateLimiter::new(Some(60), None);
+        let reserve = |model| limiter.reserve(model, 0, RequestPriority::Interactive);
+        assert_eq!(granted(reserve("llama3-8b-8192")), Duration::ZERO);
+        for _ in 0..59 {
+            reserve("llama3-8b-8192");
+        }
+        let wait = granted(reserve("llama3-8b-8192"));
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+        assert_eq!(granted(reserve("llama3-70b-8192")), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_model_limit_overrides_default() {
+        let limiter = RateLimiter::new(None, Some(1_000_000))
+            .model_limit("llama3-70b-8192", RateLimit::new(None, Some(6000)));
+        let reserve = |model, tokens| limiter.reserve(model, tokens, RequestPriority::Interactive);
+        assert_eq!(granted(reserve("llama3-70b-8192", 6000)), Duration::ZERO);
+        let wait = granted(reserve("llama3-70b-8192", 3000));
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+        assert_eq!(granted(reserve("llama3-8b-8192", 6000)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_background_requests_yield_to_interactive_ones() {
+        let limiter = RateLimiter::new(Some(60), None);
+        let model = "llama3-8b-8192";
+        assert_eq!(
+            limiter.reserve(model, 0, RequestPriority::Background),
+            Reservation::Granted(Duration::ZERO)
+        );
+        for _ in 0..59 {
+            granted(limiter.reserve(model, 0, RequestPriority::Interactive));
+        }
+        // Interactive requests may borrow future capacity, background ones have to wait for it.
+        assert!(matches!(
+            limiter.reserve(model, 0, RequestPriority::Background),
+            Reservation::Denied(_)
+        ));
+        granted(limiter.reserve(model, 0, RequestPriority::Interactive));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::GroqError;
+use serde_json::Value;
+
+/// The JSON fields holding message content, prompts or transcriptions.
+const CONTENT_FIELDS: [&str; 4] = ["content", "text", "prompt", "arguments"];
+
+/// Returns a placeholder standing for the given text, keeping its length and a stable hash so equal contents can still
+/// be correlated.
+pub(crate) fn placeholder(text: &str) -> String {
+    // 64-bit FNV-1a, which is stable across builds unlike the standard library hasher.
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("[REDACTED len={} hash={hash:016x}]", text.chars().count())
+}
+
+/// Replaces the content fields of a JSON value, at any depth, with placeholders.
+pub(crate) fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match value {
+                    Value::String(text) if CONTENT_FIELDS.contains(&key.as_str()) => {
+                        *text = placeholder(text);
+                    }
+                    value => redact_json(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Redacts the content fields of a JSON document, or the whole text if it is not valid JSON.
+pub(crate) fn redact_text(text: &str) -> String {
+    match serde_json::from_str::<Value>(text) {
+        Ok(mut value) => {
+            redact_json(&mut value);
+            value.to_string()
+        }
+        Err(_) => placeholder(text),
+    }
+}
+
+/// Masks email addresses and card numbers in the given text, for use with `scrub_content`.
+///
+/// Email addresses are replaced by `[EMAIL]`, and runs of 13 to 19 digits (optionally grouped with spaces or dashes)
+/// passing the Luhn check by `[CARD]`.
+///
+/// # Arguments
+/// * `text` - The text to scrub.
+pub fn mask_pii(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut masked = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(end) = email_end(&chars, i) {
+            masked.push_str("[EMAIL]");
+            i = end;
+        } else if let Some(end) = card_end(&chars, i) {
+            masked.push_str("[CARD]");
+            i = end;
+        } else {
+            masked.push(chars[i]);
+            i += 1;
+        }
+    }
+    masked
+}
+
+/// Returns the end of the email address starting at `start`, if any.
+fn email_end(chars: &[char], start: usize) -> Option<usize> {
+    let is_local = |c: char| c.is_alphanumeric() || "._%+-".contains(c);
+    if start > 0 && is_local(chars[start - 1]) {
+        return None;
+    }
+    let at = start + chars[start..].iter().take_while(|c| is_local(**c)).count();
+    if at == start || chars.get(at) != Some(&'@') {
+        return None;
+    }
+    let domain = &chars[at + 1..];
+    let mut len = domain
+        .iter()
+        .take_while(|c| c.is_alphanumeric() || **c == '-' || **c == '.')
+        .count();
+    while len > 0 && domain[len - 1] == '.' {
+        len -= 1;
+    }
+    let domain: String = domain[..len].iter().collect();
+    let valid = domain.contains('.') && domain.split('.').all(|label| !label.is_empty());
+    valid.then_some(at + 1 + len)
+}
+
+/// Returns the end of the card number starting at `start`, if any.
+fn card_end(chars: &[char], start: usize) -> Option<usize> {
+    if !chars[start].is_ascii_digit() || (start > 0 && chars[start - 1].is_ascii_digit()) {
+        return None;
+    }
+    let mut digits = Vec::new();
+    let mut end = start;
+    let mut i = start;
+    while i < chars.len() {
+        if let Some(digit) = chars[i].to_digit(10) {
+            digits.push(digit);
+            end = i + 1;
+            i += 1;
+        } else if matches!(chars[i], ' ' | '-')
+            && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())
+        {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    ((13..=19).contains(&digits.len()) && luhn(&digits)).then_some(end)
+}
+
+fn luhn(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match (i % 2 == 1, digit * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => digit,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+impl GroqError {
+    /// Replaces the message content held by the error, such as a response body echoing the prompt, with placeholders.
+    pub(crate) fn redact_content(mut self) -> Self {
+        self.redact_in_place();
+        self
+    }
+
+    fn redact_in_place(&mut self) {
+        match self {
+            GroqError::ApiError { body, .. } => redact_json(body),
+            GroqError::Unauthorized(payload)
+            | GroqError::Forbidden(payload)
+            | GroqError::NotFound(payload)
+            | GroqError::UnprocessableEntity(payload)
+            | GroqError::RateLimited { payload, .. }
+            | GroqError::ServerError { payload, .. } => redact_json(&mut payload.body),
+            // The payload is truncated, so it usually cannot be parsed.
+            GroqError::DeserializationError { payload, .. } => *payload = placeholder(payload),
+            GroqError::UnexpectedAnswer { answer, .. } => *answer = placeholder(answer),
+            GroqError::DeadlineExceeded { last_error, .. } => last_error.redact_in_place(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mask_pii() {
+        assert_eq!(
+            mask_pii("Mail jane.doe+groq@example.co.uk, or pay with 4111 1111 1111 1111."),
+            "Mail [EMAIL], or pay with [CARD]."
+        );
+        assert_eq!(mask_pii("card 4111-1111-1111-1111"), "card [CARD]");
+        // Numbers failing the Luhn check, and addresses without a domain, are kept.
+        assert_eq!(
+            mask_pii("order 1234567890123, user@localhost"),
+            "order 1234567890123, user@localhost"
+        );
+    }
+
+    #[test]
+    fn test_redact_json() {
+        let mut body = json!({
+            "model": "llama3-8b-8192",
+            "messages": [{"role": "user", "content": "My name is Jane"}]
+        });
+        redact_json(&mut body);
+        let content = body["messages"][0]["content"].as_str().unwrap();
+        assert!(content.starts_with("[REDACTED len=15 hash="));
+        assert_eq!(content, placeholder("My name is Jane"));
+        assert_eq!(body["model"], "llama3-8b-8192");
+        assert!(redact_text("not json").starts_with("[REDACTED len=8 "));
+    }
+}
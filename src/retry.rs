@@ -0,0 +1,319 @@
+use crate::GroqError;
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+/// Decides whether and when failed requests are retried by `GroqClient` and `AsyncGroqClient`.
+///
+/// `ExponentialBackoff` is the built-in policy. Closures taking the attempt number and the error also implement this
+/// trait, so existing policies (such as ones from the `backoff` crate) can be wrapped without a new type.
+pub trait RetryPolicy: Send + Sync {
+    /// Returns how long to wait before retrying, or `None` to give up and return the error.
+    ///
+    /// # Arguments
+    /// * `attempt` - The number of the failed attempt, starting at 1.
+    /// * `error` - The error returned by the failed attempt.
+    fn decide(&self, attempt: u32, error: &GroqError) -> Option<Duration>;
+}
+
+impl<F> RetryPolicy for F
+where
+    F: Fn(u32, &GroqError) -> Option<Duration> + Send + Sync,
+{
+    fn decide(&self, attempt: u32, error: &GroqError) -> Option<Duration> {
+        self(attempt, error)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A `RetryPolicy` retrying transient failures with exponential backoff.
+///
+/// Delays grow exponentially from `base_delay` (doubling on every attempt) and are capped at `max_delay`.
+/// When `jitter` is enabled the delay is randomized between 50% and 100% of the computed value,
+/// so that many clients failing at the same time don't retry in lockstep.
+///
+/// - `max_attempts`: The total number of attempts, including the first one.
+/// - `base_delay`: The delay before the first retry.
+/// - `max_delay`: The upper bound for any single delay.
+/// - `jitter`: Whether to randomize delays.
+/// - `retry_on_rate_limit`: Whether `429 Too Many Requests` responses are retried.
+/// - `retry_on_server_error`: Whether `5xx` responses are retried.
+/// - `retry_on_connect_error`: Whether connection failures and timeouts are retried.
+/// - `respect_retry_after`: Whether the wait time requested by the API (through the `retry-after` and
+///   `retry-after-ms` headers or a "try again in" error message) is used instead of the computed delay.
+///   The requested wait is still capped at `max_delay`.
+pub struct ExponentialBackoff {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+    pub retry_on_rate_limit: bool,
+    pub retry_on_server_error: bool,
+    pub retry_on_connect_error: bool,
+    pub respect_retry_after: bool,
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            retry_on_rate_limit: true,
+            retry_on_server_error: true,
+            retry_on_connect_error: true,
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl ExponentialBackoff {
+    /// Creates a new `ExponentialBackoff` with the given number of attempts and default values for other fields.
+    ///
+    /// # Arguments
+    /// * `max_attempts` - The total number of attempts, including the first one.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the delay before the first retry.
+    ///
+    /// # Arguments
+    /// * `base_delay` - The delay before the first retry.
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Sets the upper bound for any single delay.
+    ///
+    /// # Arguments
+    /// * `max_delay` - The maximum delay between two attempts.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets whether delays are randomized.
+    ///
+    /// # Arguments
+    /// * `jitter` - If true, delays are randomized between 50% and 100% of the computed value.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets whether `429 Too Many Requests` responses are retried.
+    pub fn retry_on_rate_limit(mut self, retry: bool) -> Self {
+        self.retry_on_rate_limit = retry;
+        self
+    }
+
+    /// Sets whether `5xx` responses are retried.
+    pub fn retry_on_server_error(mut self, retry: bool) -> Self {
+        self.retry_on_server_error = retry;
+        self
+    }
+
+    /// Sets whether connection failures and timeouts are retried.
+    pub fn retry_on_connect_error(mut self, retry: bool) -> Self {
+        self.retry_on_connect_error = retry;
+        self
+    }
+
+    /// Sets whether the wait time requested by the API is used instead of the computed delay.
+    pub fn respect_retry_after(mut self, respect: bool) -> Self {
+        self.respect_retry_after = respect;
+        self
+    }
+
+    /// Returns the delay to wait after the given failed attempt, preferring the wait time requested by the API.
+    pub(crate) fn delay_with_hint(&self, attempt: u32, hint: Option<Duration>) -> Duration {
+        match hint {
+            Some(hint) if self.respect_retry_after => hint.min(self.max_delay),
+            _ => self.delay_for(attempt),
+        }
+    }
+
+    /// Returns the delay to wait after the given failed attempt (starting at 1).
+    pub(crate) fn delay_for(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1u32 << exp)
+            .min(self.max_delay);
+        if self.jitter {
+            delay.mul_f64(0.5 + crate::random::fraction() / 2.0)
+        } else {
+            delay
+        }
+    }
+
+    /// Returns true if the given error should be retried according to this policy.
+    ///
+    /// # Arguments
+    /// * `error` - The error returned by the failed attempt.
+    pub fn should_retry(&self, error: &GroqError) -> bool {
+        if !error.is_retryable() {
+            false
+        } else if error.is_rate_limit() {
+            self.retry_on_rate_limit
+        } else if matches!(
+            error,
+            GroqError::RequestFailed { .. } | GroqError::Timeout { .. }
+        ) {
+            self.retry_on_connect_error
+        } else {
+            self.retry_on_server_error
+        }
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn decide(&self, attempt: u32, error: &GroqError) -> Option<Duration> {
+        (attempt < self.max_attempts && self.should_retry(error))
+            .then(|| self.delay_with_hint(attempt, error.retry_after()))
+    }
+}
+
+/// Reads the wait time requested through the `retry-after-ms` or `retry-after` headers.
+///
+/// Only the delay-seconds form of `retry-after` is supported, fractional values included.
+pub(crate) fn retry_after_header(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<f64>().ok();
+    let duration =
+        |secs: f64| (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs));
+
+    header("retry-after-ms")
+        .and_then(|ms| duration(ms / 1000.0))
+        .or_else(|| header("retry-after").and_then(duration))
+}
+
+/// Extracts the wait time from a rate limit message such as "Please try again in 7m12.5s.".
+pub(crate) fn retry_after_message(message: &str) -> Option<Duration> {
+    let (_, rest) = message.split_once("try again in ")?;
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | 'h' | 'm' | 's')))
+        .unwrap_or(rest.len());
+    parse_duration(rest[..end].trim_end_matches('.'))
+}
+
+/// Parses durations like `1h2m3.5s`, `450ms` or `12s`.
+pub(crate) fn parse_duration(text: &str) -> Option<Duration> {
+    if text.is_empty() {
+        return None;
+    }
+    let mut total = 0.0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let value: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let (scale, unit_len) = if rest.starts_with("ms") {
+            (0.001, 2)
+        } else if rest.starts_with('h') {
+            (3600.0, 1)
+        } else if rest.starts_with('m') {
+            (60.0, 1)
+        } else if rest.starts_with('s') {
+            (1.0, 1)
+        } else {
+            return None;
+        };
+        total += value * scale;
+        rest = &rest[unit_len..];
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_delay_grows_and_is_capped() {
+        let policy = ExponentialBackoff::new(10)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1))
+            .jitter(false);
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(400));
+        assert_eq!(policy.delay_for(8), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let policy = ExponentialBackoff::new(3).base_delay(Duration::from_millis(100));
+        for _ in 0..100 {
+            let delay = policy.delay_for(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_retry_after_hints() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", "2".parse().unwrap());
+        assert_eq!(retry_after_header(&headers), Some(Duration::from_secs(2)));
+        headers.insert("retry-after-ms", "1500".parse().unwrap());
+        assert_eq!(
+            retry_after_header(&headers),
+            Some(Duration::from_millis(1500))
+        );
+
+        let message = "Rate limit reached for model `llama3-70b-8192`. Please try again in 7m12.5s. Visit ...";
+        assert_eq!(
+            retry_after_message(message),
+            Some(Duration::from_secs_f64(432.5))
+        );
+        assert_eq!(
+            retry_after_message("Please try again in 450ms."),
+            Some(Duration::from_millis(450))
+        );
+        assert_eq!(retry_after_message("Unknown error"), None);
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        let headers = HeaderMap::new();
+        let rate_limited = GroqError::from_body(StatusCode::TOO_MANY_REQUESTS, &headers, "");
+        let bad_gateway = GroqError::from_body(StatusCode::BAD_GATEWAY, &headers, "");
+        let bad_request = GroqError::from_body(StatusCode::BAD_REQUEST, &headers, "");
+
+        let policy = ExponentialBackoff::default();
+        assert!(policy.should_retry(&rate_limited));
+        assert!(policy.should_retry(&bad_gateway));
+        assert!(!policy.should_retry(&bad_request));
+        assert!(!policy
+            .clone()
+            .retry_on_rate_limit(false)
+            .should_retry(&rate_limited));
+
+        let policy = ExponentialBackoff::new(2).jitter(false);
+        assert_eq!(
+            policy.decide(1, &bad_gateway),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(policy.decide(2, &bad_gateway), None);
+        assert_eq!(policy.decide(1, &bad_request), None);
+    }
+
+    #[test]
+    fn test_closure_policy() {
+        let policy = |attempt: u32, error: &GroqError| {
+            (attempt < 4 && error.is_rate_limit()).then_some(Duration::from_secs(1))
+        };
+        let rate_limited =
+            GroqError::from_body(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new(), "");
+        assert_eq!(
+            policy.decide(3, &rate_limited),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(policy.decide(4, &rate_limited), None);
+    }
+}
@@ -0,0 +1,716 @@
+use crate::budget::BudgetReservation;
+use crate::message::deserialize;
+use crate::telemetry::{Recorder, StreamSpan};
+use crate::usage::UsageTracker;
+#[cfg(feature = "blocking")]
+use crate::RequestContext;
+use crate::{
+    AuditEntry, AuditSink, ChatCompletionRoles, GroqError, ResponseMeta, Usage, UsageLog,
+    UsageRecord, XGroq,
+};
+use bytes::{Bytes, BytesMut};
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+#[cfg(feature = "sse")]
+use std::convert::Infallible;
+#[cfg(feature = "blocking")]
+use std::io::Read;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Represents one server-sent event of a streamed chat completion.
+///
+/// - `id`: The unique identifier of the completion, shared by all its chunks.
+/// - `model`: The name of the model generating the completion.
+/// - `created`: The timestamp (in seconds since the epoch) when the completion was created.
+/// - `choices`: The new tokens of every choice.
+/// - `x_groq`: Additional metadata about the completion, holding the usage in the last chunk on Groq.
+/// - `extra`: Fields of the chunk not modelled by this type, keyed by name.
+pub struct ChatCompletionChunk {
+    pub id: String,
+    pub model: String,
+    pub created: u64,
+    pub choices: Vec<ChunkChoice>,
+    pub x_groq: Option<XGroq>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl ChatCompletionChunk {
+    /// Returns the new content of the first choice, or an empty string if it has none.
+    pub fn content(&self) -> &str {
+        self.choices
+            .first()
+            .and_then(|choice| choice.delta.content.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Returns the usage of the completion, reported by the last chunk.
+    pub fn usage(&self) -> Option<Usage> {
+        self.x_groq
+            .as_ref()
+            .and_then(|x_groq| x_groq.extra.get("usage"))
+            .or_else(|| self.extra.get("usage"))
+            .and_then(|usage| Usage::deserialize(usage).ok())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Represents the new tokens of one choice of a streamed chat completion.
+///
+/// - `index`: The index of the choice within the list of choices.
+/// - `delta`: The part of the message generated since the previous chunk.
+/// - `finish_reason`: The reason the generation finished, such as "stop" or "length", in the last chunk of the choice.
+pub struct ChunkChoice {
+    pub index: u64,
+    pub delta: Delta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Represents the part of a message generated since the previous chunk.
+///
+/// - `role`: The role of the message, sent with the first chunk.
+/// - `content`: The new text of the message.
+/// - `tool_calls`: The fragments of the tool calls generated since the previous chunk, if any.
+pub struct Delta {
+    pub role: Option<ChatCompletionRoles>,
+    pub content: Option<String>,
+    pub tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Represents a fragment of a streamed tool call.
+///
+/// A tool call may be streamed over several chunks, whose fragments share its `index`: the first one holds its ID,
+/// type and function name, and the arguments are split across the fragments, to be concatenated in order.
+///
+/// - `index`: The index of the tool call within the message.
+/// - `id`: The ID of the call, in its first fragment.
+/// - `type_`: The type of the tool, always `function`, in its first fragment.
+/// - `function`: The part of the name and arguments of the call in this fragment.
+pub struct ToolCallDelta {
+    pub index: u64,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default, rename = "type")]
+    pub type_: Option<String>,
+    #[serde(default)]
+    pub function: Option<FunctionCallDelta>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Represents the part of the function of a `ToolCallDelta` in one fragment.
+///
+/// - `name`: The name of the tool, in the first fragment.
+/// - `arguments`: The next part of the arguments of the call.
+pub struct FunctionCallDelta {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arguments: Option<String>,
+}
+
+/// Splits the body of a streamed response into the payloads of its `data:` lines.
+///
+/// The body is copied once into `pending`; complete lines are split off it and their payloads are slices of the same
+/// buffer, so queueing an event does not allocate.
+struct EventBuffer {
+    pending: BytesMut,
+    events: VecDeque<Bytes>,
+    done: bool,
+}
+
+impl EventBuffer {
+    fn new() -> Self {
+        Self {
+            pending: BytesMut::new(),
+            events: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Appends bytes of the body, queueing the payloads of the lines they complete.
+    fn push(&mut self, bytes: &[u8]) {
+        let searched = self.pending.len();
+        self.pending.extend_from_slice(bytes);
+        let mut from = searched;
+        while let Some(end) = self.pending[from..].iter().position(|&byte| byte == b'\n') {
+            let line = self.pending.split_to(from + end + 1).freeze();
+            from = 0;
+            let Some(data) = line.trim_ascii_end().strip_prefix(b"data:") else {
+                continue;
+            };
+            match data.trim_ascii() {
+                b"[DONE]" => self.done = true,
+                data if !self.done => self.events.push_back(line.slice_ref(data)),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Decodes the body of a streamed response read `read_size` bytes at a time, as the streams do, into its chunks.
+#[cfg(feature = "bench")]
+pub(crate) fn decode_transcript(
+    transcript: &[u8],
+    read_size: usize,
+) -> Result<Vec<ChatCompletionChunk>, GroqError> {
+    let mut buffer = EventBuffer::new();
+    let mut chunks = Vec::new();
+    for bytes in transcript.chunks(read_size) {
+        buffer.push(bytes);
+        while let Some(data) = buffer.events.pop_front() {
+            chunks.push(parse_event(&data, StatusCode::OK, &HeaderMap::new())?);
+        }
+        if buffer.done {
+            break;
+        }
+    }
+    Ok(chunks)
+}
+
+/// Parses the payload of an event into a chunk, or into the error the API reported in it.
+fn parse_event(
+    data: &[u8],
+    status: StatusCode,
+    headers: &HeaderMap,
+) -> Result<ChatCompletionChunk, GroqError> {
+    if let Ok(chunk) = serde_json::from_slice(data) {
+        return Ok(chunk);
+    }
+    let value: Value = serde_json::from_slice(data)?;
+    if value.get("error").is_some() {
+        return Err(GroqError::from_response(status, headers, value));
+    }
+    deserialize(value)
+}
+
+/// The accounting of a streamed chat completion, done once the stream ends as it is for `chat_completion` once the
+/// response is read: the budget is settled from the usage reported by the last chunk, and the call is recorded by the
+/// usage tracker, the telemetry, the usage log and the audit sink.
+///
+/// - `model`: The requested model.
+/// - `started`: When the call started.
+/// - `reservation`: The tokens reserved against the client's `Budget`, refunded if the stream never opens.
+/// - `usage`: The usage tracker of the client.
+/// - `usage_log`: The usage log of the client, if any.
+/// - `audit`: The audit sink of the client, with the audit context and the hash of the request, if any.
+/// - `telemetry`: The metric sinks of the client.
+/// - `span`: The span of the call.
+pub(crate) struct StreamBookkeeping {
+    pub(crate) model: String,
+    pub(crate) started: Instant,
+    pub(crate) reservation: Option<BudgetReservation>,
+    pub(crate) usage: UsageTracker,
+    pub(crate) usage_log: Option<UsageLog>,
+    pub(crate) audit: Option<(Arc<dyn AuditSink>, Option<String>, String)>,
+    pub(crate) telemetry: Recorder,
+    pub(crate) span: StreamSpan,
+    time_to_first_byte: Option<Duration>,
+    chunks: u64,
+    reported: Option<Usage>,
+}
+
+impl StreamBookkeeping {
+    /// Creates the bookkeeping of a call without a budget reservation, to be opened once its response headers are
+    /// received.
+    pub(crate) fn new(
+        model: String,
+        started: Instant,
+        usage: UsageTracker,
+        usage_log: Option<UsageLog>,
+        audit: Option<(Arc<dyn AuditSink>, Option<String>, String)>,
+        telemetry: Recorder,
+        span: StreamSpan,
+    ) -> Self {
+        Self {
+            model,
+            started,
+            reservation: None,
+            usage,
+            usage_log,
+            audit,
+            telemetry,
+            span,
+            time_to_first_byte: None,
+            chunks: 0,
+            reported: None,
+        }
+    }
+
+    /// Marks the stream as opened after the given time to its response headers.
+    pub(crate) fn open(&mut self, time_to_first_byte: Duration) {
+        self.time_to_first_byte = Some(time_to_first_byte);
+        self.span.opened();
+    }
+
+    /// Counts a chunk, keeping the usage reported by the last one.
+    fn record(&mut self, chunk: &ChatCompletionChunk) {
+        self.chunks += 1;
+        if let Some(usage) = chunk.usage() {
+            self.reported = Some(usage);
+        }
+    }
+
+    /// Settles the budget and records the call, from the metadata of its response or the error it failed with.
+    ///
+    /// A stream failing or dropped after it opened settles the budget at the estimate if no usage was reported.
+    pub(crate) fn finish(mut self, outcome: Result<ResponseMeta, &GroqError>, dropped: bool) {
+        let usage = self.reported.take();
+        let usage = usage.as_ref();
+        let elapsed = self.started.elapsed();
+        let outcome = outcome.map(|mut meta| {
+            meta.timing.time_to_first_byte = self.time_to_first_byte.unwrap_or_default();
+            meta.with_total(elapsed, usage)
+        });
+        if let Some(reservation) = self.reservation.take() {
+            if self.time_to_first_byte.is_some() {
+                reservation.settle(&self.model, usage);
+            }
+        }
+        if outcome.is_ok() {
+            self.span
+                .in_scope(|| self.telemetry.record_usage(&self.model, usage));
+            self.usage.record(&self.model, usage);
+        }
+        if let Some(log) = &self.usage_log {
+            let record = UsageRecord::new("/chat/completions", &self.model, elapsed);
+            log.write(&match &outcome {
+                Ok(meta) => record.success(meta, usage),
+                Err(err) => record.failure(err),
+            });
+        }
+        if let Some((sink, context, hash)) = self.audit.take() {
+            sink.record(&AuditEntry::from_outcome(
+                context.as_deref(),
+                "/chat/completions",
+                &self.model,
+                hash,
+                outcome.as_ref().map_err(|err| *err),
+            ));
+        }
+        self.span
+            .closed(self.chunks, outcome.as_ref().err().copied(), dropped);
+    }
+}
+
+/// Passes an item read from a stream to its bookkeeping, finishing it at the end of the stream or on an error.
+fn track(
+    bookkeeping: &mut Option<StreamBookkeeping>,
+    item: Option<Result<&ChatCompletionChunk, &GroqError>>,
+    status: StatusCode,
+    headers: &HeaderMap,
+) {
+    match item {
+        Some(Ok(chunk)) => {
+            if let Some(bookkeeping) = bookkeeping {
+                bookkeeping.record(chunk);
+            }
+        }
+        Some(Err(err)) => {
+            if let Some(bookkeeping) = bookkeeping.take() {
+                bookkeeping.finish(Err(err), false);
+            }
+        }
+        None => {
+            if let Some(bookkeeping) = bookkeeping.take() {
+                bookkeeping.finish(Ok(ResponseMeta::from_response(status, headers)), false);
+            }
+        }
+    }
+}
+
+/// Finishes the bookkeeping of a stream dropped before its end.
+fn drop_bookkeeping(
+    bookkeeping: &mut Option<StreamBookkeeping>,
+    status: StatusCode,
+    headers: &HeaderMap,
+) {
+    if let Some(bookkeeping) = bookkeeping.take() {
+        bookkeeping.finish(Ok(ResponseMeta::from_response(status, headers)), true);
+    }
+}
+
+#[cfg(feature = "blocking")]
+/// Converts an error reading a blocking response back into the `reqwest` error it wraps, or into a `ReadFailed` error.
+fn read_error(err: std::io::Error, url: &reqwest::Url) -> GroqError {
+    match err.downcast::<reqwest::Error>() {
+        Ok(err) => GroqError::from(err),
+        Err(source) => GroqError::ReadFailed {
+            source,
+            context: RequestContext {
+                url: Some(url.to_string()),
+                ..RequestContext::default()
+            },
+        },
+    }
+}
+
+#[cfg(feature = "blocking")]
+/// A streamed chat completion, returned by `GroqClient::chat_completion_stream`.
+///
+/// Iterating reads the response as it arrives and yields its chunks, until the API marks the end of the stream.
+pub struct ChatCompletionStream {
+    response: reqwest::blocking::Response,
+    status: StatusCode,
+    headers: HeaderMap,
+    buffer: EventBuffer,
+    finished: bool,
+    bookkeeping: Option<StreamBookkeeping>,
+}
+
+#[cfg(feature = "blocking")]
+impl ChatCompletionStream {
+    pub(crate) fn new(
+        response: reqwest::blocking::Response,
+        bookkeeping: StreamBookkeeping,
+    ) -> Self {
+        Self {
+            status: response.status(),
+            headers: response.headers().clone(),
+            response,
+            buffer: EventBuffer::new(),
+            finished: false,
+            bookkeeping: Some(bookkeeping),
+        }
+    }
+
+    /// Reads the next chunk of the response.
+    fn read_next(&mut self) -> Option<Result<ChatCompletionChunk, GroqError>> {
+        let mut bytes = [0; 4096];
+        loop {
+            if let Some(data) = self.buffer.events.pop_front() {
+                return Some(parse_event(&data, self.status, &self.headers));
+            }
+            if self.finished || self.buffer.done {
+                return None;
+            }
+            match self.response.read(&mut bytes) {
+                Ok(0) => self.finished = true,
+                Ok(read) => self.buffer.push(&bytes[..read]),
+                Err(err) => {
+                    self.finished = true;
+                    let err = read_error(err, self.response.url());
+                    return Some(Err(err.with_response(self.status, &self.headers)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Iterator for ChatCompletionStream {
+    type Item = Result<ChatCompletionChunk, GroqError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.read_next();
+        track(
+            &mut self.bookkeeping,
+            item.as_ref().map(Result::as_ref),
+            self.status,
+            &self.headers,
+        );
+        item
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Drop for ChatCompletionStream {
+    fn drop(&mut self) {
+        drop_bookkeeping(&mut self.bookkeeping, self.status, &self.headers);
+    }
+}
+
+/// A streamed chat completion, returned by `AsyncGroqClient::chat_completion_stream` and `StreamApi` implementations.
+///
+/// `next` reads the response as it arrives and returns its chunks, until the API marks the end of the stream.
+pub struct AsyncChatCompletionStream {
+    response: Option<reqwest::Response>,
+    status: StatusCode,
+    headers: HeaderMap,
+    buffer: EventBuffer,
+    finished: bool,
+    bookkeeping: Option<StreamBookkeeping>,
+}
+
+impl AsyncChatCompletionStream {
+    /// Creates a stream reading the server-sent events of a response, such as one from another OpenAI-compatible API.
+    ///
+    /// # Arguments
+    /// * `response` - A successful response to a chat completion request with `stream` set.
+    pub fn from_response(response: reqwest::Response) -> Self {
+        Self {
+            status: response.status(),
+            headers: response.headers().clone(),
+            response: Some(response),
+            buffer: EventBuffer::new(),
+            finished: false,
+            bookkeeping: None,
+        }
+    }
+
+    /// Attaches the bookkeeping of the client to do once the stream ends.
+    pub(crate) fn with_bookkeeping(mut self, bookkeeping: StreamBookkeeping) -> Self {
+        self.bookkeeping = Some(bookkeeping);
+        self
+    }
+
+    /// Creates a stream returning the given chunks, for `StreamApi` implementations that do not read an HTTP response.
+    ///
+    /// # Arguments
+    /// * `chunks` - The chunks of the stream, in order.
+    pub fn from_chunks(chunks: impl IntoIterator<Item = ChatCompletionChunk>) -> Self {
+        let mut buffer = EventBuffer::new();
+        buffer.events = chunks
+            .into_iter()
+            // A chunk that cannot be serialized is queued empty, to be returned as a parse error.
+            .map(|chunk| Bytes::from(serde_json::to_vec(&chunk).unwrap_or_default()))
+            .collect();
+        Self {
+            response: None,
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            buffer,
+            finished: true,
+            bookkeeping: None,
+        }
+    }
+
+    /// Returns the next chunk, or `None` once the stream has ended.
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(&mut self) -> Option<Result<ChatCompletionChunk, GroqError>> {
+        let item = self
+            .next_data()
+            .await
+            .map(|data| data.and_then(|data| parse_event(&data, self.status, &self.headers)));
+        track(
+            &mut self.bookkeeping,
+            item.as_ref().map(Result::as_ref),
+            self.status,
+            &self.headers,
+        );
+        item
+    }
+
+    /// Returns the payload of the next event, as sent by the API.
+    async fn next_data(&mut self) -> Option<Result<Bytes, GroqError>> {
+        loop {
+            if let Some(data) = self.buffer.events.pop_front() {
+                return Some(Ok(data));
+            }
+            if self.finished || self.buffer.done {
+                return None;
+            }
+            let response = self.response.as_mut()?;
+            match response.chunk().await {
+                Ok(Some(bytes)) => self.buffer.push(&bytes),
+                Ok(None) => self.finished = true,
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(
+                        GroqError::from(err).with_response(self.status, &self.headers)
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Reads the rest of the stream, concatenating the content of the first choice.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first `GroqError` of the stream.
+    pub async fn collect_content(mut self) -> Result<String, GroqError> {
+        let mut content = String::new();
+        while let Some(chunk) = self.next().await {
+            content.push_str(chunk?.content());
+        }
+        Ok(content)
+    }
+
+    /// Re-encodes the stream as server-sent events, to relay it to the clients of a proxy.
+    ///
+    /// Every chunk is sent as a `data:` event holding its JSON as received, and the end of the stream as
+    /// `data: [DONE]`. An error ends the stream with an `error` event whose data is `{"error": {"message", "type"}}`,
+    /// or the error object sent by the API. The items convert into `Bytes`, so the stream can be the body of an `axum`
+    /// response:
+    ///
+    /// ```ignore
+    /// use axum::{body::Body, http::header, response::Response};
+    ///
+    /// async fn relay(stream: groq_api_rust::AsyncChatCompletionStream) -> Response {
+    ///     Response::builder()
+    ///         .header(header::CONTENT_TYPE, groq_api_rust::SSE_CONTENT_TYPE)
+    ///         .header(header::CACHE_CONTROL, "no-cache")
+    ///         .body(Body::from_stream(stream.into_sse()))
+    ///         .unwrap()
+    /// }
+    /// ```
+    #[cfg(feature = "sse")]
+    pub fn into_sse(self) -> impl futures_util::Stream<Item = Result<String, Infallible>> + Send {
+        futures_util::stream::unfold(Some(self), |stream| async move {
+            let mut stream = stream?;
+            let data = stream.next_data().await;
+            stream.observe(data.as_ref());
+            let event = match data {
+                None => return Some((Ok(sse_event(None, "[DONE]")), None)),
+                Some(Ok(data)) => {
+                    let data = String::from_utf8_lossy(&data);
+                    let is_error = serde_json::from_str::<Value>(&data)
+                        .is_ok_and(|value| value.get("error").is_some());
+                    if is_error {
+                        return Some((Ok(sse_event(Some("error"), &data)), None));
+                    }
+                    sse_event(None, &data)
+                }
+                Some(Err(err)) => {
+                    let error = serde_json::json!({"error": {"message": err.to_string(), "type": "stream_error"}});
+                    return Some((Ok(sse_event(Some("error"), &error.to_string())), None));
+                }
+            };
+            Some((Ok(event), Some(stream)))
+        })
+    }
+
+    /// Passes an event relayed by `into_sse` to the bookkeeping of the stream, if any.
+    #[cfg(feature = "sse")]
+    fn observe(&mut self, data: Option<&Result<Bytes, GroqError>>) {
+        if self.bookkeeping.is_none() {
+            return;
+        }
+        let chunk = match data {
+            Some(Ok(data)) => Some(parse_event(data, self.status, &self.headers)),
+            _ => None,
+        };
+        let item = match data {
+            Some(Err(err)) => Some(Err(err)),
+            _ => chunk.as_ref().map(Result::as_ref),
+        };
+        track(&mut self.bookkeeping, item, self.status, &self.headers);
+    }
+}
+
+impl Drop for AsyncChatCompletionStream {
+    fn drop(&mut self) {
+        drop_bookkeeping(&mut self.bookkeeping, self.status, &self.headers);
+    }
+}
+
+/// The content type of the server-sent events of `AsyncChatCompletionStream::into_sse`.
+#[cfg(feature = "sse")]
+pub const SSE_CONTENT_TYPE: &str = "text/event-stream";
+
+/// Frames one server-sent event, sending every line of `data` on its own `data:` line.
+#[cfg(feature = "sse")]
+fn sse_event(event: Option<&str>, data: &str) -> String {
+    let mut frame = String::new();
+    if let Some(event) = event {
+        frame.push_str(&format!("event: {}\n", event));
+    }
+    for line in data.lines() {
+        frame.push_str(&format!("data: {}\n", line));
+    }
+    frame.push('\n');
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_buffer_splits_lines() {
+        let mut buffer = EventBuffer::new();
+        buffer.push(b"data: {\"a\":1}\n\n: keep-alive\r\nda");
+        buffer.push(b"ta: {\"b\":2}\r\n\r\ndata: [DONE]\n\ndata: {\"c\":3}\n");
+        assert_eq!(buffer.events, vec![&br#"{"a":1}"#[..], br#"{"b":2}"#]);
+        assert!(buffer.done);
+
+        let mut buffer = EventBuffer::new();
+        let event = "data: {\"content\":\"café\"}\n".as_bytes();
+        let (head, tail) = event.split_at(event.len() - 4);
+        buffer.push(head);
+        buffer.push(tail);
+        assert_eq!(buffer.events[0], "{\"content\":\"café\"}".as_bytes());
+
+        let error = parse_event(
+            br#"{"error": {"message": "overloaded", "type": "server_error"}}"#,
+            StatusCode::OK,
+            &HeaderMap::new(),
+        );
+        assert!(matches!(
+            error,
+            Err(GroqError::ApiError { status: 200, .. })
+        ));
+    }
+
+    #[test]
+    fn test_tool_calls_stream_as_fragments() {
+        let chunk = |tool_call: Value| {
+            let body = serde_json::json!({
+                "id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 0, "model": "llama3-8b-8192",
+                "choices": [{"index": 0, "delta": {"tool_calls": [tool_call]}, "finish_reason": null}]
+            });
+            parse_event(
+                body.to_string().as_bytes(),
+                StatusCode::OK,
+                &HeaderMap::new(),
+            )
+            .unwrap()
+        };
+        let first = chunk(serde_json::json!({
+            "index": 0, "id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": ""}
+        }));
+        let next = chunk(serde_json::json!({"index": 0, "function": {"arguments": "{\"city\":"}}));
+
+        let first = &first.choices[0].delta.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(first.id.as_deref(), Some("call_1"));
+        assert_eq!(
+            first.function.as_ref().unwrap().name.as_deref(),
+            Some("get_weather")
+        );
+        let next = &next.choices[0].delta.tool_calls.as_ref().unwrap()[0];
+        assert_eq!((next.index, next.id.as_deref()), (0, None));
+        assert_eq!(next.function.as_ref().unwrap().name, None);
+        assert_eq!(
+            next.function.as_ref().unwrap().arguments.as_deref(),
+            Some("{\"city\":")
+        );
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_read_errors_are_transport_errors() {
+        let url = reqwest::Url::parse("https://api.groq.com/openai/v1/chat/completions").unwrap();
+        let reset = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset");
+        let err = read_error(reset, &url).with_response(StatusCode::OK, &HeaderMap::new());
+        match &err {
+            GroqError::ReadFailed { source, context } => {
+                assert_eq!(source.kind(), std::io::ErrorKind::ConnectionReset);
+                assert_eq!(context.url.as_deref(), Some(url.as_str()));
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+        assert_eq!(err.status(), Some(200));
+        assert!(!err.is_retryable());
+    }
+
+    #[cfg(feature = "sse")]
+    #[test]
+    fn test_sse_event_frames_lines() {
+        assert_eq!(sse_event(None, "[DONE]"), "data: [DONE]\n\n");
+        assert_eq!(
+            sse_event(Some("error"), "{\n\"a\": 1}"),
+            "event: error\ndata: {\ndata: \"a\": 1}\n\n"
+        );
+    }
+}
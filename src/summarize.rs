@@ -0,0 +1,203 @@
+use crate::chunk::chunk_text;
+use crate::join::join_all;
+use crate::tokens::estimate_text_tokens;
+use crate::{messages, ChatApi, ChatCompletionRequest, GroqError};
+
+#[derive(Debug, Clone)]
+/// Represents the options of `summarize_long`.
+///
+/// - `model`: The model writing the summaries.
+/// - `chunk_tokens`: The maximum number of estimated tokens of the text sent in one request.
+/// - `summary_tokens`: The maximum number of tokens of every summary.
+/// - `instructions`: The system prompt used to summarize each chunk.
+/// - `combine_instructions`: The system prompt used to combine the partial summaries.
+pub struct SummarizeOptions {
+    pub model: String,
+    pub chunk_tokens: u64,
+    pub summary_tokens: u32,
+    pub instructions: String,
+    pub combine_instructions: String,
+}
+
+impl SummarizeOptions {
+    /// Creates new `SummarizeOptions`, with chunks of 3000 tokens and summaries of up to 512 tokens.
+    ///
+    /// # Arguments
+    /// * `model` - The model writing the summaries.
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            chunk_tokens: 3000,
+            summary_tokens: 512,
+            instructions:
+                "Summarize the following text, keeping every important fact, name and figure."
+                    .to_string(),
+            combine_instructions:
+                "The following are summaries of consecutive parts of one document. Combine them \
+                into a single coherent summary, without repeating yourself."
+                    .to_string(),
+        }
+    }
+
+    /// Sets the maximum number of estimated tokens of the text sent in one request.
+    ///
+    /// It must leave room for the instructions and the summary in the context window of the model.
+    ///
+    /// # Arguments
+    /// * `tokens` - The maximum number of tokens of a chunk.
+    pub fn chunk_tokens(mut self, tokens: u64) -> Self {
+        self.chunk_tokens = tokens;
+        self
+    }
+
+    /// Sets the maximum number of tokens of every summary.
+    ///
+    /// # Arguments
+    /// * `tokens` - The `max_tokens` of the summary requests.
+    pub fn summary_tokens(mut self, tokens: u32) -> Self {
+        self.summary_tokens = tokens;
+        self
+    }
+
+    /// Sets the system prompt used to summarize each chunk.
+    ///
+    /// # Arguments
+    /// * `instructions` - The system prompt.
+    pub fn instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = instructions.into();
+        self
+    }
+
+    /// Sets the system prompt used to combine the partial summaries.
+    ///
+    /// # Arguments
+    /// * `instructions` - The system prompt.
+    pub fn combine_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.combine_instructions = instructions.into();
+        self
+    }
+}
+
+/// Summarizes a text of any length with a map-reduce over chunks of it.
+///
+/// The text is split into chunks of `chunk_tokens`, between paragraphs and sentences where possible, and the chunks
+/// are summarized concurrently. The partial summaries are then combined with a final request; if they don't fit in one
+/// chunk together, they are summarized again in chunks first, as long as that shortens them. A text fitting in one
+/// chunk is summarized with a single request.
+///
+/// # Arguments
+/// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+/// * `text` - The text to summarize.
+/// * `options` - The model, chunk size and prompts to use.
+///
+/// # Errors
+///
+/// Returns the first `GroqError` of the summary requests.
+pub async fn summarize_long(
+    client: &dyn ChatApi,
+    text: &str,
+    options: &SummarizeOptions,
+) -> Result<String, GroqError> {
+    let mut chunks = chunk_text(text, options.chunk_tokens);
+    if chunks.len() <= 1 {
+        return summarize(client, options, &options.instructions, text).await;
+    }
+    loop {
+        let summaries = join_all(
+            chunks
+                .iter()
+                .map(|chunk| summarize(client, options, &options.instructions, chunk)),
+        )
+        .await
+        .into_iter()
+        .collect::<Result<Vec<String>, GroqError>>()?;
+        let combined = summaries.join("\n\n");
+        let next = chunk_text(&combined, options.chunk_tokens);
+        // Summaries not shrinking the text would never fit in one chunk, so they are combined as they are.
+        if estimate_text_tokens(&combined) <= options.chunk_tokens || next.len() >= chunks.len() {
+            return summarize(client, options, &options.combine_instructions, &combined).await;
+        }
+        chunks = next;
+    }
+}
+
+/// Sends one summary request.
+async fn summarize(
+    client: &dyn ChatApi,
+    options: &SummarizeOptions,
+    instructions: &str,
+    text: &str,
+) -> Result<String, GroqError> {
+    let request = ChatCompletionRequest::new(
+        options.model.as_str(),
+        messages![system: instructions, user: text],
+    )
+    .max_tokens(options.summary_tokens);
+    let response = client.chat_completion(request).await?;
+    Ok(response.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{reply, server_error};
+    use crate::ChatCompletionResponse;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Summarizes a text as its first word, and records the texts it was asked to summarize.
+    #[derive(Default)]
+    struct FirstWord {
+        texts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ChatApi for FirstWord {
+        async fn chat_completion(
+            &self,
+            request: ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, GroqError> {
+            let text = request.messages[1].content.clone();
+            self.texts.lock().unwrap().push(text.to_string());
+            match text.split_whitespace().next() {
+                Some("Fail") => Err(server_error()),
+                summary => reply(&request.model, summary.unwrap_or_default()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summarize_long_maps_and_reduces() {
+        let client = FirstWord::default();
+        let text = "Alpha one two three.\n\nBeta four five six.\n\nGamma seven eight nine.";
+        let options = SummarizeOptions::new("llama3-8b-8192").chunk_tokens(6);
+
+        let summary = summarize_long(&client, text, &options).await.unwrap();
+        assert_eq!(summary, "Alpha");
+        let texts = client.texts.lock().unwrap();
+        assert_eq!(texts.len(), 4);
+        assert_eq!(texts[3], "Alpha\n\nBeta\n\nGamma");
+    }
+
+    #[tokio::test]
+    async fn test_summarize_long_sends_short_texts_once() {
+        let client = FirstWord::default();
+        let options = SummarizeOptions::new("llama3-8b-8192");
+        let summary = summarize_long(&client, "Alpha one two three.", &options)
+            .await
+            .unwrap();
+        assert_eq!(summary, "Alpha");
+        assert_eq!(*client.texts.lock().unwrap(), vec!["Alpha one two three."]);
+    }
+
+    #[tokio::test]
+    async fn test_summarize_long_returns_errors() {
+        let client = FirstWord::default();
+        let text = "Alpha one two three.\n\nFail four five six.\n\nGamma seven eight nine.";
+        let options = SummarizeOptions::new("llama3-8b-8192").chunk_tokens(6);
+        assert!(matches!(
+            summarize_long(&client, text, &options).await,
+            Err(GroqError::ServerError { status: 500, .. })
+        ));
+    }
+}
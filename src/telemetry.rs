@@ -0,0 +1,279 @@
+//! Hooks recording requests through `tracing`, OpenTelemetry and Prometheus when the `tracing`, `otel` and `metrics`
+//! features are enabled.
+//!
+//! Without the features every function is a no-op, so the clients can call them unconditionally.
+
+use crate::{GroqError, Usage};
+use std::time::Duration;
+
+/// The metric sinks of a client.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Recorder {
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<crate::GroqMetrics>,
+}
+
+impl Recorder {
+    /// Starts an attempt of a request to the given URL.
+    pub(crate) fn attempt(&self, endpoint: &str) -> Attempt {
+        #[cfg(feature = "otel")]
+        let span = {
+            use opentelemetry::trace::{SpanKind, Tracer};
+            let tracer = opentelemetry::global::tracer("groq-api-rust");
+            tracer
+                .span_builder("groq.request")
+                .with_kind(SpanKind::Client)
+                .with_attributes([opentelemetry::KeyValue::new(
+                    "url.full",
+                    endpoint.to_string(),
+                )])
+                .start(&tracer)
+        };
+        Attempt {
+            endpoint: endpoint.to_string(),
+            #[cfg(feature = "otel")]
+            span,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics.clone(),
+        }
+    }
+
+    /// Records an error returned by an attempt to the given URL.
+    pub(crate) fn record_error(&self, endpoint: &str, error: &GroqError) {
+        #[cfg(feature = "otel")]
+        otel::instruments().errors.add(
+            1,
+            &[opentelemetry::KeyValue::new(
+                "error.type",
+                error_type(error),
+            )],
+        );
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_error(endpoint, error_type(error));
+        }
+        #[cfg(not(any(feature = "otel", feature = "metrics")))]
+        let _ = error;
+        #[cfg(not(feature = "metrics"))]
+        let _ = endpoint;
+    }
+
+    /// Records the token usage reported by a chat completion.
+    pub(crate) fn record_usage(&self, model: &str, usage: Option<&Usage>) {
+        let Some(usage) = usage else {
+            return;
+        };
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            span.record("prompt_tokens", usage.prompt_tokens);
+            span.record("completion_tokens", usage.completion_tokens);
+            span.record("total_tokens", usage.total_tokens);
+        }
+        #[cfg(feature = "otel")]
+        {
+            use opentelemetry::KeyValue;
+            let tokens = &otel::instruments().tokens;
+            let model = KeyValue::new("gen_ai.request.model", model.to_string());
+            tokens.add(
+                usage.prompt_tokens,
+                &[model.clone(), KeyValue::new("gen_ai.token.type", "input")],
+            );
+            tokens.add(
+                usage.completion_tokens,
+                &[model, KeyValue::new("gen_ai.token.type", "output")],
+            );
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_usage(model, usage);
+        }
+        #[cfg(not(any(feature = "otel", feature = "metrics")))]
+        let _ = model;
+        #[cfg(not(any(feature = "tracing", feature = "otel", feature = "metrics")))]
+        let _ = usage;
+    }
+}
+
+/// One attempt of a request, exported as an OpenTelemetry client span when the `otel` feature is enabled.
+pub(crate) struct Attempt {
+    endpoint: String,
+    #[cfg(feature = "otel")]
+    span: opentelemetry::global::BoxedSpan,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::GroqMetrics>,
+}
+
+impl Attempt {
+    /// Ends the attempt, recording the HTTP status, if a response was received, and the time to the response headers.
+    pub(crate) fn finish(self, status: Option<u16>, latency: Duration) {
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            if let Some(status) = status {
+                span.record("status", status);
+            }
+            span.record("latency_ms", latency.as_millis() as u64);
+        }
+        #[cfg(feature = "otel")]
+        {
+            use opentelemetry::trace::{Span, Status};
+            use opentelemetry::KeyValue;
+            let mut span = self.span;
+            let mut attributes = vec![KeyValue::new("url.full", self.endpoint.clone())];
+            match status {
+                Some(status) => {
+                    span.set_attribute(KeyValue::new(
+                        "http.response.status_code",
+                        i64::from(status),
+                    ));
+                    attributes.push(KeyValue::new(
+                        "http.response.status_code",
+                        i64::from(status),
+                    ));
+                    if status >= 400 {
+                        span.set_status(Status::error(format!("HTTP {status}")));
+                    }
+                }
+                None => span.set_status(Status::error("no response")),
+            }
+            span.end();
+            let instruments = otel::instruments();
+            instruments.requests.add(1, &attributes);
+            instruments
+                .ttfb
+                .record(latency.as_secs_f64(), &attributes[..1]);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record_attempt(&self.endpoint, status, latency);
+        }
+        #[cfg(not(any(feature = "otel", feature = "metrics")))]
+        let _ = self.endpoint;
+        #[cfg(not(any(feature = "tracing", feature = "otel", feature = "metrics")))]
+        let _ = (status, latency);
+    }
+}
+
+/// The span of a streamed chat completion, kept by the stream so that its end is recorded in it.
+#[cfg(feature = "stream")]
+#[derive(Debug, Clone)]
+pub(crate) struct StreamSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+#[cfg(feature = "stream")]
+impl StreamSpan {
+    /// Returns the span the stream is opened in.
+    pub(crate) fn current() -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::current(),
+        }
+    }
+
+    /// Runs a closure in the span, so that it records its fields there.
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "tracing")]
+        return self.span.in_scope(f);
+        #[cfg(not(feature = "tracing"))]
+        f()
+    }
+
+    /// Emits a debug event once the response headers of the stream are received.
+    pub(crate) fn opened(&self) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(parent: &self.span, "Groq API stream opened");
+    }
+
+    /// Emits a debug event once the stream has ended, failed or been dropped, after the given number of chunks.
+    pub(crate) fn closed(&self, chunks: u64, error: Option<&GroqError>, dropped: bool) {
+        #[cfg(feature = "tracing")]
+        match error {
+            Some(error) => {
+                tracing::debug!(parent: &self.span, chunks, %error, "Groq API stream failed")
+            }
+            None if dropped => {
+                tracing::debug!(parent: &self.span, chunks, "Groq API stream dropped before its end")
+            }
+            None => tracing::debug!(parent: &self.span, chunks, "Groq API stream finished"),
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (chunks, error, dropped);
+    }
+}
+
+/// Emits a debug event before a failed attempt is retried.
+pub(crate) fn retrying(attempt: u32, delay: Duration, error: &GroqError) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(attempt, ?delay, %error, "retrying Groq API request");
+    #[cfg(not(feature = "tracing"))]
+    let _ = (attempt, delay, error);
+}
+
+/// Returns a low-cardinality name for the kind of an error.
+#[cfg(any(feature = "otel", feature = "metrics"))]
+fn error_type(error: &GroqError) -> &'static str {
+    match error {
+        GroqError::RequestFailed { .. } => "request_failed",
+        GroqError::ReadFailed { .. } => "read_failed",
+        GroqError::JsonParseError(_) | GroqError::DeserializationError { .. } => "invalid_response",
+        GroqError::ApiError { .. } => "api_error",
+        GroqError::Unauthorized(_) => "unauthorized",
+        GroqError::Forbidden(_) => "forbidden",
+        GroqError::NotFound(_) => "not_found",
+        GroqError::UnprocessableEntity(_) => "unprocessable_entity",
+        GroqError::RateLimited { .. } => "rate_limited",
+        GroqError::ServerError { .. } => "server_error",
+        GroqError::CircuitOpen { .. } => "circuit_open",
+        GroqError::Timeout { .. } => "timeout",
+        GroqError::DeadlineExceeded { .. } => "deadline_exceeded",
+        GroqError::BudgetExceeded { .. } => "budget_exceeded",
+        GroqError::CassetteMiss { .. } => "cassette_miss",
+        GroqError::AgentLimitExceeded { .. } => "agent_limit_exceeded",
+        GroqError::UnexpectedAnswer { .. } => "unexpected_answer",
+        GroqError::InvalidRequest { .. } => "invalid_request",
+    }
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::metrics::{Counter, Histogram};
+    use std::sync::OnceLock;
+
+    pub(super) struct Instruments {
+        pub(super) requests: Counter<u64>,
+        pub(super) errors: Counter<u64>,
+        pub(super) tokens: Counter<u64>,
+        pub(super) ttfb: Histogram<f64>,
+    }
+
+    /// Returns the instruments, created from the global meter provider on first use.
+    pub(super) fn instruments() -> &'static Instruments {
+        static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+        INSTRUMENTS.get_or_init(|| {
+            let meter = opentelemetry::global::meter("groq-api-rust");
+            Instruments {
+                requests: meter
+                    .u64_counter("groq.requests")
+                    .with_description("Requests sent to the Groq API, by URL and status")
+                    .build(),
+                errors: meter
+                    .u64_counter("groq.errors")
+                    .with_description("Failed requests, by error type")
+                    .build(),
+                tokens: meter
+                    .u64_counter("groq.tokens")
+                    .with_description("Tokens used by chat completions, by model and direction")
+                    .with_unit("{token}")
+                    .build(),
+                ttfb: meter
+                    .f64_histogram("groq.time_to_first_byte")
+                    .with_description("Time until the response headers were received")
+                    .with_unit("s")
+                    .build(),
+            }
+        })
+    }
+}
@@ -0,0 +1,371 @@
+use crate::{ChatCompletionMessage, ChatCompletionRoles};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+/// Represents errors that can occur when parsing or rendering a `PromptTemplate`.
+///
+/// - `UnclosedPlaceholder`: A `{` at the given byte offset has no matching `}`.
+/// - `UnmatchedBrace`: A `}` at the given byte offset closes no placeholder; write `}}` for a literal brace.
+/// - `InvalidPlaceholder`: A placeholder name is empty or contains characters other than letters, digits and `_`.
+/// - `MissingVariable`: No value was given for a placeholder when rendering.
+pub enum TemplateError {
+    #[error("Unclosed placeholder at offset {0}")]
+    UnclosedPlaceholder(usize),
+    #[error("Unmatched `}}` at offset {0}")]
+    UnmatchedBrace(usize),
+    #[error("Invalid placeholder name `{0}`")]
+    InvalidPlaceholder(String),
+    #[error("Missing value for placeholder `{0}`")]
+    MissingVariable(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+#[derive(Debug, Clone)]
+/// A prompt with named `{placeholders}`, rendered into a `ChatCompletionMessage` at runtime.
+///
+/// Literal braces are written `{{` and `}}`. Values are inserted as-is, so braces in them are never read as
+/// placeholders. Values can be bound ahead of time with `partial`, and the remaining ones are given to `render`.
+///
+/// ```
+/// use groq_api_rust::PromptTemplate;
+///
+/// let template = PromptTemplate::system("You are a {tone} assistant. Answer in {language}.")
+///     .unwrap()
+///     .partial("tone", "friendly");
+/// let message = template.to_message(&[("language", "French")]).unwrap();
+/// assert_eq!(message.content, "You are a friendly assistant. Answer in French.");
+/// ```
+pub struct PromptTemplate {
+    role: ChatCompletionRoles,
+    segments: Vec<Segment>,
+    values: HashMap<String, String>,
+}
+
+impl PromptTemplate {
+    /// Parses a template rendered into user messages.
+    ///
+    /// # Arguments
+    /// * `template` - The text of the prompt, with `{name}` placeholders.
+    pub fn new(template: &str) -> Result<Self, TemplateError> {
+        Ok(Self {
+            role: ChatCompletionRoles::User,
+            segments: parse(template)?,
+            values: HashMap::new(),
+        })
+    }
+
+    /// Parses a template rendered into system messages.
+    ///
+    /// # Arguments
+    /// * `template` - The text of the prompt, with `{name}` placeholders.
+    pub fn system(template: &str) -> Result<Self, TemplateError> {
+        Ok(Self::new(template)?.role(ChatCompletionRoles::System))
+    }
+
+    /// Parses a template rendered into user messages; the same as `new`.
+    ///
+    /// # Arguments
+    /// * `template` - The text of the prompt, with `{name}` placeholders.
+    pub fn user(template: &str) -> Result<Self, TemplateError> {
+        Self::new(template)
+    }
+
+    /// Sets the role of the rendered messages.
+    ///
+    /// # Arguments
+    /// * `role` - The role of the messages.
+    pub fn role(mut self, role: ChatCompletionRoles) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Binds a value to a placeholder, leaving the others to `render`.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the placeholder.
+    /// * `value` - The text inserted in its place.
+    pub fn partial(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    /// Returns the names of the placeholders not bound by `partial`, in order of first appearance.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for segment in &self.segments {
+            if let Segment::Placeholder(name) = segment {
+                if !self.values.contains_key(name) && !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Renders the template into text.
+    ///
+    /// # Arguments
+    /// * `values` - The values of the placeholders, taking precedence over the ones bound by `partial`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TemplateError::MissingVariable` if a placeholder has no value.
+    pub fn render(&self, values: &[(&str, &str)]) -> Result<String, TemplateError> {
+        let mut text = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(literal) => text.push_str(literal),
+                Segment::Placeholder(name) => {
+                    let value = values
+                        .iter()
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| *value)
+                        .or_else(|| self.values.get(name).map(String::as_str))
+                        .ok_or_else(|| TemplateError::MissingVariable(name.clone()))?;
+                    text.push_str(value);
+                }
+            }
+        }
+        Ok(text)
+    }
+
+    /// Renders the template into a message with the role of the template.
+    ///
+    /// # Arguments
+    /// * `values` - The values of the placeholders, taking precedence over the ones bound by `partial`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TemplateError::MissingVariable` if a placeholder has no value.
+    pub fn to_message(
+        &self,
+        values: &[(&str, &str)],
+    ) -> Result<ChatCompletionMessage, TemplateError> {
+        Ok(message(self.role.clone(), &self.render(values)?))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A few-shot prompt: a task instruction followed by example inputs and their expected outputs.
+///
+/// The examples are expanded into alternating user and assistant messages between the system instruction and the
+/// actual input, and can be capped to a token budget.
+pub struct FewShot {
+    instruction: String,
+    examples: Vec<(String, String)>,
+    max_example_tokens: Option<u64>,
+}
+
+impl FewShot {
+    /// Creates a new `FewShot` prompt without examples.
+    ///
+    /// # Arguments
+    /// * `instruction` - The task, sent as the system message.
+    pub fn new(instruction: impl Into<String>) -> Self {
+        Self {
+            instruction: instruction.into(),
+            examples: Vec::new(),
+            max_example_tokens: None,
+        }
+    }
+
+    /// Adds an example.
+    ///
+    /// # Arguments
+    /// * `input` - The example input, sent as a user message.
+    /// * `output` - The expected output, sent as an assistant message.
+    pub fn example(mut self, input: impl Into<String>, output: impl Into<String>) -> Self {
+        self.examples.push((input.into(), output.into()));
+        self
+    }
+
+    /// Caps the estimated tokens taken by the examples.
+    ///
+    /// Examples are kept in order until the next one would exceed the budget; the others are left out.
+    ///
+    /// # Arguments
+    /// * `tokens` - The maximum number of tokens of the example messages.
+    pub fn max_example_tokens(mut self, tokens: u64) -> Self {
+        self.max_example_tokens = Some(tokens);
+        self
+    }
+
+    /// Expands the prompt into messages ending with the given input.
+    ///
+    /// # Arguments
+    /// * `input` - The input to answer, sent as the last user message.
+    pub fn messages(&self, input: &str) -> Vec<ChatCompletionMessage> {
+        let mut messages = vec![message(ChatCompletionRoles::System, &self.instruction)];
+        let mut remaining = self.max_example_tokens.unwrap_or(u64::MAX);
+        for (example_input, example_output) in &self.examples {
+            let pair = [
+                message(ChatCompletionRoles::User, example_input),
+                message(ChatCompletionRoles::Assistant, example_output),
+            ];
+            let tokens = pair
+                .iter()
+                .map(ChatCompletionMessage::token_estimate)
+                .sum::<u64>();
+            if tokens > remaining {
+                break;
+            }
+            remaining -= tokens;
+            messages.extend(pair);
+        }
+        messages.push(message(ChatCompletionRoles::User, input));
+        messages
+    }
+}
+
+/// Builds a `Vec<ChatCompletionMessage>` from `role: content` pairs.
+///
+/// The roles are `system`, `user` and `assistant`, and the contents are any expressions implementing `Display`.
+///
+/// ```
+/// use groq_api_rust::{messages, ChatCompletionRequest};
+///
+/// let question = "What is the capital of France?";
+/// let request = ChatCompletionRequest::new(
+///     "llama3-8b-8192",
+///     messages![
+///         system: "You are a helpful assistant.",
+///         user: question,
+///     ],
+/// );
+/// assert_eq!(request.messages[1].content, question);
+/// ```
+#[macro_export]
+macro_rules! messages {
+    (@role system) => {
+        $crate::ChatCompletionRoles::System
+    };
+    (@role user) => {
+        $crate::ChatCompletionRoles::User
+    };
+    (@role assistant) => {
+        $crate::ChatCompletionRoles::Assistant
+    };
+    ($($role:ident: $content:expr),* $(,)?) => {
+        ::std::vec![$(
+            $crate::ChatCompletionMessage {
+                role: $crate::messages!(@role $role),
+                content: $crate::MessageContent::from(::std::string::ToString::to_string(&$content)),
+                name: ::std::option::Option::None,
+                tool_calls: ::std::option::Option::None,
+                tool_call_id: ::std::option::Option::None,
+            }
+        ),*]
+    };
+}
+
+fn message(role: ChatCompletionRoles, content: &str) -> ChatCompletionMessage {
+    ChatCompletionMessage {
+        role,
+        content: content.into(),
+        name: None,
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
+
+impl std::str::FromStr for PromptTemplate {
+    type Err = TemplateError;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        Self::new(template)
+    }
+}
+
+/// Splits a template into literal text and placeholders, unescaping `{{` and `}}`.
+fn parse(template: &str) -> Result<Vec<Segment>, TemplateError> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|&(_, next)| next == '{').is_some() => text.push('{'),
+            '}' if chars.next_if(|&(_, next)| next == '}').is_some() => text.push('}'),
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) => name.push(c),
+                        None => return Err(TemplateError::UnclosedPlaceholder(offset)),
+                    }
+                }
+                let name = name.trim();
+                if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(TemplateError::InvalidPlaceholder(name.to_string()));
+                }
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Placeholder(name.to_string()));
+            }
+            '}' => return Err(TemplateError::UnmatchedBrace(offset)),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_renders_placeholders() {
+        let template = PromptTemplate::new("Translate {{literally}} to {language}: {text}")
+            .unwrap()
+            .partial("language", "German");
+        assert_eq!(template.variables(), vec!["text"]);
+        assert_eq!(
+            template.render(&[("text", "{hello}")]).unwrap(),
+            "Translate {literally} to German: {hello}"
+        );
+        assert_eq!(
+            template.render(&[]),
+            Err(TemplateError::MissingVariable("text".to_string()))
+        );
+        assert_eq!(
+            PromptTemplate::new("Hello {name").unwrap_err(),
+            TemplateError::UnclosedPlaceholder(6)
+        );
+        assert_eq!(
+            PromptTemplate::new("Hello }").unwrap_err(),
+            TemplateError::UnmatchedBrace(6)
+        );
+        assert!(matches!(
+            PromptTemplate::new("Hello {first name}"),
+            Err(TemplateError::InvalidPlaceholder(_))
+        ));
+    }
+
+    #[test]
+    fn test_few_shot_alternates_examples() {
+        let prompt = FewShot::new("Classify the sentiment")
+            .example("I love it", "positive")
+            .example("I hate it", "negative");
+        let messages = prompt.messages("It is fine");
+        let roles = serde_json::to_value(messages.iter().map(|m| &m.role).collect::<Vec<_>>());
+        assert_eq!(
+            roles.unwrap(),
+            serde_json::json!(["system", "user", "assistant", "user", "assistant", "user"])
+        );
+
+        let capped = prompt.max_example_tokens(20).messages("It is fine");
+        assert_eq!(capped.len(), 4);
+        assert_eq!(capped[1].content, "I love it");
+        assert_eq!(capped[3].content, "It is fine");
+    }
+}
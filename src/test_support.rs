@@ -0,0 +1,71 @@
+use crate::{ChatApi, ChatCompletionRequest, ChatCompletionResponse, GroqError};
+use async_trait::async_trait;
+use reqwest::{header::HeaderMap, StatusCode};
+use serde_json::{json, Value};
+
+/// A closure answering chat completion requests.
+pub(crate) trait Answer:
+    Fn(ChatCompletionRequest) -> Result<ChatCompletionResponse, GroqError> + Send + Sync
+{
+}
+
+impl<F> Answer for F where
+    F: Fn(ChatCompletionRequest) -> Result<ChatCompletionResponse, GroqError> + Send + Sync
+{
+}
+
+/// A `ChatApi` answering every request with a closure, for the unit tests of the helpers built on `ChatApi`.
+pub(crate) struct ChatFn<F>(pub(crate) F);
+
+#[async_trait]
+impl<F: Answer> ChatApi for ChatFn<F> {
+    async fn chat_completion(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, GroqError> {
+        (self.0)(request)
+    }
+}
+
+/// Returns a chat completion of `model` with the given choices and usage, which may be `null`.
+pub(crate) fn completion(
+    model: &str,
+    choices: Value,
+    usage: Value,
+) -> Result<ChatCompletionResponse, GroqError> {
+    crate::message::deserialize(json!({
+        "id": "chatcmpl-test",
+        "object": "chat.completion",
+        "created": 0,
+        "model": model,
+        "choices": choices,
+        "usage": usage,
+    }))
+}
+
+/// Returns a chat completion of `model` answering `content`.
+pub(crate) fn reply(model: &str, content: &str) -> Result<ChatCompletionResponse, GroqError> {
+    completion(
+        model,
+        json!([{
+            "index": 0,
+            "message": {"role": "assistant", "content": content},
+            "finish_reason": "stop"
+        }]),
+        Value::Null,
+    )
+}
+
+/// Returns a chat completion of `model` without any choice.
+pub(crate) fn no_choices(model: &str) -> Result<ChatCompletionResponse, GroqError> {
+    completion(model, json!([]), Value::Null)
+}
+
+/// Returns the error of a request the API failed to serve.
+pub(crate) fn server_error() -> GroqError {
+    GroqError::from_body(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &HeaderMap::new(),
+        r#"{"error": {"message": "Internal server error", "type": "server_error"}}"#,
+    )
+}
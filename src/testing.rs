@@ -0,0 +1,281 @@
+//! Helpers for integration tests against a local OpenAI-compatible mock server.
+//!
+//! Start a `MockGroqServer`, mount the responses a test needs and point a client at `MockGroqServer::uri`:
+//!
+//! ```no_run
+//! # async fn example() {
+//! use groq_api_rust::testing::MockGroqServer;
+//!
+//! let server = MockGroqServer::start().await;
+//! server.mock_chat_completion("Hello!").await;
+//! let client = server.client().await;
+//! # }
+//! ```
+
+use crate::AsyncGroqClient;
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// A synthetic streamed chat completion in the chunk shape documented for the Groq API, answering "The capital of
+/// France is Paris." with its usage in the `x_groq` field of the last chunk.
+///
+/// The transcript is hand-written, not recorded: its IDs and timings are made up.
+pub const CHAT_STREAM_TRANSCRIPT: &str = include_str!("../fixtures/chat_stream.sse");
+
+/// A synthetic streamed chat completion in the chunk shape documented for the Groq API, calling a `get_weather` tool
+/// with `{"city":"Paris","unit":"celsius"}`, streamed as fragments of the call, and finishing with `tool_calls`.
+///
+/// The transcript is hand-written, not recorded: its IDs and timings are made up.
+pub const TOOL_CALL_STREAM_TRANSCRIPT: &str = include_str!("../fixtures/tool_call_stream.sse");
+
+/// A local mock server answering like the Groq API.
+///
+/// Mocked responses are matched in the order they were mounted, so mount specific ones (such as errors) first.
+/// The underlying `wiremock::MockServer` is available through `server` for custom matching.
+pub struct MockGroqServer {
+    server: MockServer,
+}
+
+impl MockGroqServer {
+    /// Starts a new mock server on a random local port.
+    pub async fn start() -> Self {
+        Self {
+            server: MockServer::start().await,
+        }
+    }
+
+    /// Returns the endpoint to pass to `GroqClient::new` or `AsyncGroqClient::new`.
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// Returns an `AsyncGroqClient` sending its requests to this server.
+    pub async fn client(&self) -> AsyncGroqClient {
+        AsyncGroqClient::new("test-key".to_string(), Some(self.uri())).await
+    }
+
+    /// Returns the underlying `wiremock::MockServer`.
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// Answers chat completions with an assistant message, echoing the requested model.
+    ///
+    /// # Arguments
+    /// * `content` - The content of the assistant message.
+    pub async fn mock_chat_completion(&self, content: impl Into<String>) {
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ChatResponder {
+                content: content.into(),
+            })
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answers chat completions with a server-sent event stream of `chat.completion.chunk` objects.
+    ///
+    /// # Arguments
+    /// * `chunks` - The content of each chunk, in order.
+    pub async fn mock_chat_completion_stream(&self, chunks: &[&str]) {
+        let mut body = String::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            let delta = if index == 0 {
+                json!({"role": "assistant", "content": chunk})
+            } else {
+                json!({"content": chunk})
+            };
+            body.push_str(&format!("data: {}\n\n", chunk_body(delta, None)));
+        }
+        body.push_str(&format!(
+            "data: {}\n\n",
+            chunk_body(json!({}), Some("stop"))
+        ));
+        body.push_str("data: [DONE]\n\n");
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answers chat completions with a server-sent event transcript, such as `CHAT_STREAM_TRANSCRIPT`.
+    ///
+    /// # Arguments
+    /// * `transcript` - The body of the streamed response, sent as-is.
+    pub async fn mock_stream_transcript(&self, transcript: &str) {
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(transcript.to_string(), "text/event-stream"),
+            )
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answers transcriptions and translations with the given text.
+    ///
+    /// # Arguments
+    /// * `text` - The transcribed text.
+    pub async fn mock_speech_to_text(&self, text: &str) {
+        for endpoint in ["/audio/transcriptions", "/audio/translations"] {
+            Mock::given(method("POST"))
+                .and(path(endpoint))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "text": text,
+                    "x_groq": {"id": "req_mock"}
+                })))
+                .mount(&self.server)
+                .await;
+        }
+    }
+
+    /// Answers every request with an API error.
+    ///
+    /// # Arguments
+    /// * `status` - The HTTP status of the error.
+    /// * `message` - The message of the error.
+    pub async fn mock_error(&self, status: u16, message: &str) {
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(status)
+                    .insert_header("x-request-id", "req_mock")
+                    .set_body_json(json!({
+                        "error": {"message": message, "type": "invalid_request_error"}
+                    })),
+            )
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Returns the requests received so far, in order.
+    pub async fn received_requests(&self) -> Vec<Request> {
+        self.server.received_requests().await.unwrap_or_default()
+    }
+
+    /// Removes all mounted responses and received requests.
+    pub async fn reset(&self) {
+        self.server.reset().await;
+    }
+}
+
+struct ChatResponder {
+    content: String,
+}
+
+impl Respond for ChatResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let model = serde_json::from_slice::<Value>(&request.body)
+            .ok()
+            .and_then(|body| body["model"].as_str().map(str::to_string))
+            .unwrap_or_default();
+        ResponseTemplate::new(200)
+            .insert_header("x-request-id", "req_mock")
+            .set_body_json(json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion",
+                "created": 0,
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": self.content},
+                    "logprobs": null,
+                    "finish_reason": "stop"
+                }],
+                "usage": {
+                    "prompt_tokens": 10, "prompt_time": 0.0,
+                    "completion_tokens": 2, "completion_time": 0.0,
+                    "total_tokens": 12, "total_time": 0.0
+                },
+                "x_groq": {"id": "req_mock"}
+            }))
+    }
+}
+
+fn chunk_body(delta: Value, finish_reason: Option<&str>) -> Value {
+    json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion.chunk",
+        "created": 0,
+        "model": "mock",
+        "choices": [{"index": 0, "delta": delta, "logprobs": null, "finish_reason": finish_reason}]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChatCompletionMessage, ChatCompletionRequest, ChatCompletionRoles, GroqError};
+
+    #[tokio::test]
+    async fn test_mock_server() {
+        let server = MockGroqServer::start().await;
+        server.mock_chat_completion("Hello!").await;
+        let client = server.client().await;
+        let request = ChatCompletionRequest::new(
+            "llama3-8b-8192",
+            vec![ChatCompletionMessage {
+                role: ChatCompletionRoles::User,
+                content: "Hi".into(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        );
+
+        let response = client.chat_completion(request.clone()).await.unwrap();
+        assert_eq!(response.model, "llama3-8b-8192");
+        assert_eq!(response.choices[0].message.content, "Hello!");
+        assert_eq!(server.received_requests().await.len(), 1);
+
+        server.reset().await;
+        server.mock_error(404, "The model does not exist").await;
+        assert!(matches!(
+            client.chat_completion(request).await,
+            Err(GroqError::NotFound(_))
+        ));
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_stream_transcripts() {
+        let server = MockGroqServer::start().await;
+        server.mock_stream_transcript(CHAT_STREAM_TRANSCRIPT).await;
+        let client = server.client().await;
+        let request =
+            ChatCompletionRequest::new("llama-3.3-70b-versatile", crate::messages![user: "Hi"]);
+
+        let mut stream = client
+            .chat_completion_stream(request.clone())
+            .await
+            .unwrap();
+        let mut content = String::new();
+        let mut usage = None;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            content.push_str(chunk.content());
+            usage = usage.or(chunk.usage());
+        }
+        assert_eq!(content, "The capital of France is Paris.");
+        assert_eq!(usage.unwrap().total_tokens, 53);
+
+        server.reset().await;
+        server
+            .mock_stream_transcript(TOOL_CALL_STREAM_TRANSCRIPT)
+            .await;
+        let mut stream = client.chat_completion_stream(request).await.unwrap();
+        let (mut name, mut arguments) = (String::new(), String::new());
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            for call in chunk.choices[0].delta.tool_calls.iter().flatten() {
+                let function = call.function.clone().unwrap_or_default();
+                name.extend(function.name);
+                arguments.extend(function.arguments);
+            }
+        }
+        assert_eq!(name, "get_weather");
+        assert_eq!(arguments, r#"{"city":"Paris","unit":"celsius"}"#);
+    }
+}
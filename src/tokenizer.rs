@@ -0,0 +1,238 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use regex::Regex;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// The pattern splitting text into the pieces merged by `cl100k_base` and Llama 3, without its `\s+(?!\S)`
+/// alternative: the regex crate has no lookahead, so `pieces` gives the last space of a run back to the next word.
+const SPLIT_PATTERN: &str = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+";
+
+/// The tokenizer installed with `Tokenizer::install`.
+static INSTALLED: OnceLock<Tokenizer> = OnceLock::new();
+
+/// A byte-level BPE tokenizer, compatible with the tiktoken encodings of `cl100k_base` and Llama 3.
+///
+/// Once installed, `count_tokens`, `ChatCompletionMessage::token_estimate` and every estimate built on them (trimming,
+/// budgets, rate limits and `auto_max_tokens`) count tokens with it instead of four characters per token. No vocabulary
+/// ships with the crate, so the `tokenizer` feature alone changes no estimate until one is installed. Special tokens
+/// such as `<|eot_id|>` are encoded as plain text.
+///
+/// ```
+/// use base64::{engine::general_purpose::STANDARD, Engine};
+/// use groq_api_rust::{count_tokens, messages, ChatCompletionRequest, Tokenizer};
+///
+/// // The 256 single bytes, then a merge of "Hi".
+/// let tokens = (0..=u8::MAX).map(|byte| vec![byte]).chain([b"Hi".to_vec()]);
+/// let ranks: String = tokens
+///     .enumerate()
+///     .map(|(rank, token)| format!("{} {}\n", STANDARD.encode(token), rank))
+///     .collect();
+/// let tokenizer = Tokenizer::from_tiktoken(&ranks)?;
+/// assert_eq!(tokenizer.encode("Hi!"), vec![256, 33]);
+///
+/// let request = ChatCompletionRequest::new("llama3-8b-8192", messages![user: "Hi!"]);
+/// assert_eq!(count_tokens(&request), 1 + 4);
+/// tokenizer.install().unwrap();
+/// assert_eq!(count_tokens(&request), 2 + 4);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Tokenizer {
+    ranks: HashMap<Vec<u8>, u32>,
+    pattern: Regex,
+}
+
+impl std::fmt::Debug for Tokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tokenizer")
+            .field("ranks", &self.ranks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Tokenizer {
+    /// Reads a tokenizer from the ranks of a `.tiktoken` file: one base64-encoded token and its rank per line.
+    ///
+    /// The `tokenizer.model` of Llama 3 and `cl100k_base.tiktoken` are in this format.
+    ///
+    /// # Arguments
+    /// * `ranks` - The content of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error if a line is malformed or a single byte has no rank.
+    pub fn from_tiktoken(ranks: &str) -> io::Result<Self> {
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid tiktoken line: {:?}", line),
+            )
+        };
+        let mut table = HashMap::new();
+        for line in ranks.lines().filter(|line| !line.trim().is_empty()) {
+            let (token, rank) = line.split_once(' ').ok_or_else(|| invalid(line))?;
+            let token = STANDARD.decode(token).map_err(|_| invalid(line))?;
+            let rank = rank.trim().parse::<u32>().map_err(|_| invalid(line))?;
+            table.insert(token, rank);
+        }
+        if let Some(byte) = (0..=u8::MAX).find(|byte| !table.contains_key(&[*byte][..])) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the byte {:#04x} has no rank", byte),
+            ));
+        }
+        Ok(Self {
+            ranks: table,
+            pattern: Regex::new(SPLIT_PATTERN).expect("the split pattern is valid"),
+        })
+    }
+
+    /// Reads a tokenizer from a `.tiktoken` file, as with `from_tiktoken`.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_tiktoken(&std::fs::read_to_string(path)?)
+    }
+
+    /// Installs the tokenizer for the token estimates of the crate, for the rest of the process.
+    ///
+    /// A tokenizer can only be installed once: if one already is, the given tokenizer is returned back.
+    ///
+    /// ```no_run
+    /// let tokenizer = groq_api_rust::Tokenizer::load("tokenizer.model")?;
+    /// assert!(tokenizer.install().is_ok());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn install(self) -> Result<(), Tokenizer> {
+        INSTALLED.set(self)
+    }
+
+    /// Returns the installed tokenizer, if any.
+    pub fn installed() -> Option<&'static Tokenizer> {
+        INSTALLED.get()
+    }
+
+    /// Encodes text into the ranks of its tokens.
+    ///
+    /// # Arguments
+    /// * `text` - The text to encode.
+    pub fn encode(&self, text: &str) -> Vec<u32> {
+        self.pieces(text)
+            .into_iter()
+            .flat_map(|piece| {
+                let piece = piece.as_bytes();
+                let parts = self.merge(piece);
+                parts
+                    .windows(2)
+                    .map(|pair| self.ranks[&piece[pair[0]..pair[1]]])
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Counts the tokens of text, as `encode(text).len()` without collecting the ranks.
+    ///
+    /// # Arguments
+    /// * `text` - The text to count.
+    pub fn count(&self, text: &str) -> u64 {
+        self.pieces(text)
+            .into_iter()
+            .map(|piece| self.merge(piece.as_bytes()).len() as u64 - 1)
+            .sum()
+    }
+
+    /// Splits text into the pieces merged separately, as tiktoken does.
+    fn pieces<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        while let Some(found) = self.pattern.find_at(text, start) {
+            let mut end = found.end();
+            let piece = found.as_str();
+            let followed_by_text = text[end..]
+                .chars()
+                .next()
+                .is_some_and(|c| !c.is_whitespace());
+            // Emulates `\s+(?!\S)`: a run of spaces before a word keeps its last space for the word.
+            if followed_by_text
+                && piece.chars().all(char::is_whitespace)
+                && !piece.ends_with(['\r', '\n'])
+                && piece.chars().nth(1).is_some()
+            {
+                end -= piece.chars().next_back().map_or(0, char::len_utf8);
+            }
+            pieces.push(&text[found.start()..end]);
+            start = end;
+        }
+        pieces
+    }
+
+    /// Merges the bytes of a piece by rank, lowest first, and returns the boundaries of its tokens.
+    fn merge(&self, piece: &[u8]) -> Vec<usize> {
+        if self.ranks.contains_key(piece) {
+            return vec![0, piece.len()];
+        }
+        let rank = |parts: &[usize], i: usize| {
+            parts
+                .get(i + 2)
+                .and_then(|end| self.ranks.get(&piece[parts[i]..*end]))
+                .copied()
+                .unwrap_or(u32::MAX)
+        };
+        let mut parts: Vec<usize> = (0..=piece.len()).collect();
+        let mut pair_ranks: Vec<u32> = (0..parts.len()).map(|i| rank(&parts, i)).collect();
+        while let Some((i, &min)) = pair_ranks.iter().enumerate().min_by_key(|(_, rank)| **rank) {
+            if min == u32::MAX {
+                break;
+            }
+            parts.remove(i + 1);
+            pair_ranks.remove(i + 1);
+            pair_ranks[i] = rank(&parts, i);
+            if i > 0 {
+                pair_ranks[i - 1] = rank(&parts, i - 1);
+            }
+        }
+        parts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenizer() -> Tokenizer {
+        let merges: [&[u8]; 5] = [b"he", b"the", b" s", b"at", b" sat"];
+        let tokens = (0..=u8::MAX)
+            .map(|byte| vec![byte])
+            .chain(merges.map(<[u8]>::to_vec));
+        let ranks: String = tokens
+            .enumerate()
+            .map(|(rank, token)| format!("{} {}\n", STANDARD.encode(token), rank))
+            .collect();
+        Tokenizer::from_tiktoken(&ranks).unwrap()
+    }
+
+    #[test]
+    fn test_tokenizer_merges_by_rank() {
+        let tokenizer = tokenizer();
+        assert_eq!(tokenizer.encode("the sat"), vec![257, 260]);
+        assert_eq!(tokenizer.encode("hat"), vec![104, 259]);
+        assert_eq!(tokenizer.encode("sath"), vec![115, 259, 104]);
+        assert_eq!(tokenizer.count("the hat sat"), 5);
+        assert_eq!(tokenizer.count(""), 0);
+
+        assert!(Tokenizer::from_tiktoken("aGU= 256\n").is_err());
+        assert!(Tokenizer::from_tiktoken("not base64! 1\n").is_err());
+    }
+
+    #[test]
+    fn test_tokenizer_splits_like_tiktoken() {
+        let tokenizer = tokenizer();
+        assert_eq!(
+            tokenizer.pieces("It's a   b\n\nc 1234567  "),
+            vec!["It", "'s", " a", "  ", " b", "\n\n", "c", " ", "123", "456", "7", "  "]
+        );
+        assert_eq!(tokenizer.pieces("x = y;"), vec!["x", " =", " y", ";"]);
+    }
+}
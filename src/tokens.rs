@@ -0,0 +1,72 @@
+use crate::{ChatCompletionMessage, ChatCompletionRequest};
+use serde_json::json;
+
+/// The estimated number of tokens taken by the role and delimiters of a message.
+pub(crate) const MESSAGE_OVERHEAD_TOKENS: u64 = 4;
+
+/// Estimates the number of prompt tokens of a chat completion request: its messages and tool definitions.
+///
+/// Text is estimated at roughly four characters per token, which is close for English prose and errs on the high side
+/// for code and other languages. With the `tokenizer` feature, it is counted by the `Tokenizer` installed with
+/// `Tokenizer::install`, if any. Conversation trimming, budgets, rate limits and `auto_max_tokens` all rely on this
+/// estimate.
+///
+/// # Arguments
+/// * `request` - The request to estimate.
+pub fn count_tokens(request: &ChatCompletionRequest) -> u64 {
+    let messages: u64 = request
+        .messages
+        .iter()
+        .map(ChatCompletionMessage::token_estimate)
+        .sum();
+    let tools: u64 = request
+        .tools
+        .iter()
+        .map(|tool| estimate_text_tokens(&json!(tool).to_string()))
+        .sum();
+    messages + tools
+}
+
+impl ChatCompletionMessage {
+    /// Estimates the number of tokens taken by the message in a prompt, including its role and tool calls.
+    pub fn token_estimate(&self) -> u64 {
+        let tool_calls = self
+            .tool_calls
+            .as_ref()
+            .map_or(0, |calls| estimate_text_tokens(&json!(calls).to_string()));
+        estimate_text_tokens(&self.content) + tool_calls + MESSAGE_OVERHEAD_TOKENS
+    }
+}
+
+/// Estimates the number of tokens of the given text, with the installed `Tokenizer` or at roughly four characters per
+/// token.
+pub(crate) fn estimate_text_tokens(text: &str) -> u64 {
+    #[cfg(feature = "tokenizer")]
+    if let Some(tokenizer) = crate::Tokenizer::installed() {
+        return tokenizer.count(text);
+    }
+    (text.len() as u64).div_ceil(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{messages, ToolDefinition};
+
+    #[test]
+    fn test_count_tokens() {
+        let request = ChatCompletionRequest::new(
+            "llama3-8b-8192",
+            messages![system: "Be brief.", user: "What is Rust?"],
+        );
+        assert_eq!(
+            request.messages[0].token_estimate(),
+            3 + MESSAGE_OVERHEAD_TOKENS
+        );
+        assert_eq!(count_tokens(&request), 3 + 4 + 2 * MESSAGE_OVERHEAD_TOKENS);
+
+        let tool = ToolDefinition::new("now", "Returns the time", json!({}));
+        let with_tools = request.tools(vec![tool]);
+        assert!(count_tokens(&with_tools) > 3 + 4 + 2 * MESSAGE_OVERHEAD_TOKENS);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::ToolDefinition;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// The future returned by a tool call, resolving to its result or an error message for the model.
+pub type ToolFuture = Pin<Box<dyn Future<Output = Result<Value, String>> + Send>>;
+
+/// A tool with a statically known definition, usually generated by the `#[groq_tool]` macro of the `macros` feature.
+///
+/// Register implementations on an `Agent` with `register`.
+pub trait GroqTool {
+    /// Returns the name, description and parameter schema of the tool.
+    fn definition() -> ToolDefinition;
+
+    /// Executes the tool with the arguments of a tool call.
+    ///
+    /// # Arguments
+    /// * `arguments` - The arguments generated by the model, as a JSON object.
+    fn call(arguments: Value) -> ToolFuture;
+}
+
+/// A type usable as a tool argument, describing itself as a JSON schema.
+pub trait ToolParameter {
+    /// Whether the argument must be given by the model.
+    const REQUIRED: bool = true;
+
+    /// Returns the JSON schema of the argument.
+    fn schema() -> Value;
+}
+
+macro_rules! tool_parameter {
+    ($kind:literal: $($ty:ty),*) => {
+        $(
+            impl ToolParameter for $ty {
+                fn schema() -> Value {
+                    json!({"type": $kind})
+                }
+            }
+        )*
+    };
+}
+
+tool_parameter!("string": String, char);
+tool_parameter!("boolean": bool);
+tool_parameter!("integer": i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+tool_parameter!("number": f32, f64);
+
+impl<T: ToolParameter> ToolParameter for Option<T> {
+    const REQUIRED: bool = false;
+
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: ToolParameter> ToolParameter for Vec<T> {
+    fn schema() -> Value {
+        json!({"type": "array", "items": T::schema()})
+    }
+}
+
+impl<T: ToolParameter> ToolParameter for HashMap<String, T> {
+    fn schema() -> Value {
+        json!({"type": "object", "additionalProperties": T::schema()})
+    }
+}
+
+/// Any JSON value.
+impl ToolParameter for Value {
+    fn schema() -> Value {
+        json!({})
+    }
+}
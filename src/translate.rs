@@ -0,0 +1,178 @@
+use crate::models::{LLAMA_3_3_70B_VERSATILE, WHISPER_LARGE_V3};
+use crate::{messages, AudioApi, ChatApi, ChatCompletionRequest, GroqError, SpeechToTextRequest};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Represents the result of `translate_audio`.
+///
+/// - `transcript`: The text spoken in the audio, in its original language.
+/// - `translation`: The transcript translated into the target language.
+pub struct AudioTranslation {
+    pub transcript: String,
+    pub translation: String,
+}
+
+/// Transcribes an audio file and translates the transcript into any language.
+///
+/// The audio is transcribed with `whisper-large-v3`. Translations into English use the Whisper translation endpoint;
+/// other languages are translated from the transcript by `llama-3.3-70b-versatile`.
+///
+/// ```no_run
+/// use groq_api_rust::{translate_audio, AsyncGroqClient};
+///
+/// # async fn example() -> Result<(), groq_api_rust::GroqError> {
+/// let client = AsyncGroqClient::new("my_api_key".to_string(), None).await;
+/// let audio = std::fs::read("interview.mp3").unwrap();
+/// let result = translate_audio(&client, audio, "French").await?;
+/// println!("{}\n{}", result.transcript, result.translation);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Arguments
+/// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+/// * `file` - The audio file to translate.
+/// * `target_language` - The language of the translation, as a name such as `French` or an ISO-639-1 code.
+///
+/// # Errors
+///
+/// Returns the first `GroqError` of the transcription and translation requests.
+pub async fn translate_audio<C: ChatApi + AudioApi + ?Sized>(
+    client: &C,
+    file: Vec<u8>,
+    target_language: &str,
+) -> Result<AudioTranslation, GroqError> {
+    let english = ["en", "english"]
+        .iter()
+        .any(|name| target_language.trim().eq_ignore_ascii_case(name));
+    if english {
+        let transcription = SpeechToTextRequest::new(file).model(WHISPER_LARGE_V3);
+        let translation = transcription.clone().english_text(true);
+        let transcript = client.speech_to_text(transcription).await?.text;
+        let translation = client.speech_to_text(translation).await?.text;
+        return Ok(AudioTranslation {
+            transcript,
+            translation,
+        });
+    }
+
+    let transcript = client
+        .speech_to_text(SpeechToTextRequest::new(file).model(WHISPER_LARGE_V3))
+        .await?
+        .text;
+    let instructions = format!(
+        "Translate the text given by the user into {}. Answer with the translation only.",
+        target_language
+    );
+    let request = ChatCompletionRequest::new(
+        LLAMA_3_3_70B_VERSATILE,
+        messages![system: instructions, user: transcript],
+    )
+    .temperature(0.0);
+    let translation = client.chat_completion(request).await?.to_string();
+    Ok(AudioTranslation {
+        transcript,
+        translation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{reply, server_error};
+    use crate::{ChatCompletionResponse, SpeechToTextResponse};
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Transcribes any audio as Spanish, failing on empty audio, and records the endpoints and models called.
+    #[derive(Default)]
+    struct Interpreter {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ChatApi for Interpreter {
+        async fn chat_completion(
+            &self,
+            request: ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, GroqError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("chat {}", request.model));
+            assert!(request.messages[0].content.contains("into German"));
+            reply(&request.model, "Hallo Welt")
+        }
+    }
+
+    #[async_trait]
+    impl AudioApi for Interpreter {
+        async fn speech_to_text(
+            &self,
+            request: SpeechToTextRequest,
+        ) -> Result<SpeechToTextResponse, GroqError> {
+            let endpoint = if request.english_text {
+                "translations"
+            } else {
+                "transcriptions"
+            };
+            let text = if request.english_text {
+                "Hello world"
+            } else {
+                "Hola mundo"
+            };
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", endpoint, request.model.unwrap()));
+            if request.file.is_empty() {
+                return Err(server_error());
+            }
+            crate::message::deserialize(json!({ "text": text }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_translate_audio_chains_requests() {
+        let client = Interpreter::default();
+        let german = translate_audio(&client, vec![0; 4], "German")
+            .await
+            .unwrap();
+        assert_eq!(german.transcript, "Hola mundo");
+        assert_eq!(german.translation, "Hallo Welt");
+
+        let english = translate_audio(&client, vec![0; 4], "en").await.unwrap();
+        assert_eq!(english.transcript, "Hola mundo");
+        assert_eq!(english.translation, "Hello world");
+        assert_eq!(
+            client.calls.into_inner().unwrap(),
+            vec![
+                "transcriptions whisper-large-v3",
+                "chat llama-3.3-70b-versatile",
+                "transcriptions whisper-large-v3",
+                "translations whisper-large-v3",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_translate_audio_stops_at_the_first_error() {
+        let client = Interpreter::default();
+        assert!(matches!(
+            translate_audio(&client, Vec::new(), "German").await,
+            Err(GroqError::ServerError { status: 500, .. })
+        ));
+        let english = translate_audio(&client, vec![0; 4], " English ")
+            .await
+            .unwrap();
+        assert_eq!(english.translation, "Hello world");
+        assert_eq!(
+            client.calls.into_inner().unwrap(),
+            vec![
+                "transcriptions whisper-large-v3",
+                "transcriptions whisper-large-v3",
+                "translations whisper-large-v3",
+            ]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{PricingTable, Usage};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// Represents the usage accumulated by a client since it was created or last reset.
+///
+/// - `models`: The usage of each model, keyed by the model reported in the responses.
+pub struct UsageSummary {
+    pub models: HashMap<String, ModelUsage>,
+}
+
+impl UsageSummary {
+    /// Returns the usage summed over every model.
+    pub fn total(&self) -> ModelUsage {
+        self.models
+            .values()
+            .fold(ModelUsage::default(), |mut total, usage| {
+                total.add(usage);
+                total
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+/// Represents the usage accumulated for one model.
+///
+/// - `requests`: The number of successful chat completions.
+/// - `prompt_tokens`: The number of prompt tokens.
+/// - `completion_tokens`: The number of completion tokens.
+/// - `total_time`: The processing time reported by the API.
+/// - `estimated_cost`: The estimated cost in US dollars, or zero if the model is missing from the `PricingTable`.
+pub struct ModelUsage {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_time: Duration,
+    pub estimated_cost: f64,
+}
+
+impl ModelUsage {
+    /// Returns the number of prompt and completion tokens.
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    fn add(&mut self, other: &ModelUsage) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_time += other.total_time;
+        self.estimated_cost += other.estimated_cost;
+    }
+}
+
+/// Accumulates the usage reported by the responses of a client.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UsageTracker {
+    models: Arc<Mutex<HashMap<String, ModelUsage>>>,
+    pub(crate) pricing: Arc<PricingTable>,
+}
+
+impl UsageTracker {
+    /// Records a successful chat completion of the given model.
+    pub(crate) fn record(&self, model: &str, usage: Option<&Usage>) {
+        let mut models = self.models.lock().unwrap_or_else(|err| err.into_inner());
+        let entry = models.entry(model.to_string()).or_default();
+        entry.requests += 1;
+        if let Some(usage) = usage {
+            entry.prompt_tokens += usage.prompt_tokens;
+            entry.completion_tokens += usage.completion_tokens;
+            entry.total_time += Duration::try_from_secs_f64(usage.total_time).unwrap_or_default();
+            entry.estimated_cost += self.pricing.cost(model, usage).unwrap_or_default();
+        }
+    }
+
+    pub(crate) fn summary(&self) -> UsageSummary {
+        let models = self.models.lock().unwrap_or_else(|err| err.into_inner());
+        UsageSummary {
+            models: models.clone(),
+        }
+    }
+
+    pub(crate) fn reset(&self) -> UsageSummary {
+        let mut models = self.models.lock().unwrap_or_else(|err| err.into_inner());
+        UsageSummary {
+            models: std::mem::take(&mut *models),
+        }
+    }
+}
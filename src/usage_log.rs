@@ -0,0 +1,228 @@
+use crate::{GroqError, ResponseMeta, Usage};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Represents the format of the records written by a `UsageLog`.
+///
+/// - `JsonLines`: One JSON object per line.
+/// - `Csv`: Comma-separated values, with a header line written before the first record of an empty file.
+pub enum UsageLogFormat {
+    JsonLines,
+    Csv,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+/// Represents one API call, as written by a `UsageLog`.
+///
+/// - `timestamp_ms`: When the call ended, in milliseconds since the Unix epoch.
+/// - `endpoint`: The path of the endpoint, such as `/chat/completions`.
+/// - `model`: The model used, or requested if the call failed.
+/// - `status`: The HTTP status of the response, if any.
+/// - `prompt_tokens`: The number of prompt tokens, if reported.
+/// - `completion_tokens`: The number of completion tokens, if reported.
+/// - `latency_ms`: The duration of the call in milliseconds, retries included.
+/// - `request_id`: The `x-request-id` of the response, if any.
+pub struct UsageRecord {
+    pub timestamp_ms: u64,
+    pub endpoint: String,
+    pub model: String,
+    pub status: Option<u16>,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    pub latency_ms: u64,
+    pub request_id: Option<String>,
+}
+
+impl UsageRecord {
+    /// Creates the record of a call to the given endpoint ending now.
+    pub(crate) fn new(endpoint: &str, model: &str, latency: Duration) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            timestamp_ms: timestamp.as_millis() as u64,
+            endpoint: endpoint.to_string(),
+            model: model.to_string(),
+            status: None,
+            prompt_tokens: None,
+            completion_tokens: None,
+            latency_ms: latency.as_millis() as u64,
+            request_id: None,
+        }
+    }
+
+    /// Fills the record from a successful response.
+    pub(crate) fn success(mut self, meta: &ResponseMeta, usage: Option<&Usage>) -> Self {
+        self.status = Some(meta.status);
+        self.request_id = meta.request_id.clone();
+        self.prompt_tokens = usage.map(|usage| usage.prompt_tokens);
+        self.completion_tokens = usage.map(|usage| usage.completion_tokens);
+        self
+    }
+
+    /// Fills the record from the error of a failed call.
+    pub(crate) fn failure(mut self, error: &GroqError) -> Self {
+        self.status = error.status();
+        self.request_id = error.request_id().map(|id| id.to_string());
+        self
+    }
+
+    fn to_csv(&self) -> String {
+        let optional =
+            |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+        [
+            self.timestamp_ms.to_string(),
+            csv_field(&self.endpoint),
+            csv_field(&self.model),
+            optional(self.status.map(u64::from)),
+            optional(self.prompt_tokens),
+            optional(self.completion_tokens),
+            self.latency_ms.to_string(),
+            csv_field(self.request_id.as_deref().unwrap_or_default()),
+        ]
+        .join(",")
+    }
+}
+
+const CSV_HEADER: &str =
+    "timestamp_ms,endpoint,model,status,prompt_tokens,completion_tokens,latency_ms,request_id";
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Clone)]
+/// A sink appending one `UsageRecord` per API call, for offline billing reconciliation.
+///
+/// Write failures are logged and otherwise ignored, so they never fail a call.
+/// Cloning a `UsageLog` shares its writer.
+pub struct UsageLog {
+    format: UsageLogFormat,
+    sink: Arc<Mutex<Sink>>,
+}
+
+struct Sink {
+    writer: Box<dyn Write + Send>,
+    needs_header: bool,
+}
+
+impl UsageLog {
+    /// Creates a `UsageLog` writing to the given writer.
+    ///
+    /// # Arguments
+    /// * `writer` - The writer receiving the records.
+    /// * `format` - The format of the records. CSV output starts with a header line.
+    pub fn new(writer: impl Write + Send + 'static, format: UsageLogFormat) -> Self {
+        Self::with_sink(Box::new(writer), format, true)
+    }
+
+    /// Creates a `UsageLog` appending to the file at the given path, creating it if needed.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    /// * `format` - The format of the records. CSV files get a header line when they are empty.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened.
+    pub fn file(path: impl AsRef<Path>, format: UsageLogFormat) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let empty = file.metadata()?.len() == 0;
+        Ok(Self::with_sink(Box::new(file), format, empty))
+    }
+
+    fn with_sink(writer: Box<dyn Write + Send>, format: UsageLogFormat, empty: bool) -> Self {
+        Self {
+            format,
+            sink: Arc::new(Mutex::new(Sink {
+                writer,
+                needs_header: empty && format == UsageLogFormat::Csv,
+            })),
+        }
+    }
+
+    /// Appends a record to the log.
+    pub(crate) fn write(&self, record: &UsageRecord) {
+        let line = match self.format {
+            UsageLogFormat::JsonLines => serde_json::to_string(record).unwrap_or_default(),
+            UsageLogFormat::Csv => record.to_csv(),
+        };
+        let mut sink = self.sink.lock().unwrap_or_else(|err| err.into_inner());
+        let mut output = String::new();
+        if sink.needs_header {
+            output.push_str(CSV_HEADER);
+            output.push('\n');
+        }
+        output.push_str(&line);
+        output.push('\n');
+        match sink
+            .writer
+            .write_all(output.as_bytes())
+            .and_then(|_| sink.writer.flush())
+        {
+            Ok(()) => sink.needs_header = false,
+            Err(err) => log::warn!("Failed to write the usage log: {err}"),
+        }
+    }
+}
+
+impl std::fmt::Debug for UsageLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UsageLog")
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_csv_records() {
+        let buffer = Buffer::default();
+        let log = UsageLog::new(buffer.clone(), UsageLogFormat::Csv);
+        let mut record = UsageRecord::new(
+            "/chat/completions",
+            "llama3-8b-8192",
+            Duration::from_millis(42),
+        );
+        record.timestamp_ms = 1718000000000;
+        record.status = Some(200);
+        record.prompt_tokens = Some(10);
+        record.request_id = Some("req,1".to_string());
+        log.write(&record);
+        log.write(&record);
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "1718000000000,/chat/completions,llama3-8b-8192,200,10,,42,\"req,1\""
+        );
+    }
+}
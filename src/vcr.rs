@@ -0,0 +1,263 @@
+use crate::GroqError;
+#[cfg(feature = "blocking")]
+use reqwest::blocking::{Client, Request, Response};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client as AClient, Request as ARequest, Response as AResponse, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Response headers that describe the original transfer rather than the recorded body.
+const SKIPPED_HEADERS: [&str; 4] = [
+    "content-length",
+    "content-encoding",
+    "transfer-encoding",
+    "set-cookie",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Represents what a `Cassette` does with the requests it sees.
+///
+/// - `Record`: Requests are sent to the API and every request/response pair is saved to the cassette file.
+/// - `Replay`: Requests are answered from the cassette file without network access.
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+#[derive(Debug, Clone)]
+/// A cassette file recording API interactions, so integration tests can replay them deterministically.
+///
+/// In replay mode a request is answered by the first unused interaction with the same method, URL and JSON body,
+/// whatever the order of its keys (multipart bodies, such as audio uploads, are matched by method and URL only). Requests without a recorded
+/// interaction fail with `GroqError::CassetteMiss`. Request headers, including the API key, are never recorded, and an
+/// API key sent in the URL is redacted.
+/// Cloning a `Cassette` shares its state.
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Arc<Mutex<CassetteState>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    #[serde(skip)]
+    used: Vec<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    url: String,
+    request_body: Option<String>,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Cassette {
+    /// Creates a `Cassette` recording to the given file, replacing its previous content.
+    ///
+    /// # Arguments
+    /// * `path` - The cassette file, written after every recorded interaction.
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode: CassetteMode::Record,
+            state: Arc::new(Mutex::new(CassetteState::default())),
+        }
+    }
+
+    /// Creates a `Cassette` replaying the interactions of the given file.
+    ///
+    /// # Arguments
+    /// * `path` - The cassette file, as written by a recording `Cassette`.
+    pub fn replay(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(&path)?;
+        let mut state: CassetteState = serde_json::from_str(&content)?;
+        state.used = vec![false; state.interactions.len()];
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            mode: CassetteMode::Replay,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// Creates a `Cassette` replaying the given file if it exists, or recording to it otherwise.
+    ///
+    /// # Arguments
+    /// * `path` - The cassette file.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        if path.as_ref().exists() {
+            Self::replay(path)
+        } else {
+            Ok(Self::record(path))
+        }
+    }
+
+    /// Returns whether this cassette records or replays interactions.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Sends a request through the cassette with the async client.
+    ///
+    /// The interaction is recorded and matched under `url`, the URL of the request with any API key redacted.
+    pub(crate) async fn execute_async(
+        &self,
+        client: &AClient,
+        request: ARequest,
+        url: String,
+    ) -> Result<reqwest::Result<AResponse>, GroqError> {
+        let method = request.method().to_string();
+        let request_body = body_text(request.body().and_then(|body| body.as_bytes()));
+        if self.mode == CassetteMode::Replay {
+            let (status, headers, body) = self.find(&method, &url, request_body.as_deref())?;
+            return Ok(Ok(AResponse::from(response(status, headers, body))));
+        }
+
+        let result = async {
+            let res = client.execute(request).await?;
+            let status = res.status();
+            let headers = res.headers().clone();
+            Ok((status, headers, res.bytes().await?.to_vec()))
+        }
+        .await;
+        Ok(result.map(|(status, headers, body)| {
+            let headers = self.save(method, url, request_body, status, &headers, &body);
+            AResponse::from(response(status, headers, body))
+        }))
+    }
+
+    #[cfg(feature = "blocking")]
+    /// Sends a request through the cassette with the blocking client.
+    ///
+    /// The interaction is recorded and matched under `url`, the URL of the request with any API key redacted.
+    pub(crate) fn execute(
+        &self,
+        client: &Client,
+        request: Request,
+        url: String,
+    ) -> Result<reqwest::Result<Response>, GroqError> {
+        let method = request.method().to_string();
+        let request_body = body_text(request.body().and_then(|body| body.as_bytes()));
+        if self.mode == CassetteMode::Replay {
+            let (status, headers, body) = self.find(&method, &url, request_body.as_deref())?;
+            return Ok(Ok(Response::from(response(status, headers, body))));
+        }
+
+        let result = client.execute(request).and_then(|res| {
+            let status = res.status();
+            let headers = res.headers().clone();
+            Ok((status, headers, res.bytes()?.to_vec()))
+        });
+        Ok(result.map(|(status, headers, body)| {
+            let headers = self.save(method, url, request_body, status, &headers, &body);
+            Response::from(response(status, headers, body))
+        }))
+    }
+
+    /// Takes the first unused interaction matching the request.
+    fn find(
+        &self,
+        method: &str,
+        url: &str,
+        request_body: Option<&str>,
+    ) -> Result<(StatusCode, HeaderMap, Vec<u8>), GroqError> {
+        let request_json = request_body.and_then(|body| serde_json::from_str::<Value>(body).ok());
+        let same_body = |recorded: Option<&str>| match (&request_json, recorded) {
+            (Some(request), Some(recorded)) => {
+                serde_json::from_str::<Value>(recorded).is_ok_and(|recorded| &recorded == request)
+            }
+            _ => recorded == request_body,
+        };
+        let mut state = self.state();
+        let CassetteState { interactions, used } = &mut *state;
+        let index = interactions
+            .iter()
+            .enumerate()
+            .position(|(index, interaction)| {
+                !used[index]
+                    && interaction.method == method
+                    && interaction.url == url
+                    && (request_body.is_none() || same_body(interaction.request_body.as_deref()))
+            })
+            .ok_or_else(|| GroqError::CassetteMiss {
+                method: method.to_string(),
+                url: url.to_string(),
+            })?;
+        used[index] = true;
+
+        let interaction = &interactions[index];
+        let status =
+            StatusCode::from_u16(interaction.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let headers = interaction
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+        Ok((status, headers, interaction.body.clone().into_bytes()))
+    }
+
+    /// Appends an interaction to the cassette file and returns the headers of the response to rebuild.
+    fn save(
+        &self,
+        method: String,
+        url: String,
+        request_body: Option<String>,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> HeaderMap {
+        let headers: HeaderMap = headers
+            .iter()
+            .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let mut state = self.state();
+        state.interactions.push(Interaction {
+            method,
+            url,
+            request_body,
+            status: status.as_u16(),
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: String::from_utf8_lossy(body).into_owned(),
+        });
+        let written = serde_json::to_string_pretty(&*state)
+            .map_err(std::io::Error::from)
+            .and_then(|content| std::fs::write(&self.path, content));
+        if let Err(err) = written {
+            log::warn!("Failed to write cassette {}: {err}", self.path.display());
+        }
+        headers
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, CassetteState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Returns the request body if it is text, which excludes multipart uploads.
+fn body_text(body: Option<&[u8]>) -> Option<String> {
+    body.and_then(|body| std::str::from_utf8(body).ok())
+        .map(str::to_string)
+}
+
+fn response(status: StatusCode, headers: HeaderMap, body: Vec<u8>) -> http::Response<Vec<u8>> {
+    let mut response = http::Response::new(body);
+    *response.status_mut() = status;
+    *response.headers_mut() = headers;
+    response
+}
@@ -0,0 +1,238 @@
+use crate::models::WHISPER_LARGE_V3_TURBO;
+use crate::{
+    AudioApi, ChatApi, GroqChat, GroqError, MessageContent, SpeechApi, SpeechToTextRequest,
+    TextToSpeechRequest,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Represents one turn of a `VoiceSession`.
+///
+/// - `transcript`: The text transcribed from the audio of the user.
+/// - `text`: The answer of the model.
+/// - `audio`: The answer of the model, spoken.
+pub struct VoiceReply {
+    pub transcript: String,
+    pub text: String,
+    pub audio: Vec<u8>,
+}
+
+/// A spoken conversation: the audio of the user is transcribed, answered by a `GroqChat`, and the answer is spoken.
+///
+/// The conversation is managed by the inner `GroqChat`, so its history, trimming and compaction apply to the spoken
+/// turns. Audio is transcribed with `whisper-large-v3-turbo` and spoken by `playai-tts` with the `Fritz-PlayAI` voice
+/// in WAV by default.
+///
+/// ```no_run
+/// use groq_api_rust::{AsyncGroqClient, VoiceSession};
+///
+/// # async fn example() -> Result<(), groq_api_rust::GroqError> {
+/// let client = AsyncGroqClient::new("my_api_key".to_string(), None).await;
+/// let mut session = VoiceSession::new(&client, "llama-3.3-70b-versatile")
+///     .system_prompt("You are a voice assistant. Answer in one or two short sentences.");
+/// let reply = session.respond(std::fs::read("question.wav").unwrap()).await?;
+/// std::fs::write("answer.wav", reply.audio).unwrap();
+/// # Ok(())
+/// # }
+/// ```
+pub struct VoiceSession<'a> {
+    chat: GroqChat<'a>,
+    audio: &'a dyn AudioApi,
+    speech: &'a dyn SpeechApi,
+    transcription_model: String,
+    language: Option<String>,
+    speech_model: String,
+    voice: String,
+    audio_format: String,
+}
+
+impl<'a> VoiceSession<'a> {
+    /// Creates a new `VoiceSession` sending every request with the same client.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests, such as an `AsyncGroqClient`.
+    /// * `model` - The model answering the user.
+    pub fn new<C: ChatApi + AudioApi + SpeechApi>(client: &'a C, model: impl Into<String>) -> Self {
+        Self::from_chat(GroqChat::new(client, model), client, client)
+    }
+
+    /// Creates a new `VoiceSession` continuing an existing conversation.
+    ///
+    /// # Arguments
+    /// * `chat` - The conversation answering the user.
+    /// * `audio` - The client transcribing the audio of the user.
+    /// * `speech` - The client speaking the answers.
+    pub fn from_chat(
+        chat: GroqChat<'a>,
+        audio: &'a dyn AudioApi,
+        speech: &'a dyn SpeechApi,
+    ) -> Self {
+        Self {
+            chat,
+            audio,
+            speech,
+            transcription_model: WHISPER_LARGE_V3_TURBO.to_string(),
+            language: None,
+            speech_model: "playai-tts".to_string(),
+            voice: "Fritz-PlayAI".to_string(),
+            audio_format: "wav".to_string(),
+        }
+    }
+
+    /// Sets the system prompt of the conversation.
+    ///
+    /// # Arguments
+    /// * `prompt` - The content of the system message.
+    pub fn system_prompt(mut self, prompt: impl Into<MessageContent>) -> Self {
+        self.chat = self.chat.system_prompt(prompt);
+        self
+    }
+
+    /// Sets the model transcribing the audio of the user.
+    ///
+    /// # Arguments
+    /// * `model` - A speech recognition model.
+    pub fn transcription_model(mut self, model: impl Into<String>) -> Self {
+        self.transcription_model = model.into();
+        self
+    }
+
+    /// Sets the language spoken by the user, improving the accuracy and latency of the transcription.
+    ///
+    /// # Arguments
+    /// * `language` - The ISO-639-1 code of the language.
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets the model and voice speaking the answers.
+    ///
+    /// # Arguments
+    /// * `model` - A speech synthesis model.
+    /// * `voice` - A voice of the model.
+    pub fn voice(mut self, model: impl Into<String>, voice: impl Into<String>) -> Self {
+        self.speech_model = model.into();
+        self.voice = voice.into();
+        self
+    }
+
+    /// Sets the audio format of the spoken answers.
+    ///
+    /// # Arguments
+    /// * `format` - The format, such as `wav`, `mp3` or `flac`.
+    pub fn audio_format(mut self, format: impl Into<String>) -> Self {
+        self.audio_format = format.into();
+        self
+    }
+
+    /// Returns the conversation, holding the transcripts and answers of the previous turns.
+    pub fn chat(&self) -> &GroqChat<'a> {
+        &self.chat
+    }
+
+    /// Answers one spoken turn of the user.
+    ///
+    /// # Arguments
+    /// * `audio` - The audio file of the user speaking.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `GroqError` of the transcription, chat or speech request. A failed speech request leaves the turn
+    /// in the conversation.
+    pub async fn respond(&mut self, audio: Vec<u8>) -> Result<VoiceReply, GroqError> {
+        let mut request = SpeechToTextRequest::new(audio).model(self.transcription_model.as_str());
+        if let Some(language) = &self.language {
+            request = request.language(language);
+        }
+        let transcript = self.audio.speech_to_text(request).await?.text;
+        let text = self.chat.send(&transcript).await?;
+        let request = TextToSpeechRequest::new(self.speech_model.as_str(), &text, &self.voice)
+            .response_format(&self.audio_format);
+        let audio = self.speech.text_to_speech(request).await?;
+        Ok(VoiceReply {
+            transcript,
+            text,
+            audio,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{no_choices, reply, server_error};
+    use crate::{ChatCompletionRequest, ChatCompletionResponse, SpeechToTextResponse};
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    /// Hears every audio as the bytes it holds, answers with the number of messages or nothing to "Quiet", and speaks
+    /// text as its bytes, failing on empty text.
+    struct Loopback;
+
+    #[async_trait]
+    impl ChatApi for Loopback {
+        async fn chat_completion(
+            &self,
+            request: ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, GroqError> {
+            match request.messages.last().unwrap().content.as_str() {
+                "Quiet" => no_choices(&request.model),
+                _ => reply(
+                    &request.model,
+                    &format!("{} messages", request.messages.len()),
+                ),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AudioApi for Loopback {
+        async fn speech_to_text(
+            &self,
+            request: SpeechToTextRequest,
+        ) -> Result<SpeechToTextResponse, GroqError> {
+            assert_eq!(request.model.as_deref(), Some(WHISPER_LARGE_V3_TURBO));
+            let text = String::from_utf8(request.file).unwrap();
+            crate::message::deserialize(json!({ "text": text }))
+        }
+    }
+
+    #[async_trait]
+    impl SpeechApi for Loopback {
+        async fn text_to_speech(&self, request: TextToSpeechRequest) -> Result<Vec<u8>, GroqError> {
+            assert_eq!(request.to_json()["response_format"], "wav");
+            if request.input.is_empty() {
+                return Err(server_error());
+            }
+            Ok(request.input.into_bytes())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_voice_session_keeps_conversation() {
+        let mut session =
+            VoiceSession::new(&Loopback, "llama-3.3-70b-versatile").system_prompt("Be brief");
+        let first = session.respond(b"Hi".to_vec()).await.unwrap();
+        assert_eq!(first.transcript, "Hi");
+        assert_eq!(first.text, "2 messages");
+        assert_eq!(first.audio, b"2 messages");
+
+        let second = session.respond(b"And now?".to_vec()).await.unwrap();
+        assert_eq!(second.text, "4 messages");
+        assert_eq!(session.chat().history().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_voice_session_keeps_turns_failing_to_speak() {
+        let mut session = VoiceSession::new(&Loopback, "llama-3.3-70b-versatile");
+        assert!(matches!(
+            session.respond(b"Quiet".to_vec()).await,
+            Err(GroqError::ServerError { status: 500, .. })
+        ));
+        assert_eq!(session.chat().history().len(), 2);
+        assert_eq!(session.chat().history()[0].content, "Quiet");
+
+        let reply = session.respond(b"Hi".to_vec()).await.unwrap();
+        assert_eq!(reply.text, "3 messages");
+    }
+}